@@ -0,0 +1,106 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use s3_path::generators::{realistic_keys, KeyProfile};
+use s3_path::{s3_path_buf, S3PathBuf};
+
+const KEYS: usize = 1000;
+
+fn paths() -> Vec<S3PathBuf> {
+    realistic_keys(42, KeyProfile::default())
+        .take(KEYS)
+        .collect()
+}
+
+fn construction(c: &mut Criterion) {
+    let components = paths()
+        .iter()
+        .map(|path| path.components().map(str::to_owned).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("construction");
+    group.bench_function("push", |b| {
+        b.iter_batched(
+            || components.clone(),
+            |components| {
+                for path_components in components {
+                    let mut path = S3PathBuf::new();
+                    for component in path_components {
+                        path.push(component).unwrap();
+                    }
+                    black_box(path);
+                }
+            },
+            BatchSize::SmallInput,
+        );
+    });
+    group.bench_function("s3_path_buf_macro", |b| {
+        b.iter(|| black_box(s3_path_buf!("data", "2024", "01", "report.csv").unwrap()));
+    });
+    group.finish();
+}
+
+fn parsing(c: &mut Criterion) {
+    let keys = paths().iter().map(ToString::to_string).collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("parsing");
+    group.bench_function("try_from_str", |b| {
+        b.iter(|| {
+            for key in &keys {
+                black_box(S3PathBuf::try_from_str(black_box(key)).unwrap());
+            }
+        });
+    });
+    group.bench_function("strict_parse", |b| {
+        b.iter(|| {
+            for key in &keys {
+                black_box(S3PathBuf::strict_parse(black_box(key)).unwrap());
+            }
+        });
+    });
+    group.bench_function("validate_keys", |b| {
+        b.iter(|| {
+            black_box(s3_path::batch::validate_keys(
+                keys.iter().map(String::as_str),
+            ))
+        });
+    });
+    group.finish();
+}
+
+fn display(c: &mut Criterion) {
+    let paths = paths();
+
+    let mut group = c.benchmark_group("display");
+    group.bench_function("to_string", |b| {
+        b.iter(|| {
+            for path in &paths {
+                black_box(path.to_string());
+            }
+        });
+    });
+    group.finish();
+}
+
+fn comparisons(c: &mut Criterion) {
+    let paths = paths();
+    let clones = paths.clone();
+
+    let mut group = c.benchmark_group("comparisons");
+    group.bench_function("eq", |b| {
+        b.iter(|| {
+            for (left, right) in paths.iter().zip(&clones) {
+                black_box(left == right);
+            }
+        });
+    });
+    group.bench_function("fingerprint", |b| {
+        b.iter(|| {
+            for path in &paths {
+                black_box(path.fingerprint());
+            }
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(benches, construction, parsing, display, comparisons);
+criterion_main!(benches);
@@ -0,0 +1,50 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use s3_path::{validate_component_fast, S3PathBuf};
+
+fn keys() -> Vec<String> {
+    (0..1000)
+        .map(|i| {
+            format!(
+                "inventory/2024/{:02}/{:02}/object-{i:06}.parquet",
+                i % 12 + 1,
+                i % 28 + 1
+            )
+        })
+        .collect()
+}
+
+fn try_from_str(c: &mut Criterion) {
+    let keys = keys();
+    let bytes = keys.iter().map(String::len).sum::<usize>();
+
+    let mut group = c.benchmark_group("validation");
+    group.throughput(Throughput::Bytes(bytes as u64));
+    group.bench_function("try_from_str", |b| {
+        b.iter(|| {
+            for key in &keys {
+                black_box(S3PathBuf::try_from_str(black_box(key)).unwrap());
+            }
+        });
+    });
+    let key = "a".repeat(1024);
+    group.throughput(Throughput::Bytes(key.len() as u64));
+    group.bench_function("try_from_str_long_component", |b| {
+        b.iter(|| black_box(S3PathBuf::try_from_str(black_box(&key)).unwrap()));
+    });
+    group.finish();
+}
+
+fn validate_component(c: &mut Criterion) {
+    let mut group = c.benchmark_group("validation");
+    for len in [16, 64, 1024] {
+        let component = "a1-_.Z".repeat(len).split_off(5 * len);
+        group.throughput(Throughput::Bytes(component.len() as u64));
+        group.bench_function(format!("validate_component_fast_{len}"), |b| {
+            b.iter(|| validate_component_fast(black_box(&component)).unwrap());
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, try_from_str, validate_component);
+criterion_main!(benches);
@@ -0,0 +1,232 @@
+//! Paths annotated with the meaning of each of their components.
+//!
+//! A [`Layout`] assigns a [`ComponentTag`] to each component specification of a
+//! [`KeyGrammar`]. Annotating a path using a layout yields an [`AnnotatedS3Path`], offering
+//! accessors like [`AnnotatedS3Path::tenant`] instead of magic component indices.
+//!
+//! ```
+//! use s3_path::annotate::{ComponentTag, Layout};
+//! use s3_path::grammar::KeyGrammar;
+//! use s3_path::S3PathBuf;
+//!
+//! let layout = Layout::new(
+//!     KeyGrammar::parse("tenants/{any}/{date}/{any}").unwrap(),
+//!     [
+//!         ComponentTag::Opaque,
+//!         ComponentTag::Tenant,
+//!         ComponentTag::Date,
+//!         ComponentTag::FileName,
+//!     ],
+//! )
+//! .unwrap();
+//!
+//! let path = S3PathBuf::try_from_str("tenants/acme/2024-01-31/report.csv").unwrap();
+//! let annotated = layout.annotate(&path).unwrap();
+//! assert_eq!(annotated.tenant(), Some("acme"));
+//! assert_eq!(annotated.file_name(), Some("report.csv"));
+//! ```
+
+use crate::error::{GrammarViolation, InvalidKeyGrammar};
+use crate::grammar::{ComponentSpec, KeyGrammar};
+use crate::{S3Path, S3PathBuf};
+
+/// The meaning of a single path component.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ComponentTag {
+    /// Identifies the tenant owning the object.
+    Tenant,
+
+    /// A date, or a part of it (like a year in `YYYY/MM/DD` partitions).
+    Date,
+
+    /// A shard or partition number.
+    Shard,
+
+    /// The name of the object itself.
+    FileName,
+
+    /// Anything else, e.g. fixed prefixes.
+    Opaque,
+}
+
+/// A [`KeyGrammar`], together with a tag for each of its component specifications.
+#[derive(Debug, Clone)]
+pub struct Layout {
+    grammar: KeyGrammar,
+    tags: Vec<ComponentTag>,
+}
+
+impl Layout {
+    /// Creates a layout tagging the components matched by `grammar` with `tags`, in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when the number of tags differs from the number of component specifications.
+    pub fn new(
+        grammar: KeyGrammar,
+        tags: impl IntoIterator<Item = ComponentTag>,
+    ) -> Result<Self, InvalidKeyGrammar> {
+        let tags = tags.into_iter().collect::<Vec<_>>();
+        if tags.len() != grammar.specs().len() {
+            return Err(InvalidKeyGrammar {
+                reason: format!(
+                    "Expected {} tags, got {}",
+                    grammar.specs().len(),
+                    tags.len()
+                ),
+                grammar: grammar.to_string(),
+            });
+        }
+        Ok(Self { grammar, tags })
+    }
+
+    /// Creates a layout inferring tags from `grammar`: date placeholders (`{date}`, `{year}`,
+    /// `{month}` and `{day}`) are tagged [`ComponentTag::Date`], the last component
+    /// [`ComponentTag::FileName`] and all others [`ComponentTag::Opaque`].
+    #[must_use]
+    pub fn infer(grammar: KeyGrammar) -> Self {
+        let last = grammar.specs().len().saturating_sub(1);
+        let tags = grammar
+            .specs()
+            .iter()
+            .enumerate()
+            .map(|(i, spec)| match spec {
+                ComponentSpec::Date
+                | ComponentSpec::Year
+                | ComponentSpec::Month
+                | ComponentSpec::Day => ComponentTag::Date,
+                _ if i == last => ComponentTag::FileName,
+                _ => ComponentTag::Opaque,
+            })
+            .collect();
+        Self { grammar, tags }
+    }
+
+    /// Returns the grammar of this layout.
+    #[must_use]
+    pub fn grammar(&self) -> &KeyGrammar {
+        &self.grammar
+    }
+
+    /// Returns the tags of this layout, one per component specification.
+    #[must_use]
+    pub fn tags(&self) -> &[ComponentTag] {
+        &self.tags
+    }
+
+    /// Checks `path` against the grammar of this layout, tagging its components.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when `path` does not conform to the grammar of this layout.
+    pub fn annotate(&self, path: &S3Path<'_>) -> Result<AnnotatedS3Path, GrammarViolation> {
+        self.grammar.check(path)?;
+        Ok(AnnotatedS3Path {
+            path: path.to_owned(),
+            tags: self.tags.clone(),
+        })
+    }
+}
+
+/// A path, with each of its components tagged with its meaning. Created by [`Layout::annotate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnnotatedS3Path {
+    path: S3PathBuf,
+    tags: Vec<ComponentTag>,
+}
+
+impl AnnotatedS3Path {
+    /// Returns the annotated path.
+    #[must_use]
+    pub fn path(&self) -> &S3PathBuf {
+        &self.path
+    }
+
+    /// Converts into the annotated path, dropping all tags.
+    #[must_use]
+    pub fn into_path(self) -> S3PathBuf {
+        self.path
+    }
+
+    /// Returns the tag of the component at `index`, or None if the index is out of bounds.
+    #[must_use]
+    pub fn tag(&self, index: usize) -> Option<ComponentTag> {
+        self.tags.get(index).copied()
+    }
+
+    /// Returns an iterator over all components, together with their tags.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, ComponentTag)> {
+        self.path.components().zip(self.tags.iter().copied())
+    }
+
+    /// Returns an iterator over all components tagged with `tag`, in order.
+    pub fn tagged(&self, tag: ComponentTag) -> impl Iterator<Item = &str> {
+        self.iter()
+            .filter(move |(_, t)| *t == tag)
+            .map(|(component, _)| component)
+    }
+
+    /// Returns the first component tagged with [`ComponentTag::Tenant`].
+    #[must_use]
+    pub fn tenant(&self) -> Option<&str> {
+        self.tagged(ComponentTag::Tenant).next()
+    }
+
+    /// Returns the first component tagged with [`ComponentTag::Date`]. Use
+    /// [`tagged`](Self::tagged) to get all parts of dates spread over multiple components.
+    #[must_use]
+    pub fn date(&self) -> Option<&str> {
+        self.tagged(ComponentTag::Date).next()
+    }
+
+    /// Returns the first component tagged with [`ComponentTag::Shard`].
+    #[must_use]
+    pub fn shard(&self) -> Option<&str> {
+        self.tagged(ComponentTag::Shard).next()
+    }
+
+    /// Returns the first component tagged with [`ComponentTag::FileName`].
+    #[must_use]
+    pub fn file_name(&self) -> Option<&str> {
+        self.tagged(ComponentTag::FileName).next()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::annotate::{ComponentTag, Layout};
+    use crate::grammar::KeyGrammar;
+    use crate::S3PathBuf;
+    use assertr::prelude::*;
+
+    #[test]
+    fn new_rejects_mismatching_tag_count() {
+        let grammar = KeyGrammar::parse("a/{any}").unwrap();
+        assert_that(Layout::new(grammar, [ComponentTag::Opaque])).is_err();
+    }
+
+    #[test]
+    fn infer_tags_dates_and_file_name() {
+        let layout = Layout::infer(KeyGrammar::parse("logs/{year}/{month}/{day}/{any}").unwrap());
+        let path = S3PathBuf::try_from_str("logs/2024/01/31/app.log").unwrap();
+        let annotated = layout.annotate(&path).unwrap();
+
+        assert_that(annotated.tagged(ComponentTag::Date).collect::<Vec<_>>())
+            .contains_exactly(["2024", "01", "31"]);
+        assert_that(annotated.date()).is_some().is_equal_to("2024");
+        assert_that(annotated.file_name())
+            .is_some()
+            .is_equal_to("app.log");
+        assert_that(annotated.tenant()).is_none();
+        assert_that(annotated.tag(0))
+            .is_some()
+            .is_equal_to(ComponentTag::Opaque);
+    }
+
+    #[test]
+    fn annotate_rejects_non_conforming_paths() {
+        let layout = Layout::infer(KeyGrammar::parse("logs/{date}").unwrap());
+        let path = S3PathBuf::try_from_str("logs/today").unwrap();
+        assert_that(layout.annotate(&path)).is_err();
+    }
+}
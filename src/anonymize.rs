@@ -0,0 +1,127 @@
+//! Anonymization of paths for sharing bucket layouts without leaking identifiers.
+
+use crate::{hash, S3Path, S3PathBuf};
+use std::borrow::Cow;
+
+/// Describes which components of a path get anonymized and how.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnonymizationPolicy {
+    /// Secret key (salt) of the keyed hash replacing anonymized components.
+    ///
+    /// Without knowing the key, anonymized components can neither be reversed nor reproduced by
+    /// hashing guessed identifiers. Keep it secret and reuse it to keep replacements stable.
+    pub key: [u8; 16],
+
+    /// Indices of the components to anonymize. Indices exceeding a path's length are ignored.
+    pub positions: Vec<usize>,
+
+    /// Whether to keep the extension (everything after the last '.') of anonymized components.
+    pub preserve_extensions: bool,
+}
+
+impl S3Path<'_> {
+    /// Returns a copy of this path in which all components selected by `policy` are replaced by
+    /// a salted hash (16 hex digits) of their original value.
+    ///
+    /// The structure of the path (its depth and, if requested, file extensions) is preserved,
+    /// and equal components are always replaced by equal hashes when using the same key.
+    ///
+    /// ```
+    /// use s3_path::anonymize::AnonymizationPolicy;
+    /// use s3_path::s3_path;
+    ///
+    /// let policy = AnonymizationPolicy {
+    ///     key: *b"my-secret-salt!!",
+    ///     positions: vec![1, 3],
+    ///     preserve_extensions: true,
+    /// };
+    /// let path = s3_path!("tenants", "acme", "exports", "customers.csv").unwrap();
+    /// let anonymized = path.anonymize(&policy).to_string();
+    ///
+    /// assert!(anonymized.starts_with("tenants/"));
+    /// assert!(anonymized.ends_with(".csv"));
+    /// assert!(!anonymized.contains("acme"));
+    /// ```
+    #[must_use]
+    pub fn anonymize(&self, policy: &AnonymizationPolicy) -> S3PathBuf {
+        let components = self
+            .0
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                if policy.positions.contains(&i) {
+                    Cow::Owned(anonymize_component(c, policy))
+                } else {
+                    Cow::Owned(c.to_string())
+                }
+            })
+            .collect();
+        S3PathBuf { components }
+    }
+}
+
+fn anonymize_component(component: &str, policy: &AnonymizationPolicy) -> String {
+    // A leading '.' (as in ".gitignore") does not start an extension.
+    let (stem, extension) = match component.rfind('.') {
+        Some(i) if i > 0 && policy.preserve_extensions => component.split_at(i),
+        _ => (component, ""),
+    };
+    let hash = hash::siphash24(&policy.key, stem.as_bytes());
+    // Hex digits and a tail of an already valid component keep the result valid.
+    format!("{hash:016x}{extension}")
+}
+
+#[cfg(test)]
+mod test {
+    use crate::anonymize::AnonymizationPolicy;
+    use crate::S3PathBuf;
+    use assertr::prelude::*;
+
+    fn policy(positions: Vec<usize>, preserve_extensions: bool) -> AnonymizationPolicy {
+        AnonymizationPolicy {
+            key: [7; 16],
+            positions,
+            preserve_extensions,
+        }
+    }
+
+    #[test]
+    fn replaces_only_selected_components() {
+        let path = S3PathBuf::try_from(["foo", "bar", "baz"]).unwrap();
+        let anonymized = path.anonymize(&policy(vec![1, 5], false));
+
+        assert_that(anonymized.len()).is_equal_to(3);
+        assert_that(anonymized.get(0)).is_some().is_equal_to("foo");
+        assert_that(anonymized.get(1).unwrap().len()).is_equal_to(16);
+        assert_that(anonymized.get(1))
+            .is_some()
+            .is_not_equal_to("bar");
+        assert_that(anonymized.get(2)).is_some().is_equal_to("baz");
+    }
+
+    #[test]
+    fn is_deterministic_per_key() {
+        let path = S3PathBuf::try_from(["foo", "bar"]).unwrap();
+        let first = path.anonymize(&policy(vec![0, 1], false));
+        let second = path.anonymize(&policy(vec![0, 1], false));
+        let other_key = path.anonymize(&AnonymizationPolicy {
+            key: [8; 16],
+            ..policy(vec![0, 1], false)
+        });
+
+        assert_that(&first).is_equal_to(&second);
+        assert_that(&first).is_not_equal_to(&other_key);
+    }
+
+    #[test]
+    fn preserves_extensions_when_requested() {
+        let path = S3PathBuf::try_from(["report.tar.gz", ".hidden"]).unwrap();
+
+        let anonymized = path.anonymize(&policy(vec![0, 1], true));
+        assert_that(anonymized.get(0).unwrap().ends_with(".gz")).is_true();
+        assert_that(anonymized.get(1).unwrap().len()).is_equal_to(16);
+
+        let anonymized = path.anonymize(&policy(vec![0], false));
+        assert_that(anonymized.get(0).unwrap().contains('.')).is_false();
+    }
+}
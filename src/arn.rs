@@ -0,0 +1,152 @@
+//! Matching keys against resource patterns of IAM and bucket policies.
+//!
+//! Policy statements name S3 resources by ARN, e.g. `arn:aws:s3:::my-bucket/tenants/*`. In these
+//! patterns, `*` matches any sequence of characters, including '/', and `?` matches any single
+//! character. An ARN without a '/' names the bucket itself, not the objects within it.
+//!
+//! ```
+//! use s3_path::arn::ResourcePattern;
+//! use s3_path::S3PathBuf;
+//!
+//! let pattern = ResourcePattern::parse("arn:aws:s3:::my-bucket/tenants/*/exports/*").unwrap();
+//!
+//! let key = S3PathBuf::try_from_str("tenants/acme/exports/2024/data.csv").unwrap();
+//! assert!(pattern.matches("my-bucket", &key));
+//! assert!(!pattern.matches("other-bucket", &key));
+//! ```
+
+use crate::error::InvalidResourcePattern;
+use crate::{glob, S3Path};
+
+/// A parsed S3 resource ARN pattern. See the [module documentation](crate::arn).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourcePattern {
+    pattern: String,
+    partition: String,
+    /// Everything after `arn:<partition>:s3:::`, e.g. `bucket/prefix/*`.
+    resource: String,
+}
+
+impl ResourcePattern {
+    /// Parses an S3 resource ARN pattern of the form `arn:<partition>:s3:::<bucket>[/<key>]`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when
+    /// - `pattern` is not an S3 ARN
+    /// - the resource is empty
+    /// - the pattern contains policy variables like `${aws:username}`, which can only be
+    ///   resolved by AWS
+    pub fn parse(pattern: &str) -> Result<Self, InvalidResourcePattern> {
+        let invalid = |reason: &str| InvalidResourcePattern {
+            pattern: pattern.to_string(),
+            reason: reason.to_string(),
+        };
+        let rest = pattern
+            .strip_prefix("arn:")
+            .ok_or_else(|| invalid("Not an ARN"))?;
+        let (partition, resource) = rest
+            .split_once(":s3:::")
+            .ok_or_else(|| invalid("Not an S3 resource ARN"))?;
+        if partition.is_empty() || partition.contains(':') {
+            return Err(invalid("Not an S3 resource ARN"));
+        }
+        if resource.is_empty() {
+            return Err(invalid("Empty resource"));
+        }
+        if resource.contains("${") {
+            return Err(invalid("Policy variables are not supported"));
+        }
+        Ok(Self {
+            pattern: pattern.to_string(),
+            partition: partition.to_string(),
+            resource: resource.to_string(),
+        })
+    }
+
+    /// Returns the partition of the ARN, e.g. `aws`.
+    #[must_use]
+    pub fn partition(&self) -> &str {
+        &self.partition
+    }
+
+    /// Returns true if this pattern covers the object `path` in `bucket`.
+    ///
+    /// The partition is not checked, as buckets do not carry their partition.
+    #[must_use]
+    pub fn matches(&self, bucket: &str, path: &S3Path<'_>) -> bool {
+        let resource = format!("{bucket}/{path}");
+        glob::matches_wildcards(self.resource.as_bytes(), resource.as_bytes())
+    }
+
+    /// Returns true if this pattern covers `bucket` itself, as required by bucket-level actions
+    /// like `s3:ListBucket`.
+    #[must_use]
+    pub fn matches_bucket(&self, bucket: &str) -> bool {
+        glob::matches_wildcards(self.resource.as_bytes(), bucket.as_bytes())
+    }
+}
+
+impl std::fmt::Display for ResourcePattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.pattern)
+    }
+}
+
+impl std::str::FromStr for ResourcePattern {
+    type Err = InvalidResourcePattern;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::arn::ResourcePattern;
+    use crate::S3PathBuf;
+    use assertr::prelude::*;
+
+    fn matches(pattern: &str, bucket: &str, key: &str) -> bool {
+        ResourcePattern::parse(pattern)
+            .unwrap()
+            .matches(bucket, &S3PathBuf::try_from_str(key).unwrap())
+    }
+
+    #[test]
+    fn parse_rejects_non_s3_arns() {
+        assert_that(ResourcePattern::parse("my-bucket/*")).is_err();
+        assert_that(ResourcePattern::parse("arn:aws:sqs:::queue")).is_err();
+        assert_that(ResourcePattern::parse("arn:aws:s3:::")).is_err();
+        assert_that(ResourcePattern::parse("arn:aws:s3:::b/${aws:username}/*")).is_err();
+        assert_that(
+            ResourcePattern::parse("arn:aws-cn:s3:::b/*")
+                .unwrap()
+                .partition(),
+        )
+        .is_equal_to("aws-cn");
+    }
+
+    #[test]
+    fn star_matches_across_slashes() {
+        assert_that(matches("arn:aws:s3:::b/*", "b", "a/b/c")).is_true();
+        assert_that(matches("arn:aws:s3:::b/a/*", "b", "a")).is_false();
+        assert_that(matches("arn:aws:s3:::*", "b", "a/b")).is_true();
+        assert_that(matches("arn:aws:s3:::b*/x", "bucket", "x")).is_true();
+    }
+
+    #[test]
+    fn question_mark_matches_single_character() {
+        assert_that(matches("arn:aws:s3:::b/log-?.txt", "b", "log-1.txt")).is_true();
+        assert_that(matches("arn:aws:s3:::b/log-?.txt", "b", "log-10.txt")).is_false();
+    }
+
+    #[test]
+    fn bucket_arns_match_buckets_only() {
+        let pattern = ResourcePattern::parse("arn:aws:s3:::b").unwrap();
+        assert_that(pattern.matches_bucket("b")).is_true();
+        assert_that(pattern.matches("b", &S3PathBuf::try_from_str("a").unwrap())).is_false();
+        let pattern = ResourcePattern::parse("arn:aws:s3:::b/*").unwrap();
+        assert_that(pattern.matches_bucket("b")).is_false();
+    }
+}
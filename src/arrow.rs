@@ -0,0 +1,275 @@
+//! Conversions between paths and Arrow arrays, as found in inventory reports and analytics
+//! outputs (e.g. read from Parquet files).
+
+use crate::batch;
+use crate::{S3Path, S3PathBuf};
+use arrow_array::builder::{ListBuilder, StringBuilder};
+use arrow_array::{GenericStringArray, ListArray, OffsetSizeTrait, StringArray, UInt32Array};
+use std::borrow::Cow;
+use std::fmt::Write;
+
+/// The result of parsing a column of keys using [`parse_string_array`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ParsedKeys {
+    /// One entry per array element. `None` for null and invalid keys.
+    pub paths: Vec<Option<S3PathBuf>>,
+
+    /// Indices of all invalid keys, in ascending order. Nulls are not considered invalid.
+    pub invalid: Vec<usize>,
+}
+
+/// Parses every key in `array`, using the rules of
+/// [`S3PathBuf::strict_parse`](crate::S3PathBuf::strict_parse).
+///
+/// All keys are validated in a single pass over the array, using
+/// [`validate_keys`](crate::batch::validate_keys), without allocating errors. Only valid keys are
+/// then split into paths, without validating them again.
+///
+/// ```
+/// use arrow_array::StringArray;
+/// use s3_path::arrow::parse_string_array;
+///
+/// let array = StringArray::from(vec![Some("foo/bar"), None, Some("foo//bar")]);
+/// let parsed = parse_string_array(&array);
+/// assert_eq!(parsed.paths[0].as_ref().unwrap().to_string(), "foo/bar");
+/// assert_eq!(parsed.paths[1], None);
+/// assert_eq!(parsed.invalid, [2]);
+/// ```
+pub fn parse_string_array<O: OffsetSizeTrait>(array: &GenericStringArray<O>) -> ParsedKeys {
+    // Nulls are validated as the (valid) empty key, and mapped to `None` below.
+    let report = batch::validate_keys(array.iter().map(Option::unwrap_or_default));
+
+    let mut invalid = report.invalid.iter().copied().peekable();
+    let paths = array
+        .iter()
+        .enumerate()
+        .map(|(index, key)| {
+            if invalid.next_if_eq(&index).is_some() {
+                return None;
+            }
+            key.map(|key| {
+                let mut path = S3PathBuf::new();
+                if !key.is_empty() {
+                    path.components = key.split('/').map(|c| Cow::Owned(c.to_owned())).collect();
+                }
+                path
+            })
+        })
+        .collect();
+
+    ParsedKeys {
+        paths,
+        invalid: report.invalid,
+    }
+}
+
+/// Builds an array of rendered keys from `paths`, without intermediate string allocations.
+///
+/// ```
+/// use s3_path::arrow::to_string_array;
+/// use s3_path::S3PathBuf;
+///
+/// let paths = ["foo/bar", "baz"].map(|key| S3PathBuf::try_from_str(key).unwrap());
+/// let array = to_string_array(&paths);
+/// assert_eq!(array.value(0), "foo/bar");
+/// ```
+pub fn to_string_array<'p, P: AsRef<S3Path<'p>>>(
+    paths: impl IntoIterator<Item = P>,
+) -> StringArray {
+    let mut builder = StringBuilder::new();
+    for path in paths {
+        // Writing into the builder never fails.
+        let _ = write!(builder, "{}", path.as_ref());
+        builder.append_value("");
+    }
+    builder.finish()
+}
+
+/// Builds an array holding the list of components of each path.
+///
+/// ```
+/// use arrow_array::{Array, StringArray};
+/// use s3_path::arrow::to_list_array;
+/// use s3_path::S3PathBuf;
+///
+/// let paths = ["foo/bar", "baz"].map(|key| S3PathBuf::try_from_str(key).unwrap());
+/// let array = to_list_array(&paths);
+/// let first = array.value(0);
+/// let components = first.as_any().downcast_ref::<StringArray>().unwrap();
+/// assert_eq!(components.value(1), "bar");
+/// ```
+pub fn to_list_array<'p, P: AsRef<S3Path<'p>>>(paths: impl IntoIterator<Item = P>) -> ListArray {
+    let mut builder = ListBuilder::new(StringBuilder::new());
+    for path in paths {
+        for c in path.as_ref().components() {
+            builder.values().append_value(c);
+        }
+        builder.append(true);
+    }
+    builder.finish()
+}
+
+/// Parses every non-null key of `array` using
+/// [`S3PathBuf::strict_parse`](crate::S3PathBuf::strict_parse) and applies `f` to it. Null and
+/// invalid keys, as well as keys for which `f` returns `None`, result in null.
+fn map_keys<O: OffsetSizeTrait, R, C: FromIterator<Option<R>>>(
+    array: &GenericStringArray<O>,
+    f: impl Fn(&S3Path<'_>) -> Option<R>,
+) -> C {
+    array
+        .iter()
+        .map(|key| {
+            let path = S3PathBuf::strict_parse(key?).ok()?;
+            f(&path)
+        })
+        .collect()
+}
+
+// The following kernels implement common key manipulations on whole columns. They are meant to
+// back scalar UDFs of query engines like DataFusion, so that SQL analyses share the semantics of
+// this crate.
+
+/// Returns the parent key of every key in `array`. The parent of a single-component key is the
+/// empty key. Null for the empty key and for null or invalid keys.
+pub fn key_parent<O: OffsetSizeTrait>(array: &GenericStringArray<O>) -> StringArray {
+    map_keys(array, |path| path.parent().map(|parent| parent.to_string()))
+}
+
+/// Returns the extension of the last component of every key in `array`: everything after its
+/// last '.', unless that '.' is the first character. Null for keys without extension and for
+/// null or invalid keys.
+pub fn key_extension<O: OffsetSizeTrait>(array: &GenericStringArray<O>) -> StringArray {
+    map_keys(array, |path| {
+        let last = path.last()?;
+        let dot = last.rfind('.').filter(|dot| *dot > 0)?;
+        Some(last[dot + 1..].to_owned())
+    })
+}
+
+/// Returns the number of components of every key in `array`. Null for null or invalid keys.
+pub fn key_depth<O: OffsetSizeTrait>(array: &GenericStringArray<O>) -> UInt32Array {
+    map_keys(array, |path| u32::try_from(path.len()).ok())
+}
+
+/// Returns the component at index `n` (starting at 0) of every key in `array`. Null for keys
+/// having fewer components and for null or invalid keys.
+pub fn key_component<O: OffsetSizeTrait>(array: &GenericStringArray<O>, n: usize) -> StringArray {
+    map_keys(array, |path| path.get(n).map(str::to_owned))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::arrow::{
+        key_component, key_depth, key_extension, key_parent, parse_string_array, to_list_array,
+        to_string_array,
+    };
+    use crate::S3PathBuf;
+    use arrow_array::{Array, LargeStringArray, StringArray};
+    use assertr::prelude::*;
+
+    #[test]
+    fn parses_valid_keys_and_reports_invalid_indices() {
+        let array = StringArray::from(vec![
+            Some("a/b"),
+            Some("/a"),
+            None,
+            Some(""),
+            Some("a/b c"),
+            Some("c"),
+        ]);
+        let parsed = parse_string_array(&array);
+
+        assert_that(parsed.invalid).contains_exactly([1, 4]);
+        assert_that(parsed.paths).is_equal_to(vec![
+            Some(S3PathBuf::try_from_str("a/b").unwrap()),
+            None,
+            None,
+            Some(S3PathBuf::new()),
+            None,
+            Some(S3PathBuf::try_from_str("c").unwrap()),
+        ]);
+    }
+
+    #[test]
+    fn parses_large_string_arrays() {
+        let array = LargeStringArray::from(vec!["a/b", "a/.."]);
+        let parsed = parse_string_array(&array);
+        assert_that(parsed.invalid).contains_exactly([1]);
+        assert_that(parsed.paths[0].is_some()).is_true();
+    }
+
+    #[test]
+    fn string_array_round_trips_through_parse() {
+        let paths = ["a/b", "", "c"].map(|key| S3PathBuf::try_from_str(key).unwrap());
+        let parsed = parse_string_array(&to_string_array(&paths));
+        assert_that(parsed.invalid).is_empty();
+        assert_that(parsed.paths).is_equal_to(paths.map(Some).to_vec());
+    }
+
+    #[test]
+    fn list_array_holds_components() {
+        let paths = ["a/b", "", "c"].map(|key| S3PathBuf::try_from_str(key).unwrap());
+        let array = to_list_array(&paths);
+        assert_that(array.len()).is_equal_to(3);
+        assert_that(array.value_length(0)).is_equal_to(2);
+        assert_that(array.value_length(1)).is_equal_to(0);
+        let values = array
+            .values()
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_that(values.iter().flatten().collect::<Vec<_>>()).contains_exactly(["a", "b", "c"]);
+    }
+
+    #[test]
+    fn key_kernels_match_path_semantics() {
+        let array = StringArray::from(vec![
+            Some("a/b/c.tar.gz"),
+            Some("a"),
+            Some(".hidden"),
+            Some(""),
+            Some("a//b"),
+            None,
+        ]);
+
+        let parents = key_parent(&array);
+        assert_that(parents.iter().collect::<Vec<_>>()).is_equal_to(vec![
+            Some("a/b"),
+            Some(""),
+            Some(""),
+            None,
+            None,
+            None,
+        ]);
+
+        let extensions = key_extension(&array);
+        assert_that(extensions.iter().collect::<Vec<_>>()).is_equal_to(vec![
+            Some("gz"),
+            None,
+            None,
+            None,
+            None,
+            None,
+        ]);
+
+        let depths = key_depth(&array);
+        assert_that(depths.iter().collect::<Vec<_>>()).is_equal_to(vec![
+            Some(3),
+            Some(1),
+            Some(1),
+            Some(0),
+            None,
+            None,
+        ]);
+
+        let components = key_component(&array, 1);
+        assert_that(components.iter().collect::<Vec<_>>()).is_equal_to(vec![
+            Some("b"),
+            None,
+            None,
+            None,
+            None,
+            None,
+        ]);
+    }
+}
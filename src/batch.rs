@@ -0,0 +1,118 @@
+//! Validation of many keys at once, e.g. when ingesting inventory or manifest files.
+
+use crate::error::InvalidComponentKind;
+use crate::validation;
+
+/// The result of validating a batch of keys using [`validate_keys`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BatchReport {
+    /// Number of keys validated.
+    pub keys: usize,
+
+    /// Total length of all keys validated, in bytes.
+    pub bytes: usize,
+
+    /// Indices of all invalid keys, in ascending order.
+    pub invalid: Vec<usize>,
+
+    /// Number of invalid keys containing an empty component, caused by a leading, trailing or
+    /// repeated slash.
+    pub empty_components: usize,
+
+    /// Number of invalid keys containing a character not allowed in path components.
+    pub disallowed_characters: usize,
+
+    /// Number of invalid keys containing a `.` or `..` component.
+    pub path_traversals: usize,
+
+    /// Largest number of components of any valid key.
+    pub max_depth: usize,
+}
+
+impl BatchReport {
+    /// Returns the number of valid keys.
+    #[must_use]
+    pub fn valid(&self) -> usize {
+        self.keys - self.invalid.len()
+    }
+
+    /// Returns true if all keys were valid.
+    #[must_use]
+    pub fn is_all_valid(&self) -> bool {
+        self.invalid.is_empty()
+    }
+}
+
+/// Validates all `keys` in one pass, using the rules of
+/// [`S3PathBuf::strict_parse`](crate::S3PathBuf::strict_parse): a key is valid if it parses into
+/// a path rendering back to exactly that key. The empty string is valid.
+///
+/// Unlike parsing every key, no error values are allocated and failures are not reported to the
+/// [`telemetry`](crate::telemetry) observer. Each invalid key is counted once, by its first
+/// invalid component.
+///
+/// ```
+/// use s3_path::batch::validate_keys;
+///
+/// let report = validate_keys(["foo/bar", "foo//bar", "foo/b r", "foo/.."]);
+/// assert_eq!(report.valid(), 1);
+/// assert_eq!(report.invalid, [1, 2, 3]);
+/// assert_eq!(report.empty_components, 1);
+/// ```
+pub fn validate_keys<'a>(keys: impl IntoIterator<Item = &'a str>) -> BatchReport {
+    let mut report = BatchReport::default();
+    for (index, key) in keys.into_iter().enumerate() {
+        report.keys += 1;
+        report.bytes += key.len();
+        if key.is_empty() {
+            continue;
+        }
+        match key.split('/').find_map(validation::find_violation) {
+            None => report.max_depth = report.max_depth.max(key.split('/').count()),
+            Some(violation) => {
+                report.invalid.push(index);
+                match violation {
+                    InvalidComponentKind::Empty => report.empty_components += 1,
+                    InvalidComponentKind::DisallowedCharacter(_) => {
+                        report.disallowed_characters += 1
+                    }
+                    InvalidComponentKind::PathTraversal => report.path_traversals += 1,
+                }
+            }
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod test {
+    use crate::batch::validate_keys;
+    use crate::S3PathBuf;
+    use assertr::prelude::*;
+
+    #[test]
+    fn agrees_with_strict_parse() {
+        let keys = [
+            "", "a", "a/b/c", "/a", "a/", "a//b", "a/$", "a/ä", "./a", "a/..",
+        ];
+        let report = validate_keys(keys);
+
+        let expected = (0..keys.len())
+            .filter(|i| S3PathBuf::strict_parse(keys[*i]).is_err())
+            .collect::<Vec<_>>();
+        assert_that(report.invalid.clone()).is_equal_to(expected);
+        assert_that(report.keys).is_equal_to(10);
+        assert_that(report.valid()).is_equal_to(3);
+    }
+
+    #[test]
+    fn aggregates_stats() {
+        let report = validate_keys(["a/b/c", "a//b", "/a", "a/b$", "..", "abc"]);
+        assert_that(report.bytes).is_equal_to(20);
+        assert_that(report.max_depth).is_equal_to(3);
+        assert_that(report.empty_components).is_equal_to(2);
+        assert_that(report.disallowed_characters).is_equal_to(1);
+        assert_that(report.path_traversals).is_equal_to(1);
+        assert_that(report.is_all_valid()).is_false();
+    }
+}
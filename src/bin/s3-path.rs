@@ -0,0 +1,304 @@
+//! Command line companion of the `s3-path` library, working on newline-delimited key files.
+//!
+//! Run `s3-path help` for usage.
+
+use s3_path::cli::{render_tree, TreeOptions};
+use s3_path::io::{read_ndkeys, KeyEncoding};
+use s3_path::migrate::LayoutTemplate;
+use s3_path::parse::{apply_fixes, suggest_fixes};
+use s3_path::S3PathBuf;
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: s3-path <COMMAND> [OPTIONS] [ARGS]
+
+Keys are read from FILE, one per line, or from stdin if FILE is missing or '-'.
+
+Commands:
+  validate [FILE]                       Print every invalid key with the reason
+  normalize [FILE]                      Print every key with all suggested fixes applied
+  diff OLD NEW                          Print keys removed from (-) and added to (+) OLD
+  rollup [--depth N] [FILE]             Count keys per prefix of N (default 1) components
+  tree [--depth N] [--no-counts] [FILE] Render keys as a tree
+  template-render TEMPLATE [NAME=VALUE]...
+                                        Render a layout template into a key
+  help                                  Print this message
+
+Commands exit with status 1 when finding invalid keys or differences, and 2 on errors.
+";
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let stdout = std::io::stdout();
+    match run(&args, &mut stdout.lock()) {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Arguments following the command.
+#[derive(Debug, Default)]
+struct Args {
+    positional: Vec<String>,
+    depth: Option<usize>,
+    no_counts: bool,
+}
+
+impl Args {
+    fn parse(args: &[String]) -> Result<Self, Box<dyn Error>> {
+        let mut parsed = Args::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--depth" => {
+                    let depth = args.next().ok_or("Missing value of '--depth'")?;
+                    parsed.depth = Some(
+                        depth
+                            .parse()
+                            .map_err(|_| format!("Invalid depth '{depth}'"))?,
+                    );
+                }
+                "--no-counts" => parsed.no_counts = true,
+                "-" => parsed.positional.push(arg.clone()),
+                _ if arg.starts_with('-') => return Err(format!("Unknown option '{arg}'").into()),
+                _ => parsed.positional.push(arg.clone()),
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Returns the only positional argument, if any.
+    fn file(&self) -> Result<Option<&str>, Box<dyn Error>> {
+        match self.positional.as_slice() {
+            [] => Ok(None),
+            [file] => Ok(Some(file)),
+            _ => Err("Expected at most one FILE".into()),
+        }
+    }
+}
+
+fn run(args: &[String], out: &mut impl Write) -> Result<ExitCode, Box<dyn Error>> {
+    let Some((command, args)) = args.split_first() else {
+        out.write_all(USAGE.as_bytes())?;
+        return Ok(ExitCode::from(2));
+    };
+    let args = Args::parse(args)?;
+    match command.as_str() {
+        "validate" => validate(open(args.file()?)?, out),
+        "normalize" => normalize(open(args.file()?)?, out),
+        "diff" => match args.positional.as_slice() {
+            [old, new] => diff(open(Some(old))?, open(Some(new))?, out),
+            _ => Err("Expected OLD and NEW".into()),
+        },
+        "rollup" => rollup(open(args.file()?)?, args.depth.unwrap_or(1), out),
+        "tree" => {
+            let options = TreeOptions {
+                max_depth: args.depth,
+                counts: !args.no_counts,
+            };
+            let keys = read_keys(open(args.file()?)?)?;
+            out.write_all(render_tree(&keys, &options).as_bytes())?;
+            Ok(ExitCode::SUCCESS)
+        }
+        "template-render" => template_render(&args.positional, out),
+        "help" | "--help" | "-h" => {
+            out.write_all(USAGE.as_bytes())?;
+            Ok(ExitCode::SUCCESS)
+        }
+        _ => Err(format!("Unknown command '{command}', see 's3-path help'").into()),
+    }
+}
+
+fn open(file: Option<&str>) -> Result<Box<dyn BufRead>, Box<dyn Error>> {
+    Ok(match file {
+        None | Some("-") => Box::new(std::io::stdin().lock()),
+        Some(file) => Box::new(BufReader::new(
+            File::open(file).map_err(|err| format!("Cannot open '{file}': {err}"))?,
+        )),
+    })
+}
+
+fn read_keys(input: impl BufRead) -> Result<Vec<S3PathBuf>, Box<dyn Error>> {
+    Ok(read_ndkeys(input, KeyEncoding::Plain).collect::<Result<_, _>>()?)
+}
+
+/// Iterates the non-blank lines of `input`, with their numbers (starting at 1).
+fn lines(input: impl BufRead) -> impl Iterator<Item = std::io::Result<(usize, String)>> {
+    input
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| match line {
+            Ok(line) => {
+                let line = line.strip_suffix('\r').unwrap_or(&line).to_owned();
+                (!line.is_empty()).then_some(Ok((index + 1, line)))
+            }
+            Err(err) => Some(Err(err)),
+        })
+}
+
+fn validate(input: impl BufRead, out: &mut impl Write) -> Result<ExitCode, Box<dyn Error>> {
+    let mut invalid = 0;
+    for line in lines(input) {
+        let (number, key) = line?;
+        if let Err(err) = S3PathBuf::strict_parse(&key) {
+            writeln!(out, "{number}: {err}")?;
+            invalid += 1;
+        }
+    }
+    Ok(if invalid == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+fn normalize(input: impl BufRead, out: &mut impl Write) -> Result<ExitCode, Box<dyn Error>> {
+    for line in lines(input) {
+        let (_, key) = line?;
+        writeln!(out, "{}", apply_fixes(&key, &suggest_fixes(&key)))?;
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn diff(
+    old: impl BufRead,
+    new: impl BufRead,
+    out: &mut impl Write,
+) -> Result<ExitCode, Box<dyn Error>> {
+    let rendered = |keys: Vec<S3PathBuf>| {
+        keys.iter()
+            .map(ToString::to_string)
+            .collect::<BTreeSet<_>>()
+    };
+    let old = rendered(read_keys(old)?);
+    let new = rendered(read_keys(new)?);
+    for key in old.difference(&new) {
+        writeln!(out, "- {key}")?;
+    }
+    for key in new.difference(&old) {
+        writeln!(out, "+ {key}")?;
+    }
+    Ok(if old == new {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+fn rollup(
+    input: impl BufRead,
+    depth: usize,
+    out: &mut impl Write,
+) -> Result<ExitCode, Box<dyn Error>> {
+    let mut counts = BTreeMap::<String, usize>::new();
+    for key in read_keys(input)? {
+        let mut prefix = key.components().take(depth).collect::<Vec<_>>().join("/");
+        if key.len() > depth {
+            prefix.push('/');
+        }
+        *counts.entry(prefix).or_default() += 1;
+    }
+    for (prefix, count) in counts {
+        writeln!(out, "{count}\t{prefix}")?;
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn template_render(args: &[String], out: &mut impl Write) -> Result<ExitCode, Box<dyn Error>> {
+    let Some((template, values)) = args.split_first() else {
+        return Err("Expected TEMPLATE".into());
+    };
+    let template = LayoutTemplate::parse(template)?;
+    let values = values
+        .iter()
+        .map(|value| {
+            value
+                .split_once('=')
+                .ok_or_else(|| format!("Expected NAME=VALUE, got '{value}'"))
+        })
+        .collect::<Result<BTreeMap<_, _>, _>>()?;
+    let path = template.render(|name| values.get(name).copied())?;
+    writeln!(out, "{path}")?;
+    Ok(ExitCode::SUCCESS)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{run, USAGE};
+    use assertr::prelude::*;
+    use std::process::ExitCode;
+
+    fn run_with(args: &[&str]) -> (ExitCode, String) {
+        let args = args.iter().map(ToString::to_string).collect::<Vec<_>>();
+        let mut out = Vec::new();
+        let code = run(&args, &mut out).unwrap();
+        (code, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn prints_usage() {
+        let (code, out) = run_with(&["help"]);
+        assert_that(code).is_equal_to(ExitCode::SUCCESS);
+        assert_that(out.as_str()).is_equal_to(USAGE);
+    }
+
+    #[test]
+    fn renders_templates() {
+        let (code, out) = run_with(&[
+            "template-render",
+            "logs/{day:date}/{file}",
+            "day=2024-01-31",
+            "file=a.log",
+        ]);
+        assert_that(code).is_equal_to(ExitCode::SUCCESS);
+        assert_that(out.as_str()).is_equal_to("logs/2024-01-31/a.log\n");
+
+        let args = ["template-render", "a//b"].map(ToString::to_string);
+        assert_that(run(&args, &mut Vec::new())).is_err();
+    }
+
+    #[test]
+    fn rolls_up_and_diffs_keys() {
+        let mut out = Vec::new();
+        let input = "a/b/c\na/d\ne\n";
+        crate::rollup(input.as_bytes(), 1, &mut out).unwrap();
+        assert_that(String::from_utf8(out).unwrap()).is_equal_to("2\ta/\n1\te\n".to_owned());
+
+        let mut out = Vec::new();
+        let code = crate::diff("a\nb\n".as_bytes(), "b\nc\n".as_bytes(), &mut out).unwrap();
+        assert_that(code).is_equal_to(ExitCode::FAILURE);
+        assert_that(String::from_utf8(out).unwrap()).is_equal_to("- a\n+ c\n".to_owned());
+    }
+
+    #[test]
+    fn validates_and_normalizes_keys() {
+        let mut out = Vec::new();
+        let code = crate::validate("a/b\n\na//b\n".as_bytes(), &mut out).unwrap();
+        assert_that(code).is_equal_to(ExitCode::FAILURE);
+        assert_that(String::from_utf8(out).unwrap()).is_equal_to(
+            "3: Invalid S3 path component '': Empty component is not allowed\n".to_owned(),
+        );
+
+        let mut out = Vec::new();
+        crate::normalize("/my files//x\n".as_bytes(), &mut out).unwrap();
+        assert_that(String::from_utf8(out).unwrap()).is_equal_to("my-files/x\n".to_owned());
+    }
+
+    #[test]
+    fn normalized_keys_are_valid() {
+        let mut normalized = Vec::new();
+        let input = "a//\nx/../\na/$/$\n//a//\n/my files//../x\n";
+        crate::normalize(input.as_bytes(), &mut normalized).unwrap();
+        let mut out = Vec::new();
+        let code = crate::validate(normalized.as_slice(), &mut out).unwrap();
+        assert_that(String::from_utf8(out).unwrap()).is_empty();
+        assert_that(code).is_equal_to(ExitCode::SUCCESS);
+    }
+}
@@ -0,0 +1,189 @@
+//! A bounded cache of values per path, e.g. of `HEAD` results per key in a proxy.
+//!
+//! [`PathCache`] evicts the least recently used entry once full. Lookups take a borrowed
+//! [`S3Path`], so probing the cache on the hot read path never allocates a key.
+//!
+//! ```
+//! use s3_path::cache::PathCache;
+//! use s3_path::S3PathBuf;
+//!
+//! let mut sizes = PathCache::new(2);
+//! sizes.insert(S3PathBuf::try_from_str("a").unwrap(), 1);
+//! sizes.insert(S3PathBuf::try_from_str("b").unwrap(), 2);
+//!
+//! let a = S3PathBuf::try_from_str("a").unwrap();
+//! assert_eq!(sizes.get(&a), Some(&1));
+//!
+//! // "b" was used least recently.
+//! sizes.insert(S3PathBuf::try_from_str("c").unwrap(), 3);
+//! assert_eq!(sizes.get(&S3PathBuf::try_from_str("b").unwrap()), None);
+//! ```
+
+use crate::{S3Path, S3PathBuf};
+use std::collections::{BTreeMap, HashMap};
+
+/// A cached value and the time of its last use.
+#[derive(Debug, Clone)]
+struct Entry<V> {
+    value: V,
+    used: u64,
+}
+
+/// A least recently used cache of values per path. See the [module documentation](crate::cache).
+#[derive(Debug, Clone)]
+pub struct PathCache<V> {
+    capacity: usize,
+    entries: HashMap<S3PathBuf, Entry<V>>,
+    /// All cached paths by the time of their last use, least recently used first.
+    order: BTreeMap<u64, S3PathBuf>,
+    /// Time of the last use of any entry.
+    now: u64,
+}
+
+impl<V> PathCache<V> {
+    /// Creates an empty cache holding up to `capacity` entries. A cache with a capacity of zero
+    /// caches nothing.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            now: 0,
+        }
+    }
+
+    /// Returns the maximum number of entries.
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of entries.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the cache has no entries.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the value cached for `path`, marking it as most recently used.
+    pub fn get(&mut self, path: &S3Path<'_>) -> Option<&V> {
+        self.get_mut(path).map(|value| &*value)
+    }
+
+    /// Returns the value cached for `path` mutably, marking it as most recently used.
+    pub fn get_mut(&mut self, path: &S3Path<'_>) -> Option<&mut V> {
+        let entry = self.entries.get_mut(path)?;
+        self.now += 1;
+        let path = self
+            .order
+            .remove(&entry.used)
+            .expect("cached paths are ordered");
+        self.order.insert(self.now, path);
+        entry.used = self.now;
+        Some(&mut entry.value)
+    }
+
+    /// Returns the value cached for `path`, without marking it as used.
+    #[must_use]
+    pub fn peek(&self, path: &S3Path<'_>) -> Option<&V> {
+        self.entries.get(path).map(|entry| &entry.value)
+    }
+
+    /// Caches `value` for `path`, marking it as most recently used, and returns the value
+    /// previously cached for `path`.
+    ///
+    /// Evicts the least recently used entry when the cache is full.
+    pub fn insert(&mut self, path: S3PathBuf, value: V) -> Option<V> {
+        if let Some(previous) = self.get_mut(&path) {
+            return Some(std::mem::replace(previous, value));
+        }
+        if self.capacity == 0 {
+            return None;
+        }
+        if self.entries.len() == self.capacity {
+            if let Some((_, evicted)) = self.order.pop_first() {
+                self.entries.remove(&evicted);
+            }
+        }
+        self.now += 1;
+        self.order.insert(self.now, path.clone());
+        self.entries.insert(
+            path,
+            Entry {
+                value,
+                used: self.now,
+            },
+        );
+        None
+    }
+
+    /// Removes and returns the value cached for `path`.
+    pub fn remove(&mut self, path: &S3Path<'_>) -> Option<V> {
+        let entry = self.entries.remove(path)?;
+        self.order.remove(&entry.used);
+        Some(entry.value)
+    }
+
+    /// Removes all entries.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cache::PathCache;
+    use crate::{S3Path, S3PathBuf};
+    use assertr::prelude::*;
+    use std::borrow::Cow;
+
+    fn path(key: &str) -> S3PathBuf {
+        S3PathBuf::try_from_str(key).unwrap()
+    }
+
+    #[test]
+    fn evicts_least_recently_used_entry() {
+        let mut cache = PathCache::new(2);
+        assert_that(cache.insert(path("a"), 1)).is_none();
+        assert_that(cache.insert(path("b"), 2)).is_none();
+        assert_that(cache.get(&path("a")).copied()).is_equal_to(Some(1));
+        // Peeking does not count as use.
+        assert_that(cache.peek(&path("b")).copied()).is_equal_to(Some(2));
+
+        cache.insert(path("c"), 3);
+        assert_that(cache.len()).is_equal_to(2);
+        assert_that(cache.peek(&path("b"))).is_none();
+
+        // Replacing a value counts as use.
+        assert_that(cache.insert(path("a"), 4)).is_equal_to(Some(1));
+        cache.insert(path("d"), 5);
+        assert_that(cache.peek(&path("c"))).is_none();
+        assert_that(cache.remove(&path("a"))).is_equal_to(Some(4));
+        assert_that(cache.len()).is_equal_to(1);
+    }
+
+    #[test]
+    fn looks_up_borrowed_paths() {
+        let mut cache = PathCache::new(1);
+        cache.insert(path("a/b"), 1);
+        let components = [Cow::Borrowed("a"), Cow::Borrowed("b")];
+        let borrowed = S3Path::new(&components).unwrap();
+        assert_that(cache.get(borrowed).copied()).is_equal_to(Some(1));
+    }
+
+    #[test]
+    fn caches_nothing_without_capacity() {
+        let mut cache = PathCache::new(0);
+        cache.insert(path("a"), 1);
+        assert_that(cache.is_empty()).is_true();
+        cache.clear();
+        assert_that(cache.capacity()).is_equal_to(0);
+    }
+}
@@ -0,0 +1,221 @@
+//! Helpers for command line tools working with keys.
+
+use crate::S3Path;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+/// Completes a partially typed key, directory-style, against a set of `known_keys`.
+///
+/// Everything up to the last '/' in `prefix` must match whole components. The remainder is
+/// matched against the beginning of the next component. Each completion is the key up to and
+/// including that next component, followed by a '/' if there are more components below it.
+///
+/// Returns all completions in ascending order, without duplicates.
+///
+/// ```
+/// use s3_path::cli::complete;
+/// use s3_path::S3PathBuf;
+///
+/// let keys = ["logs/2024/a.log", "logs/2025/b.log", "logs/latest.log", "data/x.csv"]
+///     .map(|key| S3PathBuf::try_from_str(key).unwrap());
+///
+/// assert_eq!(complete("logs/20", &keys), ["logs/2024/", "logs/2025/"]);
+/// assert_eq!(complete("", &keys), ["data/", "logs/"]);
+/// ```
+pub fn complete<'p, P: AsRef<S3Path<'p>>>(
+    prefix: &str,
+    known_keys: impl IntoIterator<Item = P>,
+) -> Vec<String> {
+    let (directory, partial) = match prefix.rfind('/') {
+        Some(index) => (&prefix[..index], &prefix[index + 1..]),
+        None => ("", prefix),
+    };
+    let directory = directory
+        .split('/')
+        .filter(|c| !c.is_empty())
+        .collect::<Vec<_>>();
+
+    let mut completions = BTreeSet::new();
+    for key in known_keys {
+        let key = key.as_ref();
+        let mut components = key.components();
+        if !directory
+            .iter()
+            .all(|expected| components.next() == Some(*expected))
+        {
+            continue;
+        }
+        let Some(next) = components.next() else {
+            continue;
+        };
+        if !next.starts_with(partial) {
+            continue;
+        }
+        let mut completion = String::new();
+        for c in &directory {
+            completion.push_str(c);
+            completion.push('/');
+        }
+        completion.push_str(next);
+        if components.next().is_some() {
+            completion.push('/');
+        }
+        completions.insert(completion);
+    }
+    completions.into_iter().collect()
+}
+
+/// Options for [`render_tree`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeOptions {
+    /// Number of component levels to render. Deeper levels are collapsed into their parent,
+    /// which still counts them. Renders all levels if `None`.
+    pub max_depth: Option<usize>,
+
+    /// Whether to append the number of keys at or below each node having (possibly collapsed)
+    /// children, as in `logs (3)`.
+    pub counts: bool,
+}
+
+impl Default for TreeOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: None,
+            counts: true,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Node<'a> {
+    children: BTreeMap<&'a str, Node<'a>>,
+    count: usize,
+    /// Whether components below this node were cut off by `max_depth`.
+    collapsed: bool,
+}
+
+/// Renders `paths` as a `tree(1)`-style ASCII tree, grouped by components.
+///
+/// Siblings are rendered in ascending order. The empty path is counted by the root node only.
+///
+/// ```
+/// use s3_path::cli::{render_tree, TreeOptions};
+/// use s3_path::S3PathBuf;
+///
+/// let paths = ["logs/2024/a.log", "logs/2024/b.log", "readme.txt"]
+///     .map(|key| S3PathBuf::try_from_str(key).unwrap());
+///
+/// assert_eq!(
+///     render_tree(&paths, &TreeOptions::default()),
+///     "\
+/// . (3)
+/// ├── logs (2)
+/// │   └── 2024 (2)
+/// │       ├── a.log
+/// │       └── b.log
+/// └── readme.txt
+/// "
+/// );
+/// ```
+pub fn render_tree<'p, P: AsRef<S3Path<'p>>>(
+    paths: impl IntoIterator<Item = P>,
+    options: &TreeOptions,
+) -> String {
+    let paths = paths.into_iter().collect::<Vec<_>>();
+    let mut root = Node::default();
+    for path in &paths {
+        let mut node = &mut root;
+        node.count += 1;
+        let depth = options.max_depth.unwrap_or(usize::MAX);
+        let mut components = path.as_ref().components();
+        for c in components.by_ref().take(depth) {
+            node = node.children.entry(c).or_default();
+            node.count += 1;
+        }
+        if components.next().is_some() {
+            node.collapsed = true;
+        }
+    }
+
+    let mut out = String::new();
+    write_label(&mut out, ".", &root, options);
+    write_children(&mut out, &root, "", options);
+    out
+}
+
+fn write_label(out: &mut String, label: &str, node: &Node<'_>, options: &TreeOptions) {
+    out.push_str(label);
+    if options.counts && (node.collapsed || !node.children.is_empty()) {
+        // Writing to a String never fails.
+        let _ = write!(out, " ({})", node.count);
+    }
+    out.push('\n');
+}
+
+fn write_children(out: &mut String, node: &Node<'_>, indent: &str, options: &TreeOptions) {
+    let mut children = node.children.iter().peekable();
+    while let Some((label, child)) = children.next() {
+        let last = children.peek().is_none();
+        out.push_str(indent);
+        out.push_str(if last { "└── " } else { "├── " });
+        write_label(out, label, child, options);
+        let indent = format!("{indent}{}", if last { "    " } else { "│   " });
+        write_children(out, child, &indent, options);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cli::{complete, render_tree, TreeOptions};
+    use crate::S3PathBuf;
+    use assertr::prelude::*;
+
+    fn keys(keys: &[&str]) -> Vec<S3PathBuf> {
+        keys.iter()
+            .map(|key| S3PathBuf::try_from_str(key).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn completes_next_component_directory_style() {
+        let keys = keys(&["a/bar/x", "a/bar/y", "a/baz", "a/bar", "a/qux/z", "b"]);
+        assert_that(complete("a/ba", &keys)).contains_exactly(["a/bar", "a/bar/", "a/baz"]);
+        assert_that(complete("a/", &keys)).has_length(4);
+        assert_that(complete("a", &keys)).contains_exactly(["a/"]);
+    }
+
+    #[test]
+    fn yields_nothing_for_unknown_prefixes() {
+        let keys = keys(&["a/b"]);
+        assert_that(complete("x/", &keys)).is_empty();
+        assert_that(complete("a/b/", &keys)).is_empty();
+    }
+
+    #[test]
+    fn render_tree_collapses_levels_beyond_max_depth() {
+        let keys = keys(&["a/b/c", "a/b/d", "a/e", "f"]);
+        let options = TreeOptions {
+            max_depth: Some(1),
+            counts: true,
+        };
+        assert_that(render_tree(&keys, &options)).is_equal_to(
+            "\
+. (4)
+├── a (3)
+└── f
+"
+            .to_owned(),
+        );
+    }
+
+    #[test]
+    fn render_tree_without_counts() {
+        let keys = keys(&["a/b", "a/c"]);
+        let options = TreeOptions {
+            max_depth: None,
+            counts: false,
+        };
+        assert_that(render_tree(&keys, &options))
+            .is_equal_to(".\n└── a\n    ├── b\n    └── c\n".to_owned());
+    }
+}
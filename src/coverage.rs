@@ -0,0 +1,251 @@
+//! Coverage of key layouts by observed paths, so that test suites can assert they exercised
+//! every key shape a system supports.
+//!
+//! Each grammar or layout template is one shape. A registry of layouts has one shape per
+//! version. Besides whether each shape was matched at all, coverage tracks which values of each
+//! `{enum:...}` component were observed.
+//!
+//! ```
+//! use s3_path::coverage::coverage;
+//! use s3_path::grammar::KeyGrammar;
+//! use s3_path::S3PathBuf;
+//!
+//! let grammar = KeyGrammar::parse("exports/{date}/{enum:csv|json}").unwrap();
+//! let observed = ["exports/2024-01-31/csv", "imports/x"]
+//!     .map(|key| S3PathBuf::try_from_str(key).unwrap());
+//!
+//! let coverage = coverage(&grammar, &observed);
+//! assert!(!coverage.is_complete());
+//! assert_eq!(
+//!     coverage.missing(),
+//!     ["exports/{date}/{enum:csv|json}: component 2 never was 'json'"],
+//! );
+//! assert_eq!(coverage.unmatched, [S3PathBuf::try_from_str("imports/x").unwrap()]);
+//! ```
+
+use crate::grammar::{ComponentSpec, KeyGrammar};
+use crate::migrate::{LayoutRegistry, LayoutTemplate};
+use crate::{S3Path, S3PathBuf};
+
+/// A set of key shapes whose coverage can be computed, e.g. a [`KeyGrammar`].
+pub trait KeyShapes {
+    /// Returns the name and component specifications of each shape.
+    fn shapes(&self) -> Vec<(String, Vec<ComponentSpec>)>;
+}
+
+impl KeyShapes for KeyGrammar {
+    fn shapes(&self) -> Vec<(String, Vec<ComponentSpec>)> {
+        vec![(self.to_string(), self.specs().to_vec())]
+    }
+}
+
+impl KeyShapes for LayoutTemplate {
+    fn shapes(&self) -> Vec<(String, Vec<ComponentSpec>)> {
+        vec![(self.to_string(), self.specs().cloned().collect())]
+    }
+}
+
+/// One shape per registered version, named like `v2: {tenant}/{file}`.
+impl KeyShapes for LayoutRegistry {
+    fn shapes(&self) -> Vec<(String, Vec<ComponentSpec>)> {
+        self.layouts()
+            .map(|(version, layout)| {
+                (
+                    format!("v{version}: {layout}"),
+                    layout.specs().cloned().collect(),
+                )
+            })
+            .collect()
+    }
+}
+
+impl<T: KeyShapes> KeyShapes for [T] {
+    fn shapes(&self) -> Vec<(String, Vec<ComponentSpec>)> {
+        self.iter().flat_map(KeyShapes::shapes).collect()
+    }
+}
+
+/// How often each shape, and each enum value within it, was observed. Created by [`coverage`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Coverage {
+    /// Coverage of each shape, in the order returned by [`KeyShapes::shapes`].
+    pub shapes: Vec<ShapeCoverage>,
+
+    /// Observed paths matching no shape, in observation order.
+    pub unmatched: Vec<S3PathBuf>,
+}
+
+/// Coverage of a single shape.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShapeCoverage {
+    /// Name of the shape, as returned by [`KeyShapes::shapes`].
+    pub name: String,
+
+    /// Number of observed paths matching this shape.
+    pub hits: usize,
+
+    /// Coverage of each value of each enum component, in component order.
+    pub values: Vec<ValueCoverage>,
+}
+
+/// Coverage of a single value of an enum component.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValueCoverage {
+    /// Index of the enum component in the shape.
+    pub index: usize,
+
+    /// One of the values allowed by the enum component.
+    pub value: String,
+
+    /// Number of observed paths matching the shape with this value.
+    pub hits: usize,
+}
+
+impl Coverage {
+    /// Returns true if every shape and every enum value was observed at least once.
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.missing().is_empty()
+    }
+
+    /// Describes every shape and enum value never observed.
+    #[must_use]
+    pub fn missing(&self) -> Vec<String> {
+        let mut missing = Vec::new();
+        for shape in &self.shapes {
+            if shape.hits == 0 {
+                missing.push(format!("{}: never matched", shape.name));
+                continue;
+            }
+            for value in shape.values.iter().filter(|value| value.hits == 0) {
+                missing.push(format!(
+                    "{}: component {} never was '{}'",
+                    shape.name, value.index, value.value
+                ));
+            }
+        }
+        missing
+    }
+}
+
+/// Computes how well `observed` paths cover the shapes of `shapes`. A path covers every shape it
+/// matches.
+#[must_use]
+pub fn coverage<'p, P: AsRef<S3Path<'p>>>(
+    shapes: &(impl KeyShapes + ?Sized),
+    observed: impl IntoIterator<Item = P>,
+) -> Coverage {
+    let shapes = shapes.shapes();
+    let mut coverage = Coverage {
+        shapes: shapes
+            .iter()
+            .map(|(name, specs)| ShapeCoverage {
+                name: name.clone(),
+                hits: 0,
+                values: specs
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, spec)| match spec {
+                        ComponentSpec::OneOf(values) => Some((index, values)),
+                        _ => None,
+                    })
+                    .flat_map(|(index, values)| {
+                        values.iter().map(move |value| ValueCoverage {
+                            index,
+                            value: value.clone(),
+                            hits: 0,
+                        })
+                    })
+                    .collect(),
+            })
+            .collect(),
+        unmatched: Vec::new(),
+    };
+    for path in observed {
+        let path = path.as_ref();
+        let mut matched = false;
+        for ((_, specs), shape) in shapes.iter().zip(&mut coverage.shapes) {
+            let matches = path.len() == specs.len()
+                && specs
+                    .iter()
+                    .zip(path.components())
+                    .all(|(spec, component)| spec.matches(component));
+            if !matches {
+                continue;
+            }
+            matched = true;
+            shape.hits += 1;
+            for value in &mut shape.values {
+                if path.get(value.index) == Some(value.value.as_str()) {
+                    value.hits += 1;
+                }
+            }
+        }
+        if !matched {
+            coverage.unmatched.push(path.to_owned());
+        }
+    }
+    coverage
+}
+
+#[cfg(test)]
+mod test {
+    use crate::coverage::coverage;
+    use crate::grammar::KeyGrammar;
+    use crate::migrate::LayoutRegistry;
+    use crate::S3PathBuf;
+    use assertr::prelude::*;
+
+    fn paths(keys: &[&str]) -> Vec<S3PathBuf> {
+        keys.iter()
+            .map(|key| S3PathBuf::try_from_str(key).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn counts_enum_values_per_component() {
+        let grammar = KeyGrammar::parse("{enum:a|b}/{enum:a|c}").unwrap();
+        let coverage = coverage(&grammar, paths(&["a/a", "b/a", "b/c", "c/c"]));
+        let hits = coverage.shapes[0]
+            .values
+            .iter()
+            .map(|value| (value.index, value.value.as_str(), value.hits))
+            .collect::<Vec<_>>();
+        assert_that(hits).is_equal_to(vec![(0, "a", 1), (0, "b", 2), (1, "a", 2), (1, "c", 1)]);
+        assert_that(coverage.shapes[0].hits).is_equal_to(3);
+        assert_that(coverage.is_complete()).is_true();
+        assert_that(coverage.unmatched).is_equal_to(paths(&["c/c"]));
+    }
+
+    #[test]
+    fn reports_layout_versions_never_matched() {
+        let mut registry = LayoutRegistry::new();
+        registry
+            .register(1, "{tenant}/{file}")
+            .unwrap()
+            .register(2, "v2/{tenant}/{day:date}/{file}")
+            .unwrap();
+        let coverage = coverage(&registry, paths(&["acme/a.csv"]));
+        assert_that(coverage.missing()).is_equal_to(vec![
+            "v2: v2/{tenant}/{day:date}/{file}: never matched".to_owned(),
+        ]);
+    }
+
+    #[test]
+    fn covers_every_matching_shape() {
+        let grammars = [
+            KeyGrammar::parse("{any}").unwrap(),
+            KeyGrammar::parse("{enum:x|y}").unwrap(),
+        ];
+        let coverage = coverage(grammars.as_slice(), paths(&["x", "y"]));
+        assert_that(
+            coverage
+                .shapes
+                .iter()
+                .map(|shape| shape.hits)
+                .collect::<Vec<_>>(),
+        )
+        .is_equal_to(vec![2, 2]);
+        assert_that(coverage.is_complete()).is_true();
+    }
+}
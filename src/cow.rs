@@ -0,0 +1,160 @@
+//! Paths borrowing their components, for joining borrowed segments without copying them.
+//!
+//! ```
+//! use s3_path::s3_path;
+//! use std::borrow::Cow;
+//!
+//! let root = s3_path!("tenants").unwrap();
+//! let tenant = String::from("acme"); // E.g. taken from a request.
+//!
+//! let path = root.join_borrowed(tenant.as_str()).unwrap();
+//! assert_eq!(path.to_string(), "tenants/acme");
+//! assert!(matches!(path.last_cow(), Some(Cow::Borrowed(_))));
+//! ```
+
+use crate::error::InvalidS3PathComponent;
+use crate::{validation, S3Path, S3PathBuf};
+use std::borrow::Cow;
+use std::fmt::Formatter;
+use std::ops::Deref;
+
+/// A path owning the list of its components, which may borrow data living for `'i`.
+///
+/// Like [`S3PathBuf`], but not restricted to `'static` components. Dereferences to an
+/// [`S3Path<'i>`](S3Path). Use [`into_owned`](S3PathCow::into_owned) to detach it from the
+/// borrowed data.
+#[derive(Clone, PartialEq, Eq, Hash, Default)]
+pub struct S3PathCow<'i> {
+    components: Vec<Cow<'i, str>>,
+}
+
+impl<'i> S3PathCow<'i> {
+    /// Creates an empty path.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `component` to the path after validating it, without copying borrowed data.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when the given component
+    /// - is empty
+    /// - contains characters other than: ascii alphanumeric characters, '-', '_' and '.'
+    /// - is equal to `.` or `..`
+    pub fn push(
+        &mut self,
+        component: impl Into<Cow<'i, str>>,
+    ) -> Result<&mut Self, InvalidS3PathComponent> {
+        let component = component.into();
+        validation::validate_component(&component)?;
+        self.components.push(component);
+        Ok(self)
+    }
+
+    /// Returns the last component, keeping whether it is borrowed or owned.
+    #[must_use]
+    pub fn last_cow(&self) -> Option<&Cow<'i, str>> {
+        self.components.last()
+    }
+
+    /// Converts into an `S3PathBuf`, copying all borrowed components.
+    #[must_use]
+    pub fn into_owned(self) -> S3PathBuf {
+        S3PathBuf {
+            components: self
+                .components
+                .into_iter()
+                .map(|c| Cow::Owned(c.into_owned()))
+                .collect(),
+        }
+    }
+}
+
+impl<'i> Deref for S3PathCow<'i> {
+    type Target = S3Path<'i>;
+
+    fn deref(&self) -> &Self::Target {
+        // Safety: S3Path is repr(transparent) over [Cow<'i, str>].
+        unsafe {
+            &*(std::ptr::from_ref::<[Cow<'i, str>]>(self.components.as_slice())
+                as *const S3Path<'i>)
+        }
+    }
+}
+
+impl<'i> AsRef<S3Path<'i>> for S3PathCow<'i> {
+    fn as_ref(&self) -> &S3Path<'i> {
+        self
+    }
+}
+
+impl std::fmt::Display for S3PathCow<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(&**self, f)
+    }
+}
+
+impl std::fmt::Debug for S3PathCow<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<'i> S3Path<'i> {
+    /// Like [`join`](S3Path::join), but borrowing this path's components and `component`
+    /// instead of copying them into an `S3PathBuf`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when the given `component`
+    /// - is empty
+    /// - contains characters other than: ascii alphanumeric characters, '-', '_' and '.'
+    /// - is equal to `.` or `..`
+    pub fn join_borrowed<'a>(
+        &'a self,
+        component: impl Into<Cow<'a, str>>,
+    ) -> Result<S3PathCow<'a>, InvalidS3PathComponent> {
+        let mut path = S3PathCow {
+            components: self
+                .components_cow()
+                .map(|c| Cow::Borrowed(c.as_ref()))
+                .collect(),
+        };
+        path.push(component)?;
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cow::S3PathCow;
+    use crate::S3PathBuf;
+    use assertr::prelude::*;
+    use std::borrow::Cow;
+
+    #[test]
+    fn join_borrowed_borrows_all_components() {
+        let root = S3PathBuf::try_from_str("a/b").unwrap();
+        let segment = String::from("c");
+        let joined = root.join_borrowed(segment.as_str()).unwrap();
+        assert_that(joined.to_string()).is_equal_to("a/b/c".to_owned());
+        assert_that(
+            joined
+                .components_cow()
+                .all(|c| matches!(c, Cow::Borrowed(_))),
+        )
+        .is_true();
+        assert_that(joined.into_owned()).is_equal_to(S3PathBuf::try_from_str("a/b/c").unwrap());
+    }
+
+    #[test]
+    fn push_validates_components() {
+        let mut path = S3PathCow::new();
+        let segment = String::from("..");
+        assert_that(path.push(segment.as_str())).is_err();
+        assert_that(path.push("")).is_err();
+        assert_that(path.is_empty()).is_true();
+    }
+}
@@ -0,0 +1,298 @@
+//! Reading and writing CSV or TSV files of keys with attributes, the interchange format of most
+//! bucket audits.
+//!
+//! The key column is parsed into an [`S3PathBuf`], strictly validated. All other columns are
+//! (de)serialized from a user-defined struct using serde, matched by their header names.
+//!
+//! ```
+//! use s3_path::csv::{CsvFormat, KeyedCsvReader, KeyedCsvWriter};
+//! use s3_path::S3PathBuf;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct Attrs {
+//!     size: u64,
+//!     storage_class: String,
+//! }
+//!
+//! let input = "key\tsize\tstorage_class\ndata/a.csv\t42\tGLACIER\n";
+//! let rows = KeyedCsvReader::<_, Attrs>::new(input.as_bytes(), &CsvFormat::tsv())
+//!     .unwrap()
+//!     .collect::<Result<Vec<_>, _>>()
+//!     .unwrap();
+//! assert_eq!(rows[0].0, S3PathBuf::try_from_str("data/a.csv").unwrap());
+//! assert_eq!(rows[0].1.size, 42);
+//!
+//! let mut writer = KeyedCsvWriter::new(Vec::new(), &CsvFormat::tsv());
+//! for (path, attrs) in &rows {
+//!     writer.write(path, attrs).unwrap();
+//! }
+//! assert_eq!(writer.into_inner().unwrap(), input.as_bytes());
+//! ```
+
+use crate::error::ReadCsvError;
+use crate::{S3Path, S3PathBuf};
+use ::csv::StringRecord;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::io::{Read, Write};
+use std::marker::PhantomData;
+
+/// Layout of a file of keys with attributes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvFormat {
+    /// Field delimiter, e.g. `b','` or `b'\t'`.
+    pub delimiter: u8,
+
+    /// Header name of the key column.
+    pub key_column: String,
+}
+
+impl CsvFormat {
+    /// Comma-separated values with a `key` column.
+    #[must_use]
+    pub fn csv() -> Self {
+        Self {
+            delimiter: b',',
+            key_column: "key".to_owned(),
+        }
+    }
+
+    /// Tab-separated values with a `key` column.
+    #[must_use]
+    pub fn tsv() -> Self {
+        Self {
+            delimiter: b'\t',
+            ..Self::csv()
+        }
+    }
+}
+
+impl Default for CsvFormat {
+    fn default() -> Self {
+        Self::csv()
+    }
+}
+
+/// Returns `record` without the field at `index`.
+fn without(record: &StringRecord, index: usize) -> StringRecord {
+    record
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != index)
+        .map(|(_, field)| field)
+        .collect()
+}
+
+/// Reads rows of a key and attributes of type `T` from a file starting with a header row.
+///
+/// The key column may be at any position. Keys must round-trip as defined by
+/// [`S3PathBuf::strict_parse`].
+pub struct KeyedCsvReader<R, T> {
+    records: ::csv::StringRecordsIntoIter<R>,
+    key_index: usize,
+    attr_headers: StringRecord,
+    attrs: PhantomData<fn() -> T>,
+}
+
+impl<R: Read, T: DeserializeOwned> KeyedCsvReader<R, T> {
+    /// Reads the header row of `reader`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the header row can not be read or lacks the key column.
+    pub fn new(reader: R, format: &CsvFormat) -> Result<Self, ReadCsvError> {
+        let mut reader = ::csv::ReaderBuilder::new()
+            .delimiter(format.delimiter)
+            .from_reader(reader);
+        let headers = reader.headers().map_err(ReadCsvError::Csv)?;
+        let key_index = headers
+            .iter()
+            .position(|header| header == format.key_column)
+            .ok_or_else(|| ReadCsvError::MissingKeyColumn {
+                column: format.key_column.clone(),
+            })?;
+        let attr_headers = without(headers, key_index);
+        Ok(Self {
+            records: reader.into_records(),
+            key_index,
+            attr_headers,
+            attrs: PhantomData,
+        })
+    }
+}
+
+impl<R: Read, T: DeserializeOwned> Iterator for KeyedCsvReader<R, T> {
+    type Item = Result<(S3PathBuf, T), ReadCsvError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record = match self.records.next()? {
+            Ok(record) => record,
+            Err(err) => return Some(Err(ReadCsvError::Csv(err))),
+        };
+        let line = record.position().map_or(0, ::csv::Position::line);
+        let path = match S3PathBuf::strict_parse(&record[self.key_index]) {
+            Ok(path) => path,
+            Err(err) => {
+                return Some(Err(ReadCsvError::InvalidKey {
+                    line,
+                    reason: err.to_string(),
+                }))
+            }
+        };
+        Some(
+            without(&record, self.key_index)
+                .deserialize(Some(&self.attr_headers))
+                .map(|attrs| (path, attrs))
+                .map_err(ReadCsvError::Csv),
+        )
+    }
+}
+
+/// Writes rows of a key and attributes to a file, starting with a header row.
+///
+/// The key is written to the first column. The header row is written along with the first row,
+/// naming the attribute columns after the fields of its attributes, which must be a struct.
+pub struct KeyedCsvWriter<W: Write> {
+    writer: ::csv::Writer<W>,
+    key_column: String,
+    wrote_headers: bool,
+}
+
+impl<W: Write> KeyedCsvWriter<W> {
+    /// Creates a writer, consider wrapping `writer` in a `BufWriter`.
+    pub fn new(writer: W, format: &CsvFormat) -> Self {
+        Self {
+            writer: ::csv::WriterBuilder::new()
+                .delimiter(format.delimiter)
+                .has_headers(false)
+                .from_writer(writer),
+            key_column: format.key_column.clone(),
+            wrote_headers: false,
+        }
+    }
+
+    /// Writes a row of `path` and `attrs`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if `attrs` can not be serialized or writing fails.
+    pub fn write<T: Serialize>(&mut self, path: &S3Path<'_>, attrs: &T) -> ::csv::Result<()> {
+        if !self.wrote_headers {
+            let mut headers = StringRecord::from(vec![self.key_column.as_str()]);
+            headers.extend(&attr_headers(attrs)?);
+            self.writer.write_record(&headers)?;
+            self.wrote_headers = true;
+        }
+        self.writer.serialize((path.to_string(), attrs))
+    }
+
+    /// Flushes and returns the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if flushing fails.
+    pub fn into_inner(self) -> ::csv::Result<W> {
+        self.writer
+            .into_inner()
+            .map_err(|err| ::csv::Error::from(err.into_error()))
+    }
+}
+
+/// Returns the header names csv derives from the fields of `attrs`.
+fn attr_headers<T: Serialize>(attrs: &T) -> ::csv::Result<StringRecord> {
+    let mut writer = ::csv::Writer::from_writer(Vec::new());
+    writer.serialize(attrs)?;
+    let written = writer
+        .into_inner()
+        .map_err(|err| ::csv::Error::from(err.into_error()))?;
+    ::csv::ReaderBuilder::new()
+        .has_headers(false)
+        .from_reader(written.as_slice())
+        .records()
+        .next()
+        .unwrap_or_else(|| Ok(StringRecord::new()))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::csv::{CsvFormat, KeyedCsvReader, KeyedCsvWriter};
+    use crate::error::ReadCsvError;
+    use crate::S3PathBuf;
+    use assertr::prelude::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Attrs {
+        size: u64,
+        owner: Option<String>,
+    }
+
+    type Row = Result<(S3PathBuf, Attrs), ReadCsvError>;
+
+    fn read(input: &str) -> Result<Vec<Row>, ReadCsvError> {
+        KeyedCsvReader::new(input.as_bytes(), &CsvFormat::csv()).map(Iterator::collect)
+    }
+
+    #[test]
+    fn reads_key_column_at_any_position() {
+        let rows = read("size,key,owner\n1,a/b,alice\n2,\"c,d\",\n").unwrap();
+        assert_that(rows.len()).is_equal_to(2);
+        assert_that(rows[0].as_ref().unwrap().clone()).is_equal_to((
+            S3PathBuf::try_from_str("a/b").unwrap(),
+            Attrs {
+                size: 1,
+                owner: Some("alice".to_owned()),
+            },
+        ));
+        // ',' is not allowed in path components.
+        assert_that(matches!(
+            rows[1],
+            Err(ReadCsvError::InvalidKey { line: 3, .. })
+        ))
+        .is_true();
+    }
+
+    #[test]
+    fn rejects_missing_key_column_and_invalid_attributes() {
+        assert_that(matches!(
+            read("path,size\na,1\n"),
+            Err(ReadCsvError::MissingKeyColumn { .. })
+        ))
+        .is_true();
+        let rows = read("key,size,owner\na,big,\n").unwrap();
+        assert_that(matches!(rows[0], Err(ReadCsvError::Csv(_)))).is_true();
+    }
+
+    #[test]
+    fn write_then_read_round_trips() {
+        let rows = vec![
+            (
+                S3PathBuf::try_from_str("a/b.csv").unwrap(),
+                Attrs {
+                    size: 1,
+                    owner: None,
+                },
+            ),
+            (
+                S3PathBuf::try_from_str("c").unwrap(),
+                Attrs {
+                    size: 2,
+                    owner: Some("bob".to_owned()),
+                },
+            ),
+        ];
+        let mut writer = KeyedCsvWriter::new(Vec::new(), &CsvFormat::csv());
+        for (path, attrs) in &rows {
+            writer.write(path, attrs).unwrap();
+        }
+        let written = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        assert_that(written.as_str()).is_equal_to("key,size,owner\na/b.csv,1,\nc,2,bob\n");
+        let read = read(&written)
+            .unwrap()
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_that(read).is_equal_to(rows);
+    }
+}
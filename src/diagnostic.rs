@@ -0,0 +1,143 @@
+//! Rich [`miette`] diagnostics for invalid keys, pointing at the offending characters of the
+//! original input.
+//!
+//! ```
+//! use miette::Diagnostic;
+//! use s3_path::diagnostic;
+//!
+//! let err = diagnostic::strict_parse("logs/2024 01/app.log").unwrap_err();
+//! assert_eq!((err.offset, err.len), (9, 1));
+//! assert_eq!(err.labels().unwrap().count(), 1);
+//! ```
+
+use crate::error::InvalidComponentKind;
+use crate::{validation, S3PathBuf};
+use miette::{Diagnostic, LabeledSpan, SourceCode};
+use std::fmt::{Display, Formatter};
+
+/// An invalid key, together with the location of its first violation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyDiagnostic {
+    /// The complete input.
+    pub key: String,
+
+    /// Byte offset of the offending part of `key`.
+    pub offset: usize,
+
+    /// Length of the offending part of `key`, in bytes.
+    pub len: usize,
+
+    /// Why the offending part was rejected.
+    pub kind: InvalidComponentKind,
+}
+
+impl Display for KeyDiagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid S3 key '{}': {}", self.key, self.kind)
+    }
+}
+
+impl std::error::Error for KeyDiagnostic {}
+
+impl Diagnostic for KeyDiagnostic {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new("s3_path::invalid_key"))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        let help = match self.kind {
+            InvalidComponentKind::Empty => "remove leading, trailing and repeated slashes",
+            InvalidComponentKind::DisallowedCharacter(_) => {
+                "components may only contain ascii alphanumeric characters, '-', '_' and '.'"
+            }
+            InvalidComponentKind::PathTraversal => "'.' and '..' are not allowed as components",
+        };
+        Some(Box::new(help))
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        Some(&self.key)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        let label = match self.kind {
+            InvalidComponentKind::Empty => "separator without component".to_owned(),
+            InvalidComponentKind::DisallowedCharacter(c) => format!("character '{c}'"),
+            InvalidComponentKind::PathTraversal => "path traversal".to_owned(),
+        };
+        Some(Box::new(std::iter::once(
+            LabeledSpan::new_primary_with_span(Some(label), (self.offset, self.len)),
+        )))
+    }
+}
+
+/// Like [`S3PathBuf::strict_parse`], but locating the first violation in `key` on failure.
+///
+/// # Errors
+///
+/// Returns `Err` whenever [`S3PathBuf::strict_parse`] does.
+pub fn strict_parse(key: &str) -> Result<S3PathBuf, KeyDiagnostic> {
+    S3PathBuf::strict_parse(key).map_err(|_| locate(key))
+}
+
+/// Locates the first violation in `key`, which is known to be invalid.
+fn locate(key: &str) -> KeyDiagnostic {
+    let mut start = 0;
+    for component in key.split('/') {
+        if let Some(kind) = validation::find_violation(component) {
+            let (offset, len) = match kind {
+                // Point at the superfluous separator, the one before a trailing empty component.
+                InvalidComponentKind::Empty if start < key.len() => (start, 1),
+                InvalidComponentKind::Empty => (start - 1, 1),
+                InvalidComponentKind::DisallowedCharacter(c) => {
+                    let index = component.find(c).unwrap_or_default();
+                    (start + index, c.len_utf8())
+                }
+                InvalidComponentKind::PathTraversal => (start, component.len()),
+            };
+            return KeyDiagnostic {
+                key: key.to_owned(),
+                offset,
+                len,
+                kind,
+            };
+        }
+        start += component.len() + 1;
+    }
+    unreachable!("key '{key}' was rejected by strict_parse but contains no violation")
+}
+
+#[cfg(test)]
+mod test {
+    use crate::diagnostic::strict_parse;
+    use crate::error::InvalidComponentKind;
+    use assertr::prelude::*;
+
+    fn span(key: &str) -> (usize, usize) {
+        let err = strict_parse(key).unwrap_err();
+        (err.offset, err.len)
+    }
+
+    #[test]
+    fn points_at_superfluous_separators() {
+        assert_that(span("/a")).is_equal_to((0, 1));
+        assert_that(span("a//b")).is_equal_to((2, 1));
+        assert_that(span("a/b/")).is_equal_to((3, 1));
+    }
+
+    #[test]
+    fn points_at_offending_characters_and_components() {
+        assert_that(span("a/bä")).is_equal_to((3, 2));
+        assert_that(span("a/../b")).is_equal_to((2, 2));
+        let err = strict_parse("a/b c").unwrap_err();
+        assert_that(err.kind).is_equal_to(InvalidComponentKind::DisallowedCharacter(' '));
+        assert_that(err).has_display_value("Invalid S3 key 'a/b c': Character ' ' is not allowed");
+    }
+
+    #[test]
+    fn parses_valid_keys() {
+        assert_that(strict_parse("a/b"))
+            .is_ok()
+            .has_display_value("a/b");
+    }
+}
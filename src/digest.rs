@@ -0,0 +1,93 @@
+//! Digests of rendered keys.
+
+use crate::S3Path;
+
+impl S3Path<'_> {
+    /// Feeds the rendered key (all components joined by '/') into `update`, without allocating.
+    fn digest_rendered(&self, mut update: impl FnMut(&[u8])) {
+        for (i, component) in self.0.iter().enumerate() {
+            if i > 0 {
+                update(b"/");
+            }
+            update(component.as_bytes());
+        }
+    }
+
+    /// Returns the SHA-256 digest of the rendered key, as 64 lowercase hex digits.
+    ///
+    /// Suited for dedup indices and cache keys, as equal paths always render to the same key.
+    ///
+    /// ```
+    /// use s3_path::s3_path;
+    ///
+    /// let path = s3_path!("foo", "bar").unwrap();
+    /// assert_eq!(
+    ///     path.sha256_hex(),
+    ///     "cc5d46bdb4991c6eae3eb739c9c8a7a46fe9654fab79c47b4fe48383b5b25e1c"
+    /// );
+    /// ```
+    #[cfg(feature = "sha2")]
+    #[must_use]
+    pub fn sha256_hex(&self) -> String {
+        use sha2::Digest;
+
+        let mut hasher = sha2::Sha256::new();
+        self.digest_rendered(|bytes| hasher.update(bytes));
+        to_hex(&hasher.finalize())
+    }
+
+    /// Returns the MD5 digest of the rendered key, as 32 lowercase hex digits.
+    ///
+    /// MD5 is broken as a cryptographic hash. Only use this for compatibility with existing,
+    /// ETag-style indices.
+    ///
+    /// ```
+    /// use s3_path::s3_path;
+    ///
+    /// let path = s3_path!("foo", "bar").unwrap();
+    /// assert_eq!(path.md5_hex(), "82d0f0fa8551de8b7eb5ecb65eae0261");
+    /// ```
+    #[cfg(feature = "md5")]
+    #[must_use]
+    pub fn md5_hex(&self) -> String {
+        use md5::Digest;
+
+        let mut hasher = md5::Md5::new();
+        self.digest_rendered(|bytes| hasher.update(bytes));
+        to_hex(&hasher.finalize())
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    use std::fmt::Write;
+
+    bytes.iter().fold(String::new(), |mut hex, b| {
+        let _ = write!(hex, "{b:02x}");
+        hex
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::S3PathBuf;
+    use assertr::prelude::*;
+
+    #[cfg(feature = "sha2")]
+    #[test]
+    fn sha256_hex_hashes_rendered_key() {
+        let empty = S3PathBuf::new();
+        assert_that(empty.sha256_hex()).is_equal_to(
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855".to_owned(),
+        );
+        let a = S3PathBuf::try_from_str("a/bc").unwrap();
+        let b = S3PathBuf::try_from_str("ab/c").unwrap();
+        assert_that(a.sha256_hex() == b.sha256_hex()).is_false();
+    }
+
+    #[cfg(feature = "md5")]
+    #[test]
+    fn md5_hex_hashes_rendered_key() {
+        let empty = S3PathBuf::new();
+        assert_that(empty.md5_hex()).is_equal_to("d41d8cd98f00b204e9800998ecf8427e".to_owned());
+    }
+}
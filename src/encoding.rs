@@ -0,0 +1,206 @@
+//! Embedding binary identifiers, like hashes, into path components.
+//!
+//! Both encodings only produce ascii alphanumeric characters, which are always valid in
+//! components:
+//! - hex: two lowercase hex digits per byte
+//! - base32: the lowercase alphabet of RFC 4648, without padding, 8 characters per 5 bytes
+//!
+//! ```
+//! use s3_path::encoding::decode_base32;
+//! use s3_path::S3PathBuf;
+//!
+//! let mut path = S3PathBuf::try_from(["blobs"]).unwrap();
+//! path.push_base32(b"hello").unwrap();
+//! assert_eq!(path.to_string(), "blobs/nbswy3dp");
+//! assert_eq!(decode_base32(path.last().unwrap()).unwrap(), b"hello");
+//! ```
+
+use crate::error::{InvalidComponentEncoding, InvalidS3PathComponent};
+use crate::S3PathBuf;
+use std::borrow::Cow;
+
+const BASE32_ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+
+impl S3PathBuf {
+    /// Adds `bytes`, hex encoded, as a new component.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when `bytes` is empty, which would result in an empty component.
+    pub fn push_hex(&mut self, bytes: &[u8]) -> Result<&mut Self, InvalidS3PathComponent> {
+        self.push_encoded(encode_hex(bytes))
+    }
+
+    /// Adds `bytes`, base32 encoded, as a new component.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when `bytes` is empty, which would result in an empty component.
+    pub fn push_base32(&mut self, bytes: &[u8]) -> Result<&mut Self, InvalidS3PathComponent> {
+        self.push_encoded(encode_base32(bytes))
+    }
+
+    fn push_encoded(&mut self, component: String) -> Result<&mut Self, InvalidS3PathComponent> {
+        if component.is_empty() {
+            return Err(InvalidS3PathComponent {
+                component,
+                reason: "Component must not be empty".to_string(),
+            });
+        }
+        // Encoded components only consist of ascii alphanumeric characters.
+        self.components.push(Cow::Owned(component));
+        Ok(self)
+    }
+}
+
+/// Encodes `bytes` as lowercase hex digits.
+#[must_use]
+pub fn encode_hex(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for b in bytes {
+        hex.push(char::from(DIGITS[usize::from(b >> 4)]));
+        hex.push(char::from(DIGITS[usize::from(b & 0xf)]));
+    }
+    hex
+}
+
+/// Decodes a component created by [`S3PathBuf::push_hex`]. Uppercase digits are accepted.
+///
+/// # Errors
+///
+/// Returns `Err` when `component` has an odd length or contains non-hex characters.
+pub fn decode_hex(component: &str) -> Result<Vec<u8>, InvalidComponentEncoding> {
+    let invalid = |reason: String| InvalidComponentEncoding {
+        component: component.to_string(),
+        reason,
+    };
+    if component.len() % 2 != 0 {
+        return Err(invalid("Odd number of hex digits".to_string()));
+    }
+    component
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| {
+            let digit = |b: u8| {
+                char::from(b).to_digit(16).ok_or_else(|| {
+                    invalid(format!("Character '{}' is not a hex digit", char::from(b)))
+                })
+            };
+            #[allow(clippy::cast_possible_truncation)]
+            Ok((digit(pair[0])? << 4 | digit(pair[1])?) as u8)
+        })
+        .collect()
+}
+
+/// Encodes `bytes` as unpadded, lowercase base32 (RFC 4648).
+#[must_use]
+pub fn encode_base32(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity((bytes.len() * 8).div_ceil(5));
+    let (mut buffer, mut bits) = (0u32, 0);
+    for b in bytes {
+        buffer = buffer << 8 | u32::from(*b);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(char::from(BASE32_ALPHABET[(buffer >> bits & 31) as usize]));
+        }
+    }
+    if bits > 0 {
+        encoded.push(char::from(
+            BASE32_ALPHABET[(buffer << (5 - bits) & 31) as usize],
+        ));
+    }
+    encoded
+}
+
+/// Decodes a component created by [`S3PathBuf::push_base32`]. Uppercase letters are accepted.
+///
+/// # Errors
+///
+/// Returns `Err` when `component` contains characters outside the base32 alphabet or has a
+/// length no unpadded base32 string can have.
+pub fn decode_base32(component: &str) -> Result<Vec<u8>, InvalidComponentEncoding> {
+    let invalid = |reason: String| InvalidComponentEncoding {
+        component: component.to_string(),
+        reason,
+    };
+    if matches!(component.len() % 8, 1 | 3 | 6) {
+        return Err(invalid("Invalid length".to_string()));
+    }
+    let mut decoded = Vec::with_capacity(component.len() * 5 / 8);
+    let (mut buffer, mut bits) = (0u32, 0);
+    for c in component.chars() {
+        let value = match c.to_ascii_lowercase() {
+            c @ 'a'..='z' => u32::from(c) - u32::from('a'),
+            c @ '2'..='7' => u32::from(c) - u32::from('2') + 26,
+            c => return Err(invalid(format!("Character '{c}' is not a base32 digit"))),
+        };
+        buffer = buffer << 5 | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            #[allow(clippy::cast_possible_truncation)]
+            decoded.push((buffer >> bits) as u8);
+        }
+    }
+    if buffer & ((1 << bits) - 1) != 0 {
+        return Err(invalid("Non-zero trailing bits".to_string()));
+    }
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::encoding::{decode_base32, decode_hex, encode_base32, encode_hex};
+    use crate::S3PathBuf;
+    use assertr::prelude::*;
+
+    #[test]
+    fn base32_matches_rfc_4648_test_vectors() {
+        let vectors = [
+            ("", ""),
+            ("f", "my"),
+            ("fo", "mzxq"),
+            ("foo", "mzxw6"),
+            ("foob", "mzxw6yq"),
+            ("fooba", "mzxw6ytb"),
+            ("foobar", "mzxw6ytboi"),
+        ];
+        for (plain, encoded) in vectors {
+            assert_that(encode_base32(plain.as_bytes())).is_equal_to(encoded.to_owned());
+            assert_that(decode_base32(encoded))
+                .is_ok()
+                .is_equal_to(plain.as_bytes().to_vec());
+        }
+        assert_that(decode_base32("MZXW6")).is_ok();
+    }
+
+    #[test]
+    fn base32_rejects_malformed_input() {
+        assert_that(decode_base32("m")).is_err();
+        assert_that(decode_base32("mzx")).is_err();
+        assert_that(decode_base32("mz")).is_err();
+        assert_that(decode_base32("m1")).is_err();
+    }
+
+    #[test]
+    fn hex_round_trips() {
+        let bytes = [0x00, 0x7f, 0x80, 0xff];
+        assert_that(encode_hex(&bytes)).is_equal_to("007f80ff".to_owned());
+        assert_that(decode_hex("007F80ff"))
+            .is_ok()
+            .is_equal_to(bytes.to_vec());
+        assert_that(decode_hex("abc")).is_err();
+        assert_that(decode_hex("zz")).is_err();
+    }
+
+    #[test]
+    fn pushes_encoded_components() {
+        let mut path = S3PathBuf::new();
+        path.push_hex(&[0xab]).unwrap().push_base32(b"f").unwrap();
+        assert_that(path).has_display_value("ab/my");
+        assert_that(S3PathBuf::new().push_hex(&[])).is_err();
+        assert_that(S3PathBuf::new().push_base32(&[])).is_err();
+    }
+}
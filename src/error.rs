@@ -17,3 +17,389 @@ impl std::fmt::Display for InvalidS3PathComponent {
 }
 
 impl std::error::Error for InvalidS3PathComponent {}
+
+impl From<InvalidS3PathComponent> for std::io::Error {
+    fn from(err: InvalidS3PathComponent) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, err)
+    }
+}
+
+/// Why a component is invalid, without any allocated data.
+///
+/// Returned by [`validate_component_fast`](crate::validate_component_fast).
+/// Convert it into a full [`InvalidS3PathComponent`] using
+/// [`into_error`](InvalidComponentKind::into_error) when needed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InvalidComponentKind {
+    /// The component is empty.
+    Empty,
+
+    /// The component contains the given character, which is not allowed.
+    DisallowedCharacter(char),
+
+    /// The component is equal to `.` or `..`.
+    PathTraversal,
+}
+
+impl InvalidComponentKind {
+    /// Creates the full error for the rejected `component`.
+    #[must_use]
+    pub fn into_error(self, component: &str) -> InvalidS3PathComponent {
+        InvalidS3PathComponent {
+            component: component.to_string(),
+            reason: self.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for InvalidComponentKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => f.write_str("Empty component is not allowed"),
+            Self::DisallowedCharacter(c) => write!(f, "Character '{c}' is not allowed"),
+            Self::PathTraversal => {
+                f.write_str("Potentially path traversing components are forbidden.")
+            }
+        }
+    }
+}
+
+impl std::error::Error for InvalidComponentKind {}
+
+/// A key not conforming to a [`KeyGrammar`](crate::grammar::KeyGrammar).
+#[derive(Debug)]
+pub struct GrammarViolation {
+    /// Index of the first component violating the grammar.
+    pub index: usize,
+    pub reason: String,
+}
+
+impl std::fmt::Display for GrammarViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Key violates grammar at component {}: {}",
+            self.index, self.reason
+        )
+    }
+}
+
+impl std::error::Error for GrammarViolation {}
+
+/// A grammar definition which could not be parsed.
+#[derive(Debug)]
+pub struct InvalidKeyGrammar {
+    pub grammar: String,
+    pub reason: String,
+}
+
+impl std::fmt::Display for InvalidKeyGrammar {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid key grammar '{}': {}", self.grammar, self.reason)
+    }
+}
+
+impl std::error::Error for InvalidKeyGrammar {}
+
+/// A string which is not a valid glob pattern.
+#[derive(Debug)]
+pub struct InvalidGlob {
+    pub glob: String,
+    pub reason: String,
+}
+
+impl std::fmt::Display for InvalidGlob {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid glob '{}': {}", self.glob, self.reason)
+    }
+}
+
+impl std::error::Error for InvalidGlob {}
+
+/// An access policy configuration which could not be parsed.
+#[derive(Debug)]
+pub struct InvalidAccessPolicy {
+    /// The 1-based line number of the invalid rule.
+    pub line: usize,
+    pub reason: String,
+}
+
+impl std::fmt::Display for InvalidAccessPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Invalid access policy in line {}: {}",
+            self.line, self.reason
+        )
+    }
+}
+
+impl std::error::Error for InvalidAccessPolicy {}
+
+/// A string which is not a valid S3 resource ARN pattern.
+#[derive(Debug)]
+pub struct InvalidResourcePattern {
+    pub pattern: String,
+    pub reason: String,
+}
+
+impl std::fmt::Display for InvalidResourcePattern {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Invalid resource pattern '{}': {}",
+            self.pattern, self.reason
+        )
+    }
+}
+
+impl std::error::Error for InvalidResourcePattern {}
+
+/// An object tag violating the constraints of S3.
+#[derive(Debug)]
+pub struct InvalidObjectTag {
+    pub key: String,
+    pub value: String,
+    pub reason: String,
+}
+
+impl std::fmt::Display for InvalidObjectTag {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Invalid object tag '{}={}': {}",
+            self.key, self.value, self.reason
+        )
+    }
+}
+
+impl std::error::Error for InvalidObjectTag {}
+
+/// A component which could not be decoded into bytes.
+#[derive(Debug)]
+pub struct InvalidComponentEncoding {
+    pub component: String,
+    pub reason: String,
+}
+
+impl std::fmt::Display for InvalidComponentEncoding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Cannot decode component '{}': {}",
+            self.component, self.reason
+        )
+    }
+}
+
+impl std::error::Error for InvalidComponentEncoding {}
+
+/// A patch which cannot be applied to a manifest.
+#[derive(Debug)]
+pub struct PatchConflict {
+    pub path: String,
+    pub reason: String,
+}
+
+impl std::fmt::Display for PatchConflict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Cannot apply patch to '{}': {}", self.path, self.reason)
+    }
+}
+
+impl std::error::Error for PatchConflict {}
+
+/// A path which cannot safely be converted into a `std::path::PathBuf`.
+#[derive(Debug)]
+pub struct UnsafeStdPath {
+    /// All invalid components of the path, in order.
+    pub components: Vec<InvalidS3PathComponent>,
+}
+
+impl std::fmt::Display for UnsafeStdPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Path contains unsafe components: ")?;
+        for (i, component) in self.components.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "'{}' ({})", component.component, component.reason)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for UnsafeStdPath {}
+
+/// A failure while executing operations against an [`ObjectStoreLike`](crate::store::ObjectStoreLike)
+/// store.
+#[derive(Debug)]
+pub enum StoreError<E> {
+    /// The store itself failed.
+    Store(E),
+
+    /// The object at the given path does not exist.
+    MissingObject { path: String },
+
+    /// A path could not be migrated. Reported before any object was written.
+    Migration(LayoutMigrationError),
+}
+
+impl<E: std::fmt::Display> std::fmt::Display for StoreError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Store(err) => write!(f, "Store operation failed: {err}"),
+            Self::MissingObject { path } => write!(f, "Object '{path}' does not exist"),
+            Self::Migration(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for StoreError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Store(err) => Some(err),
+            Self::MissingObject { .. } => None,
+            Self::Migration(err) => Some(err),
+        }
+    }
+}
+
+/// A path which could not be migrated to another layout version.
+#[derive(Debug)]
+pub struct LayoutMigrationError {
+    pub path: String,
+    pub reason: String,
+}
+
+impl std::fmt::Display for LayoutMigrationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Cannot migrate '{}': {}", self.path, self.reason)
+    }
+}
+
+impl std::error::Error for LayoutMigrationError {}
+
+/// A string which is not a valid S3 key.
+#[derive(Debug)]
+pub struct InvalidS3Key {
+    pub key: String,
+    pub reason: String,
+}
+
+impl std::fmt::Display for InvalidS3Key {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid S3 key '{}': {}", self.key, self.reason)
+    }
+}
+
+impl std::error::Error for InvalidS3Key {}
+
+impl From<InvalidS3Key> for std::io::Error {
+    fn from(err: InvalidS3Key) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, err)
+    }
+}
+
+/// A failure while reading a newline-delimited key file.
+#[cfg(feature = "io")]
+#[derive(Debug)]
+pub enum ReadNdKeysError {
+    /// Reading from the underlying reader failed.
+    Io(std::io::Error),
+
+    /// The key in the given line (starting at 1) is invalid.
+    InvalidKey { line: usize, reason: String },
+}
+
+#[cfg(feature = "io")]
+impl std::fmt::Display for ReadNdKeysError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "Failed to read keys: {err}"),
+            Self::InvalidKey { line, reason } => write!(f, "Invalid key in line {line}: {reason}"),
+        }
+    }
+}
+
+#[cfg(feature = "io")]
+impl std::error::Error for ReadNdKeysError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::InvalidKey { .. } => None,
+        }
+    }
+}
+
+/// A failure while reading a CSV or TSV file of keys with attributes.
+#[cfg(feature = "csv")]
+#[derive(Debug)]
+pub enum ReadCsvError {
+    /// Reading or deserializing a record failed.
+    Csv(::csv::Error),
+
+    /// The header row lacks the key column.
+    MissingKeyColumn { column: String },
+
+    /// The key in the given line (starting at 1) is invalid.
+    InvalidKey { line: u64, reason: String },
+}
+
+#[cfg(feature = "csv")]
+impl std::fmt::Display for ReadCsvError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Csv(err) => write!(f, "Failed to read records: {err}"),
+            Self::MissingKeyColumn { column } => write!(f, "Missing key column '{column}'"),
+            Self::InvalidKey { line, reason } => write!(f, "Invalid key in line {line}: {reason}"),
+        }
+    }
+}
+
+#[cfg(feature = "csv")]
+impl std::error::Error for ReadCsvError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Csv(err) => Some(err),
+            Self::MissingKeyColumn { .. } | Self::InvalidKey { .. } => None,
+        }
+    }
+}
+
+/// A failure while reading a newline-delimited JSON manifest.
+#[cfg(feature = "jsonl")]
+#[derive(Debug)]
+pub enum ReadJsonlError {
+    /// Reading from the underlying reader failed.
+    Io(std::io::Error),
+
+    /// The record in the given line (starting at 1) is not a valid manifest record.
+    InvalidRecord { line: usize, reason: String },
+
+    /// The key in the given line (starting at 1) is invalid.
+    InvalidKey { line: usize, reason: String },
+}
+
+#[cfg(feature = "jsonl")]
+impl std::fmt::Display for ReadJsonlError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "Failed to read records: {err}"),
+            Self::InvalidRecord { line, reason } => {
+                write!(f, "Invalid record in line {line}: {reason}")
+            }
+            Self::InvalidKey { line, reason } => write!(f, "Invalid key in line {line}: {reason}"),
+        }
+    }
+}
+
+#[cfg(feature = "jsonl")]
+impl std::error::Error for ReadJsonlError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::InvalidRecord { .. } | Self::InvalidKey { .. } => None,
+        }
+    }
+}
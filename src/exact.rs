@@ -0,0 +1,236 @@
+//! Paths remembering the exact form of the key they were parsed from.
+
+use crate::error::InvalidS3PathComponent;
+use crate::{telemetry, S3Path, S3PathBuf};
+use std::fmt::Formatter;
+
+/// How to treat a leading slash when parsing keys, as some SDKs and APIs treat `/foo` and `foo`
+/// as different keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeadingSlash {
+    /// Silently drop a leading slash.
+    Strip,
+
+    /// Keep a leading slash, flagging it in the parsed [`ExactS3Path`].
+    Keep,
+
+    /// Fail parsing when encountering a leading slash.
+    Reject,
+}
+
+/// Renders a path with a leading slash. Created by [`S3Path::display_rooted`].
+#[derive(Debug, Clone, Copy)]
+pub struct RootedDisplay<'p, 'i>(&'p S3Path<'i>);
+
+impl std::fmt::Display for RootedDisplay<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "/{}", self.0)
+    }
+}
+
+impl<'i> S3Path<'i> {
+    /// Returns a value rendering this path with a leading slash, e.g. `/foo/bar`.
+    ///
+    /// ```
+    /// use s3_path::s3_path;
+    ///
+    /// let path = s3_path!("foo", "bar").unwrap();
+    /// assert_eq!(path.display_rooted().to_string(), "/foo/bar");
+    /// ```
+    #[must_use]
+    pub fn display_rooted(&self) -> RootedDisplay<'_, 'i> {
+        RootedDisplay(self)
+    }
+}
+
+/// A path, together with whether the key it was parsed from had a leading and/or trailing
+/// slash. Created by [`S3PathBuf::try_from_str_exact`].
+///
+/// Renders back to exactly the parsed key, allowing proxies to forward keys byte-exactly.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ExactS3Path {
+    path: S3PathBuf,
+    leading_slash: bool,
+    trailing_slash: bool,
+}
+
+impl ExactS3Path {
+    /// Returns the path, without any leading or trailing slash.
+    #[must_use]
+    pub fn path(&self) -> &S3PathBuf {
+        &self.path
+    }
+
+    /// Converts into the path, dropping the information about leading and trailing slashes.
+    #[must_use]
+    pub fn into_path(self) -> S3PathBuf {
+        self.path
+    }
+
+    /// Returns true if the parsed key started with a slash.
+    #[must_use]
+    pub fn has_leading_slash(&self) -> bool {
+        self.leading_slash
+    }
+
+    /// Returns true if the parsed key ended with a slash.
+    #[must_use]
+    pub fn has_trailing_slash(&self) -> bool {
+        self.trailing_slash
+    }
+}
+
+impl std::fmt::Display for ExactS3Path {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.leading_slash {
+            f.write_str("/")?;
+        }
+        write!(f, "{}", self.path)?;
+        if self.trailing_slash {
+            f.write_str("/")?;
+        }
+        Ok(())
+    }
+}
+
+impl S3PathBuf {
+    /// Parses `string` like `strict_parse`, but allows for a single leading and a single
+    /// trailing slash, which are recorded in the returned [`ExactS3Path`].
+    ///
+    /// Other than `try_from_str`, this never silently drops empty components.
+    ///
+    /// ```
+    /// use s3_path::S3PathBuf;
+    ///
+    /// let exact = S3PathBuf::try_from_str_exact("/foo/bar/").unwrap();
+    /// assert_eq!(exact.path().to_string(), "foo/bar");
+    /// assert_eq!(exact.to_string(), "/foo/bar/");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when any component read
+    /// - is empty (caused by repeated slashes)
+    /// - contains characters other than: ascii alphanumeric characters, '-', '_' and '.'
+    /// - is equal to `.` or `..`
+    pub fn try_from_str_exact(
+        string: impl AsRef<str>,
+    ) -> Result<ExactS3Path, InvalidS3PathComponent> {
+        let string = string.as_ref();
+        let (leading_slash, string) = match string.strip_prefix('/') {
+            Some(rest) => (true, rest),
+            None => (false, string),
+        };
+        let (trailing_slash, string) = match string.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, string),
+        };
+        Ok(ExactS3Path {
+            path: S3PathBuf::strict_parse(string)?,
+            leading_slash,
+            trailing_slash,
+        })
+    }
+
+    /// Parses `string` like `try_from_str_exact`, treating a leading slash as dictated by
+    /// `policy`.
+    ///
+    /// ```
+    /// use s3_path::exact::LeadingSlash;
+    /// use s3_path::S3PathBuf;
+    ///
+    /// let kept = S3PathBuf::try_from_str_with_leading_slash("/foo", LeadingSlash::Keep).unwrap();
+    /// assert_eq!(kept.to_string(), "/foo");
+    ///
+    /// let stripped = S3PathBuf::try_from_str_with_leading_slash("/foo", LeadingSlash::Strip).unwrap();
+    /// assert_eq!(stripped.to_string(), "foo");
+    ///
+    /// assert!(S3PathBuf::try_from_str_with_leading_slash("/foo", LeadingSlash::Reject).is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when
+    /// - `string` starts with a slash and `policy` is `LeadingSlash::Reject`
+    /// - any component read is empty (caused by repeated slashes)
+    /// - any component read contains characters other than: ascii alphanumeric characters, '-',
+    ///   '_' and '.'
+    /// - any component read is equal to `.` or `..`
+    pub fn try_from_str_with_leading_slash(
+        string: impl AsRef<str>,
+        policy: LeadingSlash,
+    ) -> Result<ExactS3Path, InvalidS3PathComponent> {
+        let string = string.as_ref();
+        let mut exact = Self::try_from_str_exact(string)?;
+        if exact.leading_slash {
+            match policy {
+                LeadingSlash::Strip => exact.leading_slash = false,
+                LeadingSlash::Keep => {}
+                LeadingSlash::Reject => {
+                    return Err(telemetry::report(InvalidS3PathComponent {
+                        component: String::new(),
+                        reason: format!("Leading slash in '{string}' is not allowed"),
+                    }));
+                }
+            }
+        }
+        Ok(exact)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::exact::LeadingSlash;
+    use crate::S3PathBuf;
+    use assertr::prelude::*;
+
+    #[test]
+    fn display_rooted_prepends_slash() {
+        assert_that(S3PathBuf::new().display_rooted()).has_display_value("/");
+        assert_that(
+            S3PathBuf::try_from(["foo", "bar"])
+                .unwrap()
+                .display_rooted(),
+        )
+        .has_display_value("/foo/bar");
+    }
+
+    #[test]
+    fn leading_slash_policy() {
+        let parse = S3PathBuf::try_from_str_with_leading_slash;
+        assert_that(parse("/foo/", LeadingSlash::Strip))
+            .is_ok()
+            .has_display_value("foo/");
+        assert_that(parse("/foo/", LeadingSlash::Keep))
+            .is_ok()
+            .has_display_value("/foo/");
+        assert_that(parse("foo", LeadingSlash::Reject))
+            .is_ok()
+            .has_display_value("foo");
+        assert_that(parse("/foo", LeadingSlash::Reject)).is_err();
+    }
+
+    #[test]
+    fn reproduces_the_original_key() {
+        for key in ["", "/", "//", "foo", "/foo", "foo/", "/foo/bar/"] {
+            assert_that(S3PathBuf::try_from_str_exact(key))
+                .is_ok()
+                .has_display_value(key);
+        }
+    }
+
+    #[test]
+    fn records_leading_and_trailing_slashes() {
+        let exact = S3PathBuf::try_from_str_exact("/foo/bar").unwrap();
+        assert_that(exact.has_leading_slash()).is_true();
+        assert_that(exact.has_trailing_slash()).is_false();
+        assert_that(exact.into_path()).has_display_value("foo/bar");
+    }
+
+    #[test]
+    fn rejects_empty_components() {
+        assert_that(S3PathBuf::try_from_str_exact("foo//bar")).is_err();
+        assert_that(S3PathBuf::try_from_str_exact("//foo")).is_err();
+        assert_that(S3PathBuf::try_from_str_exact("///")).is_err();
+    }
+}
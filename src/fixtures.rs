@@ -0,0 +1,229 @@
+//! Deterministic trees of keys, for seeding S3-compatible test servers like MinIO or LocalStack.
+//!
+//! ```
+//! use s3_path::fixtures::KeyTree;
+//!
+//! let tree = KeyTree {
+//!     depth: 2,
+//!     fan_out: 3,
+//!     files_per_directory: 4,
+//!     ..KeyTree::default()
+//! };
+//! assert_eq!(tree.len(), 36);
+//!
+//! let mut uploaded = Vec::new();
+//! tree.put_all(|path, meta| {
+//!     uploaded.push((path.to_string(), meta.size));
+//!     Ok::<(), std::io::Error>(())
+//! })
+//! .unwrap();
+//! assert_eq!(uploaded.len(), 36);
+//! assert_eq!(tree.manifest().len(), 36);
+//! ```
+
+use crate::manifest::{Manifest, ObjectMeta};
+use crate::rng::SplitMix64;
+use crate::S3PathBuf;
+use std::borrow::Cow;
+use std::time::{Duration, SystemTime};
+
+/// Shape of a tree of keys: `fan_out` directories per level, `depth` levels deep, with
+/// `files_per_directory` files in each directory of the last level.
+///
+/// Directories are named `d<level>-<index>` and files `f-<index>.<extension>`. Equal trees
+/// always produce the same keys and metadata.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyTree {
+    /// Number of directory levels above the files.
+    pub depth: usize,
+
+    /// Number of subdirectories of each directory above the last level.
+    pub fan_out: usize,
+
+    /// Number of files in each directory of the last level.
+    pub files_per_directory: usize,
+
+    /// File extensions (without the leading '.') with their relative weights. File names have
+    /// no extension if empty.
+    pub extensions: Vec<(String, u32)>,
+
+    /// Maximum object size in bytes, as reported in the generated metadata.
+    pub max_size: u64,
+
+    pub seed: u64,
+}
+
+impl Default for KeyTree {
+    fn default() -> Self {
+        Self {
+            depth: 2,
+            fan_out: 4,
+            files_per_directory: 8,
+            extensions: vec![
+                ("json".to_owned(), 4),
+                ("csv".to_owned(), 2),
+                ("parquet".to_owned(), 1),
+            ],
+            max_size: 1024,
+            seed: 0,
+        }
+    }
+}
+
+impl KeyTree {
+    /// Returns the number of keys in this tree.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.directories() * self.files_per_directory
+    }
+
+    /// Returns true if this tree has no keys.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn directories(&self) -> usize {
+        (0..self.depth).fold(1, |count, _| count * self.fan_out)
+    }
+
+    /// Returns all keys of this tree with their metadata, in depth-first order.
+    pub fn entries(&self) -> impl Iterator<Item = (S3PathBuf, ObjectMeta)> + '_ {
+        let mut rng = SplitMix64::new(self.seed);
+        let total_weight = self.extensions.iter().map(|(_, w)| *w).sum::<u32>();
+        let epoch = SystemTime::UNIX_EPOCH + Duration::from_secs(1_704_067_200); // 2024-01-01
+        (0..self.directories()).flat_map(move |directory| {
+            let mut rng = SplitMix64::new(rng.next_u64());
+            (0..self.files_per_directory).map(move |file| {
+                let mut path = S3PathBuf::new();
+                let mut divisor = self.directories();
+                for level in 0..self.depth {
+                    divisor /= self.fan_out;
+                    let index = directory / divisor % self.fan_out;
+                    path.components
+                        .push(Cow::Owned(format!("d{level}-{index}")));
+                }
+                let mut file_name = format!("f-{file}");
+                if total_weight > 0 {
+                    let mut pick = rng.next_u64() % u64::from(total_weight);
+                    for (extension, weight) in &self.extensions {
+                        let weight = u64::from(*weight);
+                        if pick < weight {
+                            file_name.push('.');
+                            file_name.push_str(extension);
+                            break;
+                        }
+                        pick -= weight;
+                    }
+                }
+                path.components.push(Cow::Owned(file_name));
+                let meta = ObjectMeta {
+                    size: rng.next_u64() % self.max_size.saturating_add(1),
+                    etag: format!("\"{:016x}{:016x}\"", rng.next_u64(), rng.next_u64()),
+                    last_modified: epoch + Duration::from_secs(rng.next_u64() % 86_400),
+                };
+                (path, meta)
+            })
+        })
+    }
+
+    /// Returns all keys of this tree, in depth-first order.
+    pub fn keys(&self) -> impl Iterator<Item = S3PathBuf> + '_ {
+        self.entries().map(|(path, _)| path)
+    }
+
+    /// Collects all entries of this tree into a manifest.
+    #[must_use]
+    pub fn manifest(&self) -> Manifest {
+        self.entries().collect()
+    }
+
+    /// Calls `put` for every entry of this tree, e.g. uploading an object of `meta.size` bytes
+    /// to a test server. Returns the number of entries put.
+    ///
+    /// # Errors
+    ///
+    /// Stops at and returns the first error returned by `put`.
+    pub fn put_all<E>(
+        &self,
+        mut put: impl FnMut(&S3PathBuf, &ObjectMeta) -> Result<(), E>,
+    ) -> Result<usize, E> {
+        let mut count = 0;
+        for (path, meta) in self.entries() {
+            put(&path, &meta)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::fixtures::KeyTree;
+    use assertr::prelude::*;
+
+    #[test]
+    fn generates_deterministic_tree() {
+        let tree = KeyTree {
+            depth: 2,
+            fan_out: 2,
+            files_per_directory: 1,
+            extensions: Vec::new(),
+            ..KeyTree::default()
+        };
+        let keys = tree.keys().map(|key| key.to_string()).collect::<Vec<_>>();
+        assert_that(keys.clone()).is_equal_to(
+            [
+                "d0-0/d1-0/f-0",
+                "d0-0/d1-1/f-0",
+                "d0-1/d1-0/f-0",
+                "d0-1/d1-1/f-0",
+            ]
+            .map(String::from)
+            .to_vec(),
+        );
+        assert_that(tree.manifest()).is_equal_to(tree.manifest());
+    }
+
+    #[test]
+    fn mixes_extensions_by_weight() {
+        let tree = KeyTree {
+            depth: 1,
+            fan_out: 1,
+            files_per_directory: 1000,
+            extensions: vec![
+                ("a".to_owned(), 3),
+                ("b".to_owned(), 1),
+                ("c".to_owned(), 0),
+            ],
+            ..KeyTree::default()
+        };
+        let count = |ext: &str| {
+            tree.keys()
+                .filter(|key| key.last().is_some_and(|name| name.ends_with(ext)))
+                .count()
+        };
+        assert_that(count(".a")).is_greater_than(650);
+        assert_that(count(".b")).is_greater_than(150);
+        assert_that(count(".c")).is_equal_to(0);
+    }
+
+    #[test]
+    fn put_all_stops_at_first_error() {
+        let tree = KeyTree::default();
+        let mut calls = 0;
+        let result = tree.put_all(|_, _| {
+            calls += 1;
+            if calls == 3 {
+                Err("full")
+            } else {
+                Ok(())
+            }
+        });
+        assert_that(result).is_err();
+        assert_that(calls).is_equal_to(3);
+        assert_that(tree.put_all(|_, _| Ok::<(), ()>(())))
+            .is_ok()
+            .is_equal_to(128);
+    }
+}
@@ -0,0 +1,366 @@
+/// Creates an `S3PathBuf` from a `format!`-style template, e.g.
+/// `s3_format!("exports/{tenant}/{}.csv", id, tenant = name)`.
+///
+/// The literal parts of the template are validated at compile time: they may only contain
+/// allowed characters, and must not produce empty, `.` or `..` components. Every interpolated
+/// value is validated at runtime, after formatting, as exactly one component, so values can never
+/// inject further components, e.g. through a '/'. Values may still be combined with literal
+/// parts into a single component, like `{}.csv`.
+///
+/// Paths, i.e. values implementing `AsRef<S3Path>`, are the exception: they are interpolated with
+/// all their components, like a root prefix in `{root}/exports`.
+///
+/// All values must be passed as arguments, positional or named. Variables captured from the
+/// surrounding scope, like `{tenant}` without a `tenant = ...` argument, do not compile, as they
+/// could not be validated.
+///
+/// ```
+/// use s3_path::{s3_format, S3PathBuf};
+///
+/// let tenant = "acme";
+/// let path = s3_format!("exports/{tenant}/{:03}.csv", 42, tenant = tenant).unwrap();
+/// assert_eq!(path.to_string(), "exports/acme/042.csv");
+///
+/// let root = S3PathBuf::try_from_str("data/v2").unwrap();
+/// let path = s3_format!(
+///     "{root}/{tenant}/exports/{name}.csv",
+///     root = root,
+///     tenant = tenant,
+///     name = "q1",
+/// )
+/// .unwrap();
+/// assert_eq!(path.to_string(), "data/v2/acme/exports/q1.csv");
+///
+/// assert!(s3_format!("exports/{}.csv", "my report").is_err());
+/// assert!(s3_format!("exports/{}.csv", "../secrets").is_err());
+/// ```
+///
+/// Invalid literal parts and captured variables do not compile:
+///
+/// ```compile_fail
+/// let path = s3_path::s3_format!("my exports/{}.csv", "report");
+/// ```
+///
+/// ```compile_fail
+/// let path = s3_path::s3_format!("exports//{}.csv", "report");
+/// ```
+///
+/// ```compile_fail
+/// let name = "report";
+/// let path = s3_path::s3_format!("exports/{name}.csv");
+/// ```
+#[macro_export]
+macro_rules! s3_format {
+    ($template:literal $(, $($args:tt)*)?) => {
+        $crate::s3_format!(@munch $template, state, [], [], $($($args)*)?)
+    };
+    (@munch $template:literal, $state:ident, [$($names:expr),*], [$($out:tt)*],) => {{
+        const _: () = $crate::__check_format_template($template, &[$($names),*]);
+        #[allow(unused_imports)]
+        use $crate::{__FormatComponentArg as _, __FormatPathArg as _};
+        let $state = $crate::__FormatState::new();
+        let result = ::std::fmt::Write::write_fmt(
+            &mut &$state,
+            ::std::format_args!($template, $($out)*),
+        );
+        $state.finish(result)
+    }};
+    (@munch $template:literal, $state:ident, [$($names:expr),*], [$($out:tt)*], $name:ident = $value:expr $(, $($rest:tt)*)?) => {
+        $crate::s3_format!(
+            @munch $template,
+            $state,
+            [$($names,)* ::std::stringify!($name)],
+            [$($out)* $name = (&$state.arg(&$value)).wrap(),],
+            $($($rest)*)?
+        )
+    };
+    (@munch $template:literal, $state:ident, [$($names:expr),*], [$($out:tt)*], $value:expr $(, $($rest:tt)*)?) => {
+        $crate::s3_format!(
+            @munch $template,
+            $state,
+            [$($names),*],
+            [$($out)* (&$state.arg(&$value)).wrap(),],
+            $($($rest)*)?
+        )
+    };
+}
+
+use crate::error::InvalidS3PathComponent;
+use crate::{validation, S3Path, S3PathBuf};
+use std::cell::{Cell, RefCell};
+use std::fmt::Formatter;
+
+/// State of a single [`s3_format!`] invocation: the key rendered so far and the first
+/// interpolated value which is not a valid component.
+#[doc(hidden)]
+#[derive(Default)]
+pub struct FormatState {
+    rendered: RefCell<String>,
+    error: Cell<Option<InvalidS3PathComponent>>,
+}
+
+/// An interpolated value, wrapped into a [`FormatPath`] if it is a path, or a [`FormatComponent`]
+/// otherwise, by calling `(&arg).wrap()` with [`PathArg`] and [`ComponentArg`] in scope.
+#[doc(hidden)]
+pub struct FormatArg<'s, T: ?Sized> {
+    state: &'s FormatState,
+    value: &'s T,
+}
+
+/// An interpolated value, validated as exactly one component once formatted.
+#[doc(hidden)]
+pub struct FormatComponent<'s, T: ?Sized> {
+    state: &'s FormatState,
+    value: &'s T,
+}
+
+/// An interpolated path, rendered with all its components.
+#[doc(hidden)]
+pub struct FormatPath<'s, P: ?Sized> {
+    value: &'s P,
+}
+
+/// Wraps path arguments. Takes precedence over [`ComponentArg`], as it needs no autoref.
+#[doc(hidden)]
+pub trait PathArg {
+    type Wrapped;
+
+    fn wrap(&self) -> Self::Wrapped;
+}
+
+impl<'s, 'p, P: AsRef<S3Path<'p>> + ?Sized> PathArg for FormatArg<'s, P> {
+    type Wrapped = FormatPath<'s, P>;
+
+    fn wrap(&self) -> Self::Wrapped {
+        FormatPath { value: self.value }
+    }
+}
+
+/// Wraps all other arguments.
+#[doc(hidden)]
+pub trait ComponentArg {
+    type Wrapped;
+
+    fn wrap(&self) -> Self::Wrapped;
+}
+
+impl<'s, T: ?Sized> ComponentArg for &FormatArg<'s, T> {
+    type Wrapped = FormatComponent<'s, T>;
+
+    fn wrap(&self) -> Self::Wrapped {
+        FormatComponent {
+            state: self.state,
+            value: self.value,
+        }
+    }
+}
+
+impl FormatState {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn arg<'s, T: ?Sized>(&'s self, value: &'s T) -> FormatArg<'s, T> {
+        FormatArg { state: self, value }
+    }
+
+    /// Parses the rendered key, or returns the first invalid interpolated value.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if an interpolated value or the rendered key is invalid.
+    pub fn finish(self, result: std::fmt::Result) -> Result<S3PathBuf, InvalidS3PathComponent> {
+        if let Some(err) = self.error.take() {
+            return Err(err);
+        }
+        result.expect("formatting into a String cannot fail");
+        S3PathBuf::strict_parse(self.rendered.into_inner())
+    }
+}
+
+impl std::fmt::Write for &FormatState {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.rendered.borrow_mut().push_str(s);
+        Ok(())
+    }
+}
+
+macro_rules! impl_format_component {
+    ($($trait:ident),*) => {$(
+        impl<T: std::fmt::$trait + ?Sized> std::fmt::$trait for FormatComponent<'_, T> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                let start = self.state.rendered.borrow().len();
+                std::fmt::$trait::fmt(self.value, f)?;
+                let rendered = self.state.rendered.borrow();
+                validation::validate_component(&rendered[start..]).map_err(|err| {
+                    self.state.error.set(Some(err));
+                    std::fmt::Error
+                })
+            }
+        }
+    )*};
+}
+
+impl_format_component!(Display, Debug, LowerHex, UpperHex, Octal, Binary);
+
+// The rendered key is parsed strictly once formatted, which validates the components of paths.
+impl<'p, P: AsRef<S3Path<'p>> + ?Sized> std::fmt::Display for FormatPath<'_, P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(self.value.as_ref(), f)
+    }
+}
+
+/// Checks the literal parts of an [`s3_format!`] template, panicking (at compile time, when
+/// called in a const context) if they cannot be part of a valid key, or if a placeholder names
+/// none of the named arguments `names`.
+#[doc(hidden)]
+pub const fn check_template(template: &str, names: &[&str]) {
+    let bytes = template.as_bytes();
+    if bytes.is_empty() {
+        return;
+    }
+    let mut i = 0;
+    // Length of the current component, counting placeholders as one.
+    let mut len = 0;
+    // Number of '.' in the current component, if it only consists of literal dots.
+    let mut dots = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        match b {
+            b'/' => {
+                check_component(len, dots);
+                len = 0;
+                dots = 0;
+            }
+            b'{' => {
+                if i + 1 < bytes.len() && bytes[i + 1] == b'{' {
+                    panic!("s3_format!: braces are not allowed in path components");
+                }
+                let name_start = i + 1;
+                while i < bytes.len() && bytes[i] != b'}' && bytes[i] != b':' {
+                    i += 1;
+                }
+                check_placeholder(bytes, name_start, i, names);
+                while i < bytes.len() && bytes[i] != b'}' {
+                    i += 1;
+                }
+                if i == bytes.len() {
+                    panic!("s3_format!: unclosed placeholder");
+                }
+                len += 1;
+                dots = usize::MAX;
+            }
+            b'}' => panic!("s3_format!: braces are not allowed in path components"),
+            b'.' => {
+                len += 1;
+                if dots != usize::MAX {
+                    dots += 1;
+                }
+            }
+            _ if b.is_ascii_alphanumeric() || b == b'-' || b == b'_' => {
+                len += 1;
+                dots = usize::MAX;
+            }
+            _ => panic!("s3_format!: the template contains a character not allowed in keys"),
+        }
+        i += 1;
+    }
+    check_component(len, dots);
+}
+
+/// Checks that the placeholder argument `bytes[start..end]` is positional or one of `names`.
+const fn check_placeholder(bytes: &[u8], start: usize, end: usize, names: &[&str]) {
+    if start == end || bytes[start].is_ascii_digit() {
+        return;
+    }
+    let mut n = 0;
+    while n < names.len() {
+        let name = names[n].as_bytes();
+        if name.len() == end - start {
+            let mut j = 0;
+            while j < name.len() && name[j] == bytes[start + j] {
+                j += 1;
+            }
+            if j == name.len() {
+                return;
+            }
+        }
+        n += 1;
+    }
+    panic!("s3_format!: pass interpolated values as arguments, e.g. `name = name`");
+}
+
+const fn check_component(len: usize, dots: usize) {
+    if len == 0 {
+        panic!("s3_format!: leading, trailing or repeated '/' produce empty components");
+    }
+    if len == dots && dots <= 2 {
+        panic!("s3_format!: '.' and '..' are not allowed as components");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::format::check_template;
+    use assertr::prelude::*;
+
+    #[test]
+    fn accepts_valid_templates() {
+        for template in ["", "a", "{}/b", "{}.csv/x-{0:>3}_z", "a/...", "{a}./.b"] {
+            check_template(template, &["a"]);
+        }
+        let x = "b";
+        assert_that(s3_format!("a/{x}/{}", 1, x = x))
+            .is_ok()
+            .has_display_value("a/b/1");
+        assert_that(s3_format!("{:02x}", 255_u8,))
+            .is_ok()
+            .has_display_value("ff");
+        assert_that(s3_format!("a")).is_ok().has_display_value("a");
+    }
+
+    #[test]
+    fn rejects_invalid_templates() {
+        for template in [
+            "/a", "a/", "a//b", "a/../b", ".", "a b", "a/{{b}}", "a/{b", "a}", "{b}", "{ab}",
+        ] {
+            assert_that(std::panic::catch_unwind(|| {
+                check_template(template, &["a"])
+            }))
+            .is_err();
+        }
+    }
+
+    #[test]
+    fn validates_each_interpolated_value_as_one_component() {
+        let up = "..";
+        assert_that(s3_format!("tenants/{id}/data", id = "a/../b")).is_err();
+        assert_that(s3_format!("tenants/{id}/data", id = "x/y")).is_err();
+        assert_that(s3_format!("a/{}", up)).is_err();
+        assert_that(s3_format!("a/{}", "")).is_err();
+        // Values may be part of a component, as long as they are valid components on their own.
+        assert_that(s3_format!("a/{}-{}.csv", "v1", 2))
+            .is_ok()
+            .has_display_value("a/v1-2.csv");
+        assert_that(s3_format!("a/{}.csv", ".")).is_err();
+        assert_that(s3_format!("a/{}", "b c")).is_err();
+    }
+
+    #[test]
+    fn interpolates_paths_with_all_components() {
+        let root = crate::S3PathBuf::try_from_str("data/v2").unwrap();
+        assert_that(s3_format!("{root}/{}.csv", "q1", root = root))
+            .is_ok()
+            .has_display_value("data/v2/q1.csv");
+        assert_that(s3_format!("{}/x", &root))
+            .is_ok()
+            .has_display_value("data/v2/x");
+        assert_that(s3_format!("{}/x", &*root))
+            .is_ok()
+            .has_display_value("data/v2/x");
+        // Strings are still validated as exactly one component.
+        assert_that(s3_format!("{}/x", root.to_string())).is_err();
+        assert_that(s3_format!("{}/x", crate::S3PathBuf::new())).is_err();
+    }
+}
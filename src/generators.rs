@@ -0,0 +1,350 @@
+//! Deterministic generation of plausible keys, e.g. for benchmarks and load tests, and of
+//! near-miss invalid keys for negative tests.
+
+use crate::rng::SplitMix64;
+use crate::validation::MAX_KEY_LEN;
+use crate::{S3Path, S3PathBuf};
+use std::borrow::Cow;
+
+const WORDS: &[&str] = &[
+    "archive",
+    "backups",
+    "data",
+    "events",
+    "exports",
+    "images",
+    "logs",
+    "media",
+    "processed",
+    "raw",
+    "reports",
+    "staging",
+    "tenants",
+    "uploads",
+    "users",
+];
+
+/// Shape of the keys created by [`realistic_keys`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyProfile {
+    /// Minimum number of directory components preceding the file name.
+    pub min_depth: usize,
+
+    /// Maximum number of directory components preceding the file name.
+    pub max_depth: usize,
+
+    /// File extensions (without the leading '.') to choose from. File names have no extension
+    /// if empty.
+    pub extensions: Vec<String>,
+
+    /// Ratio (`0.0` to `1.0`) of keys getting a `YYYY/MM/DD` date partition inserted before the
+    /// file name. Date partitions do not count towards the depth.
+    pub date_partition_ratio: f64,
+}
+
+impl Default for KeyProfile {
+    fn default() -> Self {
+        Self {
+            min_depth: 1,
+            max_depth: 4,
+            extensions: ["csv", "gz", "json", "log", "parquet", "png", "txt"]
+                .map(String::from)
+                .to_vec(),
+            date_partition_ratio: 0.3,
+        }
+    }
+}
+
+/// Returns an endless stream of plausible keys shaped by `profile`.
+///
+/// Equal seeds and profiles always produce the same sequence of keys.
+///
+/// ```
+/// use s3_path::generators::{realistic_keys, KeyProfile};
+///
+/// let keys = realistic_keys(42, KeyProfile::default())
+///     .take(1000)
+///     .collect::<Vec<_>>();
+/// assert_eq!(keys.len(), 1000);
+/// ```
+pub fn realistic_keys(seed: u64, profile: KeyProfile) -> impl Iterator<Item = S3PathBuf> {
+    let mut rng = SplitMix64::new(seed);
+    let max_depth = profile.max_depth.max(profile.min_depth);
+    std::iter::repeat_with(move || {
+        let depth = profile.min_depth + rng.below(max_depth - profile.min_depth + 1);
+        let mut components = Vec::with_capacity(depth + 4);
+        for _ in 0..depth {
+            let word = *rng.pick(WORDS);
+            components.push(match rng.below(3) {
+                0 => Cow::Owned(format!("{word}-{}", rng.below(100))),
+                _ => Cow::Borrowed(word),
+            });
+        }
+        if rng.next_f64() < profile.date_partition_ratio {
+            components.push(Cow::Owned(format!("{:04}", 2015 + rng.below(12))));
+            components.push(Cow::Owned(format!("{:02}", 1 + rng.below(12))));
+            components.push(Cow::Owned(format!("{:02}", 1 + rng.below(28))));
+        }
+        let mut file_name = format!("{}-{:06}", rng.pick(WORDS), rng.below(1_000_000));
+        if !profile.extensions.is_empty() {
+            file_name.push('.');
+            let extension: &String = rng.pick(&profile.extensions);
+            file_name.push_str(extension);
+        }
+        components.push(Cow::Owned(file_name));
+        S3PathBuf { components }
+    })
+}
+
+/// Characters of the components generated by [`random_key`]. All of them are easy to type on
+/// any keyboard.
+#[cfg(feature = "rand")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Charset {
+    /// `a` to `z` and `0` to `9`.
+    #[default]
+    Lowercase,
+
+    /// `a` to `z`, `A` to `Z` and `0` to `9`.
+    Alphanumeric,
+
+    /// `0` to `9` and `a` to `f`.
+    Hex,
+}
+
+#[cfg(feature = "rand")]
+impl Charset {
+    fn chars(self) -> &'static [u8] {
+        match self {
+            Charset::Lowercase => b"abcdefghijklmnopqrstuvwxyz0123456789",
+            Charset::Alphanumeric => {
+                b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789"
+            }
+            Charset::Hex => b"0123456789abcdef",
+        }
+    }
+}
+
+/// Shape of the keys created by [`random_key`].
+#[cfg(feature = "rand")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RandomKeyOptions {
+    /// Number of components.
+    pub depth: usize,
+
+    /// Characters components are drawn from.
+    pub charset: Charset,
+
+    /// Number of characters of each component. Lengths below 1 are treated as 1.
+    pub len: usize,
+}
+
+#[cfg(feature = "rand")]
+impl Default for RandomKeyOptions {
+    fn default() -> Self {
+        Self {
+            depth: 3,
+            charset: Charset::Lowercase,
+            len: 8,
+        }
+    }
+}
+
+#[cfg(feature = "rand")]
+impl RandomKeyOptions {
+    /// Returns the entropy of the generated keys in bits. Generating `n` keys, the chance of any
+    /// collision is roughly `n² / 2^(bits + 1)`.
+    #[must_use]
+    pub fn entropy_bits(&self) -> f64 {
+        let chars = self.charset.chars().len() as f64;
+        (self.depth * self.len.max(1)) as f64 * chars.log2()
+    }
+}
+
+/// Generates a random key of `options.depth` components, each of `options.len` characters from
+/// `options.charset`. Cheaper than formatting UUIDs, e.g. for load tests creating unique keys at
+/// high rates.
+///
+/// ```
+/// use rand::rngs::SmallRng;
+/// use rand::SeedableRng;
+/// use s3_path::generators::{random_key, Charset, RandomKeyOptions};
+///
+/// let mut rng = SmallRng::seed_from_u64(7);
+/// let options = RandomKeyOptions {
+///     depth: 2,
+///     charset: Charset::Hex,
+///     len: 4,
+/// };
+/// let key = random_key(&mut rng, options);
+/// assert_eq!(key.len(), 2);
+/// assert_eq!(key.to_string().len(), 9);
+/// assert_eq!(options.entropy_bits(), 32.0);
+/// ```
+#[cfg(feature = "rand")]
+pub fn random_key<R: rand::Rng + ?Sized>(rng: &mut R, options: RandomKeyOptions) -> S3PathBuf {
+    let chars = options.charset.chars();
+    S3PathBuf {
+        components: (0..options.depth)
+            .map(|_| {
+                let component = (0..options.len.max(1))
+                    .map(|_| char::from(chars[rng.random_range(0..chars.len())]))
+                    .collect::<String>();
+                Cow::Owned(component)
+            })
+            .collect(),
+    }
+}
+
+/// Produces near-miss invalid variants of `path`, for negative tests of validators and APIs
+/// accepting keys.
+///
+/// For each component, variants inject a space into it or replace it with `.` or `..`. Further
+/// variants contain a double slash between two components, a leading or a trailing slash, and
+/// finally the key with its last component padded to exceed S3's maximum key length of 1024
+/// bytes. All but the overlong key are rejected by [`S3PathBuf::strict_parse`].
+///
+/// ```
+/// use s3_path::generators::mutate_invalid;
+/// use s3_path::s3_path;
+///
+/// let keys = mutate_invalid(s3_path!("a", "bc").unwrap()).collect::<Vec<_>>();
+/// assert_eq!(&keys[..7], [" a/bc", "./bc", "../bc", "a/b c", "a/.", "a/..", "a//bc"]);
+/// assert_eq!(&keys[7..9], ["/a/bc", "a/bc/"]);
+/// assert_eq!(keys[9].len(), 1025);
+/// ```
+pub fn mutate_invalid(path: &S3Path<'_>) -> impl Iterator<Item = String> {
+    let components = path.components().collect::<Vec<_>>();
+    let key = components.join("/");
+    let mut keys = Vec::new();
+    for (index, component) in components.iter().enumerate() {
+        let middle = component
+            .char_indices()
+            .nth(component.chars().count() / 2)
+            .map_or(component.len(), |(at, _)| at);
+        let spaced = format!("{} {}", &component[..middle], &component[middle..]);
+        for replacement in [spaced.as_str(), ".", ".."] {
+            let mut mutated = components.clone();
+            mutated[index] = replacement;
+            keys.push(mutated.join("/"));
+        }
+    }
+    if components.is_empty() {
+        keys.extend([" ", ".", ".."].map(String::from));
+    }
+    for index in 1..components.len() {
+        let mut mutated = components.clone();
+        mutated.insert(index, "");
+        keys.push(mutated.join("/"));
+    }
+    keys.push(format!("/{key}"));
+    if !components.is_empty() {
+        keys.push(format!("{key}/"));
+    }
+    let mut overlong = key;
+    if overlong.is_empty() {
+        overlong.push('a');
+    }
+    let padding = (MAX_KEY_LEN + 1).saturating_sub(overlong.len());
+    overlong.extend(std::iter::repeat('a').take(padding));
+    keys.push(overlong);
+    keys.into_iter()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::generators::{mutate_invalid, realistic_keys, KeyProfile};
+    use crate::S3PathBuf;
+    use assertr::prelude::*;
+
+    #[test]
+    fn is_deterministic() {
+        let first = realistic_keys(7, KeyProfile::default()).take(100);
+        let second = realistic_keys(7, KeyProfile::default()).take(100);
+        assert_that(first.eq(second)).is_true();
+    }
+
+    #[test]
+    fn produces_valid_keys_respecting_the_profile() {
+        let profile = KeyProfile {
+            min_depth: 2,
+            max_depth: 3,
+            extensions: vec!["csv".to_string()],
+            date_partition_ratio: 0.0,
+        };
+        for key in realistic_keys(7, profile).take(1000) {
+            assert_that(S3PathBuf::try_from_str(key.to_string()))
+                .is_ok()
+                .is_equal_to(key.clone());
+            assert_that((3..=4).contains(&key.len())).is_true();
+            assert_that(key.last().unwrap().ends_with(".csv")).is_true();
+        }
+    }
+
+    #[test]
+    fn inserts_date_partitions() {
+        let profile = KeyProfile {
+            min_depth: 1,
+            max_depth: 1,
+            extensions: Vec::new(),
+            date_partition_ratio: 1.0,
+        };
+        for key in realistic_keys(7, profile).take(100) {
+            assert_that(key.len()).is_equal_to(5);
+            assert_that(key.get(1).unwrap().len()).is_equal_to(4);
+            assert_that(key.last().unwrap().contains('.')).is_false();
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "rand")]
+    fn random_keys_are_valid_and_unique() {
+        use crate::generators::{random_key, Charset, RandomKeyOptions};
+        use rand::SeedableRng;
+
+        let mut rng = rand::rngs::SmallRng::seed_from_u64(7);
+        let options = RandomKeyOptions {
+            depth: 2,
+            charset: Charset::Alphanumeric,
+            len: 6,
+        };
+        let keys = (0..1000)
+            .map(|_| random_key(&mut rng, options))
+            .collect::<Vec<_>>();
+        for key in &keys {
+            assert_that(S3PathBuf::strict_parse(key.to_string()))
+                .is_ok()
+                .is_equal_to(key.clone());
+            assert_that(key.to_string().len()).is_equal_to(13);
+        }
+        let mut unique = keys.iter().map(ToString::to_string).collect::<Vec<_>>();
+        unique.sort_unstable();
+        unique.dedup();
+        assert_that(unique.len()).is_equal_to(1000);
+
+        let empty = random_key(&mut rng, RandomKeyOptions { len: 0, ..options });
+        assert_that(empty.to_string().len()).is_equal_to(3);
+    }
+
+    #[test]
+    fn mutations_are_invalid() {
+        for key in ["", "a", "a/b.csv/c", "data/2024-01-31/report.csv"] {
+            let path = S3PathBuf::try_from_str(key).unwrap();
+            let mutated = mutate_invalid(&path).collect::<Vec<_>>();
+            let (overlong, rest) = mutated.split_last().unwrap();
+            for key in rest {
+                assert_that(S3PathBuf::strict_parse(key.as_str())).is_err();
+            }
+            assert_that(overlong.len()).is_greater_than(1024);
+            assert_that(S3PathBuf::strict_parse(overlong.as_str())).is_ok();
+        }
+    }
+
+    #[test]
+    fn mutates_empty_path() {
+        let mutated = mutate_invalid(&S3PathBuf::new()).collect::<Vec<_>>();
+        assert_that(mutated[..4].to_vec())
+            .is_equal_to([" ", ".", "..", "/"].map(String::from).to_vec());
+        assert_that(mutated.len()).is_equal_to(5);
+    }
+}
@@ -0,0 +1,286 @@
+//! Component-wise glob patterns matching paths.
+//!
+//! Patterns are split on '/' into component patterns, matched against the components of a path:
+//!
+//! | Pattern | Matches                                      |
+//! |---------|----------------------------------------------|
+//! | `**`    | any number of components, including none     |
+//! | `*`     | any characters within a single component     |
+//! | `?`     | a single character                           |
+//!
+//! All other characters match themselves. Wildcards never match across a '/'.
+//!
+//! ```
+//! use s3_path::glob::Glob;
+//! use s3_path::S3PathBuf;
+//!
+//! let glob = Glob::parse("tenants/*/**/*.csv").unwrap();
+//! assert!(glob.matches(&S3PathBuf::try_from_str("tenants/acme/2024/01/data.csv").unwrap()));
+//! assert!(glob.matches(&S3PathBuf::try_from_str("tenants/acme/data.csv").unwrap()));
+//! assert!(!glob.matches(&S3PathBuf::try_from_str("tenants/data.csv").unwrap()));
+//! ```
+
+use crate::error::InvalidGlob;
+use crate::{validation, S3Path};
+use std::fmt::Formatter;
+
+/// A single component of a [`Glob`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    /// `**`, matching any number of components.
+    AnyComponents,
+
+    /// A component pattern, possibly containing `*` and `?`.
+    Pattern(String),
+}
+
+/// A parsed glob pattern. See the [module documentation](crate::glob).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Glob {
+    glob: String,
+    segments: Vec<Segment>,
+}
+
+impl Glob {
+    /// Parses a glob pattern.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when
+    /// - the pattern has an empty component
+    /// - a component contains characters not allowed in path components, other than `*` and `?`
+    /// - `**` is combined with other characters in a single component
+    pub fn parse(glob: &str) -> Result<Self, InvalidGlob> {
+        let invalid = |reason: String| InvalidGlob {
+            glob: glob.to_string(),
+            reason,
+        };
+        let mut segments = Vec::new();
+        if glob.is_empty() {
+            return Ok(Self {
+                glob: String::new(),
+                segments,
+            });
+        }
+        for segment in glob.split('/') {
+            if segment.is_empty() {
+                return Err(invalid("Empty component".to_string()));
+            }
+            if segment == "**" {
+                // Consecutive `**` are equivalent to a single one.
+                if segments.last() != Some(&Segment::AnyComponents) {
+                    segments.push(Segment::AnyComponents);
+                }
+                continue;
+            }
+            if segment.contains("**") {
+                return Err(invalid(format!(
+                    "'**' must be a component on its own, found '{segment}'"
+                )));
+            }
+            if let Some(c) = segment
+                .chars()
+                .find(|c| *c != '*' && *c != '?' && !validation::is_allowed_char(*c))
+            {
+                return Err(invalid(format!("Character '{c}' is not allowed")));
+            }
+            segments.push(Segment::Pattern(segment.to_string()));
+        }
+        Ok(Self {
+            glob: glob.to_string(),
+            segments,
+        })
+    }
+
+    /// Returns the pattern this glob was parsed from.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.glob
+    }
+
+    /// Returns true if `path` matches this glob.
+    #[must_use]
+    pub fn matches(&self, path: &S3Path<'_>) -> bool {
+        let components = path.0.iter().map(AsRef::as_ref).collect::<Vec<&str>>();
+        matches_components(&self.segments, &components)
+    }
+
+    /// Returns the longest literal prefix of this glob, the components before the first one
+    /// containing a wildcard. Every path matched by this glob starts with these components.
+    #[must_use]
+    pub fn literal_prefix(&self) -> Vec<&str> {
+        self.segments
+            .iter()
+            .map_while(|segment| match segment {
+                Segment::Pattern(pattern) if !pattern.contains(['*', '?']) => {
+                    Some(pattern.as_str())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+impl std::fmt::Display for Glob {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.glob)
+    }
+}
+
+impl std::str::FromStr for Glob {
+    type Err = InvalidGlob;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl S3Path<'_> {
+    /// Generalizes this path into a glob, keeping the first `depth` components and replacing all
+    /// further components with `*`. The glob matches all paths of equal depth sharing the kept
+    /// components, e.g. to derive monitoring rules from an example key.
+    ///
+    /// ```
+    /// use s3_path::s3_path;
+    ///
+    /// let path = s3_path!("tenants", "acme", "2024", "data.csv").unwrap();
+    /// assert_eq!(path.to_globbed_parent(2).to_string(), "tenants/acme/*/*");
+    /// ```
+    #[must_use]
+    pub fn to_globbed_parent(&self, depth: usize) -> Glob {
+        let segments = self
+            .0
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                Segment::Pattern(if i < depth {
+                    c.to_string()
+                } else {
+                    "*".to_string()
+                })
+            })
+            .collect::<Vec<_>>();
+        let glob = segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Pattern(pattern) => pattern.as_str(),
+                Segment::AnyComponents => "**",
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+        Glob { glob, segments }
+    }
+}
+
+fn matches_components(segments: &[Segment], components: &[&str]) -> bool {
+    match segments.split_first() {
+        None => components.is_empty(),
+        Some((Segment::AnyComponents, rest)) => {
+            (0..=components.len()).any(|skip| matches_components(rest, &components[skip..]))
+        }
+        Some((Segment::Pattern(pattern), rest)) => match components.split_first() {
+            Some((component, remaining)) => {
+                matches_wildcards(pattern.as_bytes(), component.as_bytes())
+                    && matches_components(rest, remaining)
+            }
+            None => false,
+        },
+    }
+}
+
+/// Matches `input` against a pattern in which `*` matches any bytes and `?` matches a single byte.
+///
+/// Components and bucket names are ascii-only, making byte-wise matching equivalent to char-wise
+/// matching.
+pub(crate) fn matches_wildcards(pattern: &[u8], input: &[u8]) -> bool {
+    // Iterative wildcard matching, backtracking to the last `*` only.
+    let (mut p, mut c) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while c < input.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                star = Some((p, c));
+                p += 1;
+            }
+            Some(b'?') => {
+                p += 1;
+                c += 1;
+            }
+            Some(b) if *b == input[c] => {
+                p += 1;
+                c += 1;
+            }
+            _ => match star {
+                Some((star_p, star_c)) => {
+                    star = Some((star_p, star_c + 1));
+                    p = star_p + 1;
+                    c = star_c + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|b| *b == b'*')
+}
+
+#[cfg(test)]
+mod test {
+    use crate::glob::Glob;
+    use crate::S3PathBuf;
+    use assertr::prelude::*;
+
+    fn matches(glob: &str, key: &str) -> bool {
+        Glob::parse(glob)
+            .unwrap()
+            .matches(&S3PathBuf::try_from_str(key).unwrap())
+    }
+
+    #[test]
+    fn parse_rejects_invalid_globs() {
+        assert_that(Glob::parse("a//b")).is_err();
+        assert_that(Glob::parse("a/b**")).is_err();
+        assert_that(Glob::parse("a/$")).is_err();
+    }
+
+    #[test]
+    fn single_component_wildcards() {
+        assert_that(matches("a/*.csv", "a/b.csv")).is_true();
+        assert_that(matches("a/*.csv", "a/.csv")).is_true();
+        assert_that(matches("a/*.csv", "a/b/c.csv")).is_false();
+        assert_that(matches("a/b?", "a/bc")).is_true();
+        assert_that(matches("a/b?", "a/b")).is_false();
+        assert_that(matches("*a*b*", "xaxxbx")).is_true();
+        assert_that(matches("*a*b*", "xbxa")).is_false();
+    }
+
+    #[test]
+    fn double_star_matches_any_number_of_components() {
+        assert_that(matches("a/**", "a")).is_true();
+        assert_that(matches("a/**", "a/b/c")).is_true();
+        assert_that(matches("**/c", "c")).is_true();
+        assert_that(matches("a/**/**/c", "a/b/b/c")).is_true();
+        assert_that(matches("a/**/c", "a/b/d")).is_false();
+        assert_that(matches("", "")).is_true();
+        assert_that(matches("", "a")).is_false();
+    }
+
+    #[test]
+    fn globbed_parent_matches_siblings_of_equal_depth() {
+        let path = S3PathBuf::try_from_str("a/b/c/d").unwrap();
+        let glob = path.to_globbed_parent(2);
+        assert_that(glob.to_string()).is_equal_to("a/b/*/*".to_owned());
+        assert_that(glob.matches(&path)).is_true();
+        assert_that(glob.matches(&S3PathBuf::try_from_str("a/b/x/y").unwrap())).is_true();
+        assert_that(glob.matches(&S3PathBuf::try_from_str("a/x/c/d").unwrap())).is_false();
+        assert_that(glob.matches(&S3PathBuf::try_from_str("a/b/c").unwrap())).is_false();
+        assert_that(path.to_globbed_parent(9)).is_equal_to(Glob::parse("a/b/c/d").unwrap());
+    }
+
+    #[test]
+    fn literal_prefix_ends_at_first_wildcard() {
+        let glob = Glob::parse("a/b/c*/d").unwrap();
+        assert_that(glob.literal_prefix()).is_equal_to(vec!["a", "b"]);
+        let glob = Glob::parse("a/**/d").unwrap();
+        assert_that(glob.literal_prefix()).is_equal_to(vec!["a"]);
+    }
+}
@@ -0,0 +1,461 @@
+//! Declarative key layouts and conformance checking.
+//!
+//! A [`KeyGrammar`] describes the layout of keys as a sequence of component specifications. It
+//! can be written in a small DSL, in which components are separated by '/' and each component is
+//! either a literal or a placeholder in braces:
+//!
+//! | Placeholder        | Matches                                         |
+//! |--------------------|-------------------------------------------------|
+//! | `{any}`            | any valid component                             |
+//! | `{enum:a\|b\|c}`   | one of the listed values                        |
+//! | `{date}`           | a calendar date formatted as `YYYY-MM-DD`       |
+//! | `{year}`           | a four-digit year                               |
+//! | `{month}`          | a two-digit month, `01` to `12`                 |
+//! | `{day}`            | a two-digit day of month, `01` to `31`          |
+//! | `{uuid}`           | a hyphenated UUID in any letter case            |
+//! | `{regex:pattern}`  | components entirely matching `pattern` (requires the `regex` feature) |
+//!
+//! ```
+//! use s3_path::grammar::KeyGrammar;
+//! use s3_path::s3_path;
+//!
+//! let grammar = KeyGrammar::parse("tenants/{uuid}/{date}/{enum:csv|json}").unwrap();
+//!
+//! let key = s3_path!("tenants", "67e55044-10b1-426f-9247-bb680e5fe0c8", "2024-02-29", "csv");
+//! assert!(grammar.check(key.unwrap()).is_ok());
+//!
+//! let key = s3_path!("tenants", "acme", "2024-02-29", "csv");
+//! assert!(grammar.check(key.unwrap()).is_err());
+//! ```
+
+use crate::error::{GrammarViolation, InvalidKeyGrammar};
+use crate::rng::SplitMix64;
+use crate::{validation, S3Path, S3PathBuf};
+use std::borrow::Cow;
+use std::fmt::Formatter;
+
+/// Specification of a single path component.
+#[derive(Debug, Clone)]
+pub enum ComponentSpec {
+    /// Exactly the given string.
+    Literal(String),
+
+    /// One of the given strings.
+    OneOf(Vec<String>),
+
+    /// A calendar date formatted as `YYYY-MM-DD`.
+    Date,
+
+    /// A four-digit year.
+    Year,
+
+    /// A two-digit month, `01` to `12`.
+    Month,
+
+    /// A two-digit day of month, `01` to `31`.
+    Day,
+
+    /// A hyphenated UUID, e.g. `67e55044-10b1-426f-9247-bb680e5fe0c8`, in any letter case.
+    Uuid,
+
+    /// Any valid component.
+    Any,
+
+    /// A component matching the given regular expression.
+    ///
+    /// The expression must be anchored to match entire components only. Use
+    /// [`ComponentSpec::regex`] to create an anchored expression from a pattern.
+    #[cfg(feature = "regex")]
+    Regex(regex::Regex),
+}
+
+impl ComponentSpec {
+    /// Creates a `ComponentSpec::Regex`, matching only components entirely matched by `pattern`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when `pattern` is not a valid regular expression.
+    #[cfg(feature = "regex")]
+    pub fn regex(pattern: &str) -> Result<Self, regex::Error> {
+        regex::Regex::new(&format!("^(?:{pattern})$")).map(ComponentSpec::Regex)
+    }
+
+    /// Returns true if `component` satisfies this specification.
+    #[must_use]
+    pub fn matches(&self, component: &str) -> bool {
+        match self {
+            ComponentSpec::Literal(literal) => component == literal,
+            ComponentSpec::OneOf(values) => values.iter().any(|it| it == component),
+            ComponentSpec::Date => parse_date(component).is_some(),
+            ComponentSpec::Year => parse_digits(component, 4).is_some(),
+            ComponentSpec::Month => {
+                parse_digits(component, 2).is_some_and(|m| (1..=12).contains(&m))
+            }
+            ComponentSpec::Day => parse_digits(component, 2).is_some_and(|d| (1..=31).contains(&d)),
+            ComponentSpec::Uuid => is_uuid(component),
+            ComponentSpec::Any => true,
+            #[cfg(feature = "regex")]
+            ComponentSpec::Regex(regex) => regex.is_match(component),
+        }
+    }
+
+    pub(crate) fn describe(&self) -> String {
+        match self {
+            ComponentSpec::Literal(literal) => format!("'{literal}'"),
+            ComponentSpec::OneOf(values) => format!("one of '{}'", values.join("', '")),
+            ComponentSpec::Date => "a date (YYYY-MM-DD)".to_string(),
+            ComponentSpec::Year => "a year (YYYY)".to_string(),
+            ComponentSpec::Month => "a month (MM)".to_string(),
+            ComponentSpec::Day => "a day (DD)".to_string(),
+            ComponentSpec::Uuid => "a UUID".to_string(),
+            ComponentSpec::Any => "any component".to_string(),
+            #[cfg(feature = "regex")]
+            ComponentSpec::Regex(regex) => format!("matching '{}'", regex.as_str()),
+        }
+    }
+
+    /// Generates a random component satisfying this specification, if possible.
+    fn sample(&self, rng: &mut SplitMix64) -> Option<String> {
+        const ALPHANUMERIC: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
+        Some(match self {
+            ComponentSpec::Literal(literal) => literal.clone(),
+            ComponentSpec::OneOf(values) => rng.pick(values).clone(),
+            ComponentSpec::Date => format!(
+                "{:04}-{:02}-{:02}",
+                2000 + rng.below(100),
+                1 + rng.below(12),
+                1 + rng.below(28)
+            ),
+            ComponentSpec::Year => format!("{:04}", 2000 + rng.below(100)),
+            ComponentSpec::Month => format!("{:02}", 1 + rng.below(12)),
+            ComponentSpec::Day => format!("{:02}", 1 + rng.below(28)),
+            ComponentSpec::Uuid => {
+                let hi = rng.next_u64() & 0xffff_ffff_ffff_0fff | 0x4000;
+                let lo = rng.next_u64() & 0x3fff_ffff_ffff_ffff | 0x8000_0000_0000_0000;
+                format!(
+                    "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+                    hi >> 32,
+                    (hi >> 16) & 0xffff,
+                    hi & 0xffff,
+                    lo >> 48,
+                    lo & 0xffff_ffff_ffff
+                )
+            }
+            ComponentSpec::Any => (0..8)
+                .map(|_| char::from(*rng.pick(ALPHANUMERIC)))
+                .collect(),
+            #[cfg(feature = "regex")]
+            ComponentSpec::Regex(_) => return None,
+        })
+    }
+}
+
+impl std::fmt::Display for ComponentSpec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ComponentSpec::Literal(literal) => f.write_str(literal),
+            ComponentSpec::OneOf(values) => write!(f, "{{enum:{}}}", values.join("|")),
+            ComponentSpec::Date => f.write_str("{date}"),
+            ComponentSpec::Year => f.write_str("{year}"),
+            ComponentSpec::Month => f.write_str("{month}"),
+            ComponentSpec::Day => f.write_str("{day}"),
+            ComponentSpec::Uuid => f.write_str("{uuid}"),
+            ComponentSpec::Any => f.write_str("{any}"),
+            #[cfg(feature = "regex")]
+            ComponentSpec::Regex(regex) => write!(f, "{{regex:{}}}", regex.as_str()),
+        }
+    }
+}
+
+/// The layout of a family of keys, as a sequence of component specifications.
+///
+/// See the [module documentation](crate::grammar) for the DSL accepted by [`KeyGrammar::parse`].
+#[derive(Debug, Clone)]
+pub struct KeyGrammar {
+    specs: Vec<ComponentSpec>,
+}
+
+impl KeyGrammar {
+    /// Creates a grammar from a sequence of component specifications.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when a literal or enum value is not a valid path component, or when an enum
+    /// has no values.
+    pub fn new(specs: impl IntoIterator<Item = ComponentSpec>) -> Result<Self, InvalidKeyGrammar> {
+        let grammar = KeyGrammar {
+            specs: specs.into_iter().collect(),
+        };
+        for spec in &grammar.specs {
+            let values = match spec {
+                ComponentSpec::Literal(literal) => std::slice::from_ref(literal),
+                ComponentSpec::OneOf(values) if values.is_empty() => {
+                    return Err(grammar.invalid("Enum without any values".to_string()));
+                }
+                ComponentSpec::OneOf(values) => values.as_slice(),
+                _ => continue,
+            };
+            for value in values {
+                if let Err(err) = validation::validate_component(value) {
+                    return Err(grammar.invalid(err.to_string()));
+                }
+            }
+        }
+        Ok(grammar)
+    }
+
+    /// Parses a grammar written in the DSL described in the [module documentation](crate::grammar).
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when
+    /// - the grammar contains empty components
+    /// - a placeholder is unknown or misses its argument
+    /// - a literal or enum value is not a valid path component
+    /// - a regular expression is invalid
+    pub fn parse(grammar: &str) -> Result<Self, InvalidKeyGrammar> {
+        let invalid = |reason: String| InvalidKeyGrammar {
+            grammar: grammar.to_string(),
+            reason,
+        };
+        if grammar.is_empty() {
+            return Ok(KeyGrammar { specs: Vec::new() });
+        }
+        let mut specs = Vec::new();
+        for segment in grammar.split('/') {
+            let Some(placeholder) = segment
+                .strip_prefix('{')
+                .and_then(|it| it.strip_suffix('}'))
+            else {
+                specs.push(ComponentSpec::Literal(segment.to_string()));
+                continue;
+            };
+            let (kind, argument) = match placeholder.split_once(':') {
+                Some((kind, argument)) => (kind, Some(argument)),
+                None => (placeholder, None),
+            };
+            let spec = match (kind, argument) {
+                ("any", None) => ComponentSpec::Any,
+                ("date", None) => ComponentSpec::Date,
+                ("year", None) => ComponentSpec::Year,
+                ("month", None) => ComponentSpec::Month,
+                ("day", None) => ComponentSpec::Day,
+                ("uuid", None) => ComponentSpec::Uuid,
+                ("enum", Some(values)) => {
+                    ComponentSpec::OneOf(values.split('|').map(str::to_string).collect())
+                }
+                #[cfg(feature = "regex")]
+                ("regex", Some(pattern)) => {
+                    ComponentSpec::regex(pattern).map_err(|err| invalid(err.to_string()))?
+                }
+                _ => return Err(invalid(format!("Unknown placeholder '{segment}'"))),
+            };
+            specs.push(spec);
+        }
+        KeyGrammar::new(specs).map_err(|err| invalid(err.reason))
+    }
+
+    /// Returns the component specifications of this grammar.
+    #[must_use]
+    pub fn specs(&self) -> &[ComponentSpec] {
+        &self.specs
+    }
+
+    /// Checks whether `path` conforms to this grammar.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` describing the first component violating the grammar, which might also be
+    /// a missing or an unexpected trailing component.
+    pub fn check(&self, path: &S3Path<'_>) -> Result<(), GrammarViolation> {
+        for (index, spec) in self.specs.iter().enumerate() {
+            match path.get(index) {
+                None => {
+                    return Err(GrammarViolation {
+                        index,
+                        reason: format!("Missing component, expected {}", spec.describe()),
+                    });
+                }
+                Some(component) if !spec.matches(component) => {
+                    return Err(GrammarViolation {
+                        index,
+                        reason: format!("Expected {}, got '{component}'", spec.describe()),
+                    });
+                }
+                Some(_) => {}
+            }
+        }
+        if let Some(component) = path.get(self.specs.len()) {
+            return Err(GrammarViolation {
+                index: self.specs.len(),
+                reason: format!("Unexpected component '{component}'"),
+            });
+        }
+        Ok(())
+    }
+
+    /// Generates a random key conforming to this grammar. Equal seeds produce equal keys.
+    ///
+    /// Returns `None` if this grammar contains regular expressions, for which no samples can be
+    /// generated.
+    #[must_use]
+    pub fn sample(&self, seed: u64) -> Option<S3PathBuf> {
+        let mut rng = SplitMix64::new(seed);
+        let components = self
+            .specs
+            .iter()
+            .map(|spec| spec.sample(&mut rng).map(Cow::Owned))
+            .collect::<Option<Vec<_>>>()?;
+        Some(S3PathBuf { components })
+    }
+
+    fn invalid(&self, reason: String) -> InvalidKeyGrammar {
+        InvalidKeyGrammar {
+            grammar: self.to_string(),
+            reason,
+        }
+    }
+}
+
+impl std::fmt::Display for KeyGrammar {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, spec) in self.specs.iter().enumerate() {
+            if i > 0 {
+                f.write_str("/")?;
+            }
+            write!(f, "{spec}")?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_digits(s: &str, len: usize) -> Option<u32> {
+    if s.len() == len && s.bytes().all(|b| b.is_ascii_digit()) {
+        s.parse().ok()
+    } else {
+        None
+    }
+}
+
+/// Parses a `YYYY-MM-DD` date, returning `(year, month, day)` if it exists in the calendar.
+pub(crate) fn parse_date(s: &str) -> Option<(u32, u32, u32)> {
+    let mut parts = s.split('-');
+    let year = parse_digits(parts.next()?, 4)?;
+    let month = parse_digits(parts.next()?, 2)?;
+    let day = parse_digits(parts.next()?, 2)?;
+    if parts.next().is_some() || !(1..=12).contains(&month) {
+        return None;
+    }
+    (day >= 1 && day <= days_in_month(year, month)).then_some((year, month, day))
+}
+
+pub(crate) fn days_in_month(year: u32, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+fn is_uuid(s: &str) -> bool {
+    s.len() == 36
+        && s.bytes().enumerate().all(|(i, b)| match i {
+            8 | 13 | 18 | 23 => b == b'-',
+            _ => b.is_ascii_hexdigit(),
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::grammar::{ComponentSpec, KeyGrammar};
+    use crate::S3PathBuf;
+    use assertr::prelude::*;
+
+    fn path(key: &str) -> S3PathBuf {
+        S3PathBuf::try_from_str(key).unwrap()
+    }
+
+    #[test]
+    fn parse_and_display_round_trip() {
+        let dsl = "tenants/{uuid}/{year}/{month}/{day}/{enum:csv|json}/{any}/{date}";
+        let grammar = KeyGrammar::parse(dsl).unwrap();
+        assert_that(grammar.specs().len()).is_equal_to(8);
+        assert_that(grammar).has_display_value(dsl);
+    }
+
+    #[test]
+    fn parse_rejects_invalid_grammars() {
+        assert_that(KeyGrammar::parse("foo//bar")).is_err();
+        assert_that(KeyGrammar::parse("foo/{unknown}")).is_err();
+        assert_that(KeyGrammar::parse("foo/{enum}")).is_err();
+        assert_that(KeyGrammar::parse("foo/{enum:a|..}")).is_err();
+        assert_that(KeyGrammar::parse("foo$")).is_err();
+    }
+
+    #[test]
+    fn new_rejects_invalid_literals() {
+        assert_that(KeyGrammar::new([ComponentSpec::Literal("a/b".to_string())])).is_err();
+        assert_that(KeyGrammar::new([ComponentSpec::OneOf(Vec::new())])).is_err();
+    }
+
+    #[test]
+    fn check_accepts_conforming_keys() {
+        let grammar = KeyGrammar::parse("logs/{date}/{enum:app|db}/{any}").unwrap();
+        assert_that(grammar.check(&path("logs/2024-02-29/db/0001.log"))).is_ok();
+    }
+
+    #[test]
+    fn check_reports_first_violation() {
+        let grammar = KeyGrammar::parse("logs/{date}/{enum:app|db}").unwrap();
+
+        let violation = grammar.check(&path("logs/2023-02-29/db")).unwrap_err();
+        assert_that(violation.index).is_equal_to(1);
+        assert_that(violation).has_display_value(
+            "Key violates grammar at component 1: Expected a date (YYYY-MM-DD), got '2023-02-29'",
+        );
+
+        let violation = grammar.check(&path("logs/2024-02-29")).unwrap_err();
+        assert_that(violation.index).is_equal_to(2);
+
+        let violation = grammar
+            .check(&path("logs/2024-02-29/db/extra"))
+            .unwrap_err();
+        assert_that(violation.index).is_equal_to(3);
+    }
+
+    #[test]
+    fn check_uuid_and_date_parts() {
+        let grammar = KeyGrammar::parse("{uuid}/{year}/{month}/{day}").unwrap();
+        assert_that(grammar.check(&path("67E55044-10b1-426f-9247-bb680e5fe0c8/2024/12/31")))
+            .is_ok();
+        assert_that(grammar.check(&path("67e55044-10b1-426f-9247/2024/12/31"))).is_err();
+        assert_that(grammar.check(&path("67e55044-10b1-426f-9247-bb680e5fe0c8/2024/13/31")))
+            .is_err();
+        assert_that(grammar.check(&path("67e55044-10b1-426f-9247-bb680e5fe0c8/2024/12/32")))
+            .is_err();
+    }
+
+    #[test]
+    fn sample_generates_conforming_keys_deterministically() {
+        let grammar =
+            KeyGrammar::parse("tenants/{uuid}/{date}/{year}/{month}/{day}/{enum:a|b}/{any}")
+                .unwrap();
+        for seed in 0..100 {
+            let sample = grammar.sample(seed).unwrap();
+            assert_that(grammar.check(&sample)).is_ok();
+            assert_that(grammar.sample(seed))
+                .is_some()
+                .is_equal_to(sample);
+        }
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn regex_placeholders_match_entire_components() {
+        let grammar = KeyGrammar::parse("{regex:part-[0-9]+}").unwrap();
+        assert_that(grammar.check(&path("part-0001"))).is_ok();
+        assert_that(grammar.check(&path("part-0001.bak"))).is_err();
+        assert_that(grammar.sample(0)).is_none();
+    }
+}
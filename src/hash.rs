@@ -0,0 +1,111 @@
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Computes the 64-bit FNV-1a hash over the rendered form of the given components, as if they
+/// were joined by '/'.
+///
+/// Hashing component-wise avoids allocating the joined string.
+pub(crate) fn fnv1a_64<C: AsRef<str>>(components: impl Iterator<Item = C>) -> u64 {
+    let mut hash = FNV_OFFSET_BASIS;
+    let mut feed = |bytes: &[u8]| {
+        for b in bytes {
+            hash ^= u64::from(*b);
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+    };
+    for (i, c) in components.enumerate() {
+        if i > 0 {
+            feed(b"/");
+        }
+        feed(c.as_ref().as_bytes());
+    }
+    hash
+}
+
+/// Jump consistent hash by Lamping and Veach (<https://arxiv.org/abs/1406.2294>).
+///
+/// Maps `key` to a bucket in `0..num_buckets`. When `num_buckets` grows from `n` to `n + 1`, only
+/// about `1 / (n + 1)` of all keys move to a different bucket.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+pub(crate) fn jump_consistent_hash(mut key: u64, num_buckets: u32) -> u32 {
+    let mut b: i64 = -1;
+    let mut j: i64 = 0;
+    while j < i64::from(num_buckets) {
+        b = j;
+        key = key.wrapping_mul(2_862_933_555_777_941_757).wrapping_add(1);
+        j = ((b + 1) as f64 * ((1_i64 << 31) as f64 / ((key >> 33) + 1) as f64)) as i64;
+    }
+    b as u32
+}
+
+/// SipHash-2-4 by Aumasson and Bernstein, a keyed pseudorandom function. Unlike FNV-1a, its
+/// output cannot be predicted without knowing `key`.
+pub(crate) fn siphash24(key: &[u8; 16], data: &[u8]) -> u64 {
+    fn round(v: &mut [u64; 4]) {
+        v[0] = v[0].wrapping_add(v[1]);
+        v[1] = v[1].rotate_left(13) ^ v[0];
+        v[0] = v[0].rotate_left(32);
+        v[2] = v[2].wrapping_add(v[3]);
+        v[3] = v[3].rotate_left(16) ^ v[2];
+        v[0] = v[0].wrapping_add(v[3]);
+        v[3] = v[3].rotate_left(21) ^ v[0];
+        v[2] = v[2].wrapping_add(v[1]);
+        v[1] = v[1].rotate_left(17) ^ v[2];
+        v[2] = v[2].rotate_left(32);
+    }
+
+    fn compress(v: &mut [u64; 4], m: u64) {
+        v[3] ^= m;
+        round(v);
+        round(v);
+        v[0] ^= m;
+    }
+
+    let mut k = [0u8; 8];
+    k.copy_from_slice(&key[..8]);
+    let k0 = u64::from_le_bytes(k);
+    k.copy_from_slice(&key[8..]);
+    let k1 = u64::from_le_bytes(k);
+
+    let mut v = [
+        k0 ^ 0x736f_6d65_7073_6575,
+        k1 ^ 0x646f_7261_6e64_6f6d,
+        k0 ^ 0x6c79_6765_6e65_7261,
+        k1 ^ 0x7465_6462_7974_6573,
+    ];
+
+    let mut chunks = data.chunks_exact(8);
+    for chunk in &mut chunks {
+        k.copy_from_slice(chunk);
+        compress(&mut v, u64::from_le_bytes(k));
+    }
+    let mut last = [0u8; 8];
+    last[..chunks.remainder().len()].copy_from_slice(chunks.remainder());
+    // Only the lowest byte of the length is mixed in, as specified.
+    last[7] = data.len().to_le_bytes()[0];
+    compress(&mut v, u64::from_le_bytes(last));
+
+    v[2] ^= 0xff;
+    for _ in 0..4 {
+        round(&mut v);
+    }
+    v[0] ^ v[1] ^ v[2] ^ v[3]
+}
+
+#[cfg(test)]
+mod test {
+    use crate::hash::siphash24;
+    use assertr::prelude::*;
+
+    #[test]
+    fn siphash24_matches_reference_vectors() {
+        let key = std::array::from_fn(|i| u8::try_from(i).unwrap());
+        let message = (0..15).collect::<Vec<u8>>();
+        assert_that(siphash24(&key, &[])).is_equal_to(0x726f_db47_dd0e_0e31);
+        assert_that(siphash24(&key, &message)).is_equal_to(0xa129_ca61_49be_45e5);
+    }
+}
@@ -0,0 +1,199 @@
+//! Estimation of request rates per prefix.
+//!
+//! A [`PrefixHeat`] consumes a stream of accessed paths and tracks an exponentially weighted
+//! moving average (EWMA) of the request rate of each prefix at a fixed depth. The hottest prefixes
+//! are candidates for further sharding, e.g. using [`S3Path::shard_for`].
+//!
+//! ```
+//! use s3_path::heat::PrefixHeat;
+//! use s3_path::S3PathBuf;
+//! use std::time::{Duration, Instant};
+//!
+//! let mut heat = PrefixHeat::new(1, Duration::from_secs(60));
+//! let start = Instant::now();
+//! for i in 0..100 {
+//!     let key = if i % 10 == 0 { "cold/a" } else { "hot/a" };
+//!     heat.record(&S3PathBuf::try_from_str(key).unwrap(), start + Duration::from_millis(i * 100));
+//! }
+//!
+//! let hottest = heat.hottest(1, start + Duration::from_secs(10));
+//! assert_eq!(hottest[0].0.to_string(), "hot");
+//! ```
+
+use crate::{S3Path, S3PathBuf};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// The EWMA of the request rate of a single prefix.
+#[derive(Debug, Clone)]
+struct Heat {
+    prefix: S3PathBuf,
+    /// Requests per second, as of `updated`.
+    rate: f64,
+    updated: Instant,
+}
+
+/// Request rates of prefixes. See the [module documentation](crate::heat).
+#[derive(Debug, Clone)]
+pub struct PrefixHeat {
+    depth: usize,
+    half_life: Duration,
+    heat: HashMap<String, Heat>,
+}
+
+impl PrefixHeat {
+    /// Creates an estimator tracking prefixes of `depth` components. The weight of a request
+    /// halves every `half_life`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `half_life` is zero.
+    #[must_use]
+    pub fn new(depth: usize, half_life: Duration) -> Self {
+        assert!(!half_life.is_zero(), "half_life must be greater than zero");
+        Self {
+            depth,
+            half_life,
+            heat: HashMap::new(),
+        }
+    }
+
+    /// Records a request to `path` at time `at`. Paths with less than `depth` components count
+    /// towards themselves.
+    ///
+    /// Requests should be recorded in chronological order. Requests recorded out of order are
+    /// treated as happening at the time of the latest request of their prefix.
+    pub fn record(&mut self, path: &S3Path<'_>, at: Instant) {
+        let components = &path.0[..self.depth.min(path.0.len())];
+        let key = components.join("/");
+        // With requests arriving at a constant rate, this converges to that rate.
+        let weight = std::f64::consts::LN_2 / self.half_life.as_secs_f64();
+        let decay = match self.heat.get(&key) {
+            Some(heat) => decay(self.half_life, at.saturating_duration_since(heat.updated)),
+            None => 0.0,
+        };
+        let heat = self.heat.entry(key).or_insert_with(|| Heat {
+            prefix: S3PathBuf {
+                components: components
+                    .iter()
+                    .map(|it| Cow::Owned(it.to_string()))
+                    .collect(),
+            },
+            rate: 0.0,
+            updated: at,
+        });
+        heat.rate = heat.rate * decay + weight;
+        heat.updated = heat.updated.max(at);
+    }
+
+    /// Returns the estimated request rate of `prefix` at time `now`, in requests per second.
+    #[must_use]
+    pub fn rate(&self, prefix: &S3Path<'_>, now: Instant) -> f64 {
+        self.heat
+            .get(&prefix.0.join("/"))
+            .map_or(0.0, |heat| self.current_rate(heat, now))
+    }
+
+    fn current_rate(&self, heat: &Heat, now: Instant) -> f64 {
+        heat.rate * decay(self.half_life, now.saturating_duration_since(heat.updated))
+    }
+
+    /// Returns the `n` prefixes with the highest estimated request rates at time `now`, together
+    /// with these rates, in descending order of rate.
+    #[must_use]
+    pub fn hottest(&self, n: usize, now: Instant) -> Vec<(S3PathBuf, f64)> {
+        let mut rates = self
+            .heat
+            .iter()
+            .map(|(key, heat)| (key, heat, self.current_rate(heat, now)))
+            .collect::<Vec<_>>();
+        rates.sort_by(|a, b| b.2.total_cmp(&a.2).then_with(|| a.0.cmp(b.0)));
+        rates
+            .into_iter()
+            .take(n)
+            .map(|(_, heat, rate)| (heat.prefix.clone(), rate))
+            .collect()
+    }
+
+    /// Forgets all prefixes with an estimated request rate below `min_rate` at time `now`,
+    /// bounding the memory used when tracking many short-lived prefixes.
+    pub fn prune(&mut self, min_rate: f64, now: Instant) {
+        let half_life = self.half_life;
+        self.heat.retain(|_, heat| {
+            heat.rate * decay(half_life, now.saturating_duration_since(heat.updated)) >= min_rate
+        });
+    }
+
+    /// Returns the number of prefixes tracked.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.heat.len()
+    }
+
+    /// Returns true if no prefix is tracked.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.heat.is_empty()
+    }
+}
+
+/// Returns the factor by which rates decay over `elapsed`.
+fn decay(half_life: Duration, elapsed: Duration) -> f64 {
+    (-std::f64::consts::LN_2 * elapsed.as_secs_f64() / half_life.as_secs_f64()).exp()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::heat::PrefixHeat;
+    use crate::S3PathBuf;
+    use assertr::prelude::*;
+    use std::time::{Duration, Instant};
+
+    fn path(key: &str) -> S3PathBuf {
+        S3PathBuf::try_from_str(key).unwrap()
+    }
+
+    #[test]
+    fn converges_to_constant_rate() {
+        let mut heat = PrefixHeat::new(1, Duration::from_secs(10));
+        let start = Instant::now();
+        // 5 requests per second for 100 seconds.
+        for i in 0..500 {
+            heat.record(&path("a/b"), start + Duration::from_millis(i * 200));
+        }
+        let rate = heat.rate(&path("a"), start + Duration::from_secs(100));
+        assert_that((rate - 5.0).abs() < 0.1).is_true();
+    }
+
+    #[test]
+    fn rates_halve_every_half_life() {
+        let mut heat = PrefixHeat::new(2, Duration::from_secs(10));
+        let start = Instant::now();
+        heat.record(&path("a/b/c"), start);
+        let initial = heat.rate(&path("a/b"), start);
+        let later = heat.rate(&path("a/b"), start + Duration::from_secs(10));
+        assert_that((later * 2.0 - initial).abs() < 1e-9).is_true();
+        assert_that(heat.rate(&path("a"), start)).is_equal_to(0.0);
+    }
+
+    #[test]
+    fn hottest_and_prune() {
+        let mut heat = PrefixHeat::new(1, Duration::from_secs(10));
+        let start = Instant::now();
+        for (i, key) in ["a", "b", "b", "c", "c", "c"].into_iter().enumerate() {
+            heat.record(&path(key), start + Duration::from_millis(i as u64));
+        }
+        let now = start + Duration::from_secs(1);
+        let hottest = heat
+            .hottest(2, now)
+            .into_iter()
+            .map(|(prefix, _)| prefix.to_string())
+            .collect::<Vec<_>>();
+        assert_that(hottest).is_equal_to(vec!["c".to_string(), "b".to_string()]);
+
+        let threshold = heat.rate(&path("b"), now);
+        heat.prune(threshold, now);
+        assert_that(heat.len()).is_equal_to(2);
+    }
+}
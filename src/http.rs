@@ -0,0 +1,155 @@
+//! Mapping errors to HTTP responses at service boundaries.
+//!
+//! ```
+//! use s3_path::http::HttpProblem;
+//! use s3_path::S3PathBuf;
+//!
+//! let err = S3PathBuf::strict_parse("foo/b r").unwrap_err();
+//! assert_eq!(err.status(), http::StatusCode::BAD_REQUEST);
+//! assert_eq!(
+//!     err.problem_details(),
+//!     r#"{"type":"about:blank","title":"Invalid S3 path component","status":400,"detail":"Invalid S3 path component 'b r': Character ' ' is not allowed"}"#,
+//! );
+//! ```
+
+use crate::error::{
+    GrammarViolation, InvalidComponentKind, InvalidS3Key, InvalidS3PathComponent,
+    LayoutMigrationError, PatchConflict,
+};
+use http::StatusCode;
+use std::fmt::Write;
+
+/// An error which can be reported to HTTP clients.
+pub trait HttpProblem: std::error::Error {
+    /// The suggested status code of the response.
+    fn status(&self) -> StatusCode;
+
+    /// A short, human-readable summary of the problem, not changing between occurrences.
+    fn title(&self) -> &'static str;
+
+    /// Renders an RFC 9457 problem details object (`application/problem+json`), using the
+    /// `Display` output of the error as detail.
+    fn problem_details(&self) -> String {
+        let mut json = String::from(r#"{"type":"about:blank","title":"#);
+        push_json_string(&mut json, self.title());
+        write!(json, r#","status":{},"detail":"#, self.status().as_u16())
+            .expect("writing to a String can not fail");
+        push_json_string(&mut json, &self.to_string());
+        json.push('}');
+        json
+    }
+}
+
+impl HttpProblem for InvalidS3PathComponent {
+    fn status(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+
+    fn title(&self) -> &'static str {
+        "Invalid S3 path component"
+    }
+}
+
+impl HttpProblem for InvalidComponentKind {
+    fn status(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+
+    fn title(&self) -> &'static str {
+        "Invalid S3 path component"
+    }
+}
+
+impl HttpProblem for InvalidS3Key {
+    fn status(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+
+    fn title(&self) -> &'static str {
+        "Invalid S3 key"
+    }
+}
+
+impl HttpProblem for GrammarViolation {
+    fn status(&self) -> StatusCode {
+        StatusCode::UNPROCESSABLE_ENTITY
+    }
+
+    fn title(&self) -> &'static str {
+        "Key violates grammar"
+    }
+}
+
+impl HttpProblem for LayoutMigrationError {
+    fn status(&self) -> StatusCode {
+        StatusCode::UNPROCESSABLE_ENTITY
+    }
+
+    fn title(&self) -> &'static str {
+        "Key cannot be migrated"
+    }
+}
+
+impl HttpProblem for PatchConflict {
+    fn status(&self) -> StatusCode {
+        StatusCode::CONFLICT
+    }
+
+    fn title(&self) -> &'static str {
+        "Conflicting patch"
+    }
+}
+
+/// Appends `value` as a quoted and escaped JSON string.
+fn push_json_string(json: &mut String, value: &str) {
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => {
+                write!(json, "\\u{:04x}", u32::from(c)).expect("writing to a String can not fail");
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::{InvalidComponentKind, PatchConflict};
+    use crate::http::HttpProblem;
+    use assertr::prelude::*;
+    use http::StatusCode;
+
+    #[test]
+    fn maps_error_kinds_to_status_codes() {
+        assert_that(InvalidComponentKind::Empty.status()).is_equal_to(StatusCode::BAD_REQUEST);
+        let conflict = PatchConflict {
+            path: "a".to_owned(),
+            reason: "exists".to_owned(),
+        };
+        assert_that(conflict.status()).is_equal_to(StatusCode::CONFLICT);
+    }
+
+    #[test]
+    fn escapes_problem_details() {
+        let err = InvalidComponentKind::DisallowedCharacter('"').into_error("a\"\n");
+        let json = err.problem_details();
+        assert_that(json.as_str()).is_equal_to(
+            r#"{"type":"about:blank","title":"Invalid S3 path component","status":400,"detail":"Invalid S3 path component 'a\"\n': Character '\"' is not allowed"}"#,
+        );
+        let value = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+        assert_that(value["detail"].as_str().unwrap_or_default()).is_equal_to(err.to_string());
+    }
+
+    #[test]
+    fn converts_into_io_error() {
+        let err = std::io::Error::from(InvalidComponentKind::Empty.into_error(""));
+        assert_that(err.kind()).is_equal_to(std::io::ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,179 @@
+//! Reading and writing newline-delimited key files ("ndkeys"): one key per line.
+//!
+//! Keys can optionally be URL-encoded (percent-encoded), as done by S3 inventory reports. Keys
+//! are strictly validated when read.
+
+use crate::error::ReadNdKeysError;
+use crate::{S3Path, S3PathBuf};
+use std::io::{BufRead, Write};
+
+/// How keys are encoded in a newline-delimited key file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeyEncoding {
+    /// Keys are written as-is.
+    #[default]
+    Plain,
+
+    /// Keys are percent-encoded, except for unreserved characters and '/'.
+    UrlEncoded,
+}
+
+/// Writes all `paths` to `writer`, one key per line, returning the number of keys written.
+///
+/// Paths are written as-is, so consider wrapping `writer` in a `BufWriter`. The empty path is
+/// skipped, as it would be indistinguishable from a blank line.
+///
+/// # Errors
+///
+/// Returns `Err` if writing fails.
+pub fn write_ndkeys<'p, P: AsRef<S3Path<'p>>>(
+    mut writer: impl Write,
+    paths: impl IntoIterator<Item = P>,
+    encoding: KeyEncoding,
+) -> std::io::Result<usize> {
+    let mut written = 0;
+    for path in paths {
+        let path = path.as_ref();
+        if path.is_empty() {
+            continue;
+        }
+        match encoding {
+            KeyEncoding::Plain => write!(writer, "{path}")?,
+            KeyEncoding::UrlEncoded => {
+                for (i, c) in path.components().enumerate() {
+                    if i > 0 {
+                        writer.write_all(b"/")?;
+                    }
+                    write_url_encoded(&mut writer, c)?;
+                }
+            }
+        }
+        writer.write_all(b"\n")?;
+        written += 1;
+    }
+    Ok(written)
+}
+
+fn write_url_encoded(writer: &mut impl Write, component: &str) -> std::io::Result<()> {
+    for b in component.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b'~') {
+            writer.write_all(&[b])?;
+        } else {
+            write!(writer, "%{b:02X}")?;
+        }
+    }
+    Ok(())
+}
+
+/// Reads keys from `reader`, one key per line, parsing each into a path.
+///
+/// Blank lines are skipped. A trailing carriage return ("\r\n" line endings) is ignored. Keys
+/// must round-trip as defined by [`S3PathBuf::strict_parse`].
+///
+/// ```
+/// use s3_path::io::{read_ndkeys, KeyEncoding};
+///
+/// let input = "foo/bar\n\nfoo/b%20r\n";
+/// let keys = read_ndkeys(input.as_bytes(), KeyEncoding::UrlEncoded).collect::<Vec<_>>();
+/// assert_eq!(keys[0].as_ref().unwrap().to_string(), "foo/bar");
+/// assert!(keys[1].is_err()); // ' ' is not allowed in path components.
+/// ```
+pub fn read_ndkeys(
+    reader: impl BufRead,
+    encoding: KeyEncoding,
+) -> impl Iterator<Item = Result<S3PathBuf, ReadNdKeysError>> {
+    reader.lines().enumerate().filter_map(move |(index, line)| {
+        let line = match line {
+            Ok(line) => line,
+            Err(err) => return Some(Err(ReadNdKeysError::Io(err))),
+        };
+        let key = line.strip_suffix('\r').unwrap_or(&line);
+        if key.is_empty() {
+            return None;
+        }
+        let invalid = |reason: String| ReadNdKeysError::InvalidKey {
+            line: index + 1,
+            reason,
+        };
+        Some(
+            match encoding {
+                KeyEncoding::Plain => Ok(key.to_owned()),
+                KeyEncoding::UrlEncoded => url_decode(key),
+            }
+            .map_err(invalid)
+            .and_then(|key| S3PathBuf::strict_parse(key).map_err(|err| invalid(err.to_string()))),
+        )
+    })
+}
+
+fn url_decode(key: &str) -> Result<String, String> {
+    let bytes = key.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = bytes
+                .get(i + 1..i + 3)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                .ok_or_else(|| format!("Invalid percent-encoding at byte {i}"))?;
+            decoded.push(hex);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).map_err(|_| "Decoded key is not valid UTF-8".to_owned())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::ReadNdKeysError;
+    use crate::io::{read_ndkeys, write_ndkeys, KeyEncoding};
+    use crate::S3PathBuf;
+    use assertr::prelude::*;
+
+    #[test]
+    fn write_then_read_round_trips() {
+        let paths = ["foo/bar.txt", "a/b/c"].map(|key| S3PathBuf::try_from_str(key).unwrap());
+        for encoding in [KeyEncoding::Plain, KeyEncoding::UrlEncoded] {
+            let mut out = Vec::new();
+            let written = write_ndkeys(&mut out, &paths, encoding).unwrap();
+            assert_that(written).is_equal_to(2);
+            assert_that(String::from_utf8(out.clone()).unwrap())
+                .is_equal_to("foo/bar.txt\na/b/c\n".to_owned());
+
+            let read = read_ndkeys(out.as_slice(), encoding)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_that(read).is_equal_to(paths.to_vec());
+        }
+    }
+
+    #[test]
+    fn read_reports_invalid_lines_with_line_number() {
+        let input = "ok\r\n\nfoo//bar\nfoo%2\n";
+        let results = read_ndkeys(input.as_bytes(), KeyEncoding::UrlEncoded).collect::<Vec<_>>();
+        assert_that(results.len()).is_equal_to(3);
+        assert_that(results[0].is_ok()).is_true();
+        assert_that(matches!(
+            results[1],
+            Err(ReadNdKeysError::InvalidKey { line: 3, .. })
+        ))
+        .is_true();
+        assert_that(matches!(
+            results[2],
+            Err(ReadNdKeysError::InvalidKey { line: 4, .. })
+        ))
+        .is_true();
+    }
+
+    #[test]
+    fn url_decoding_is_only_applied_when_requested() {
+        let decoded = read_ndkeys("a%2Eb".as_bytes(), KeyEncoding::UrlEncoded).next();
+        assert_that(decoded.unwrap().unwrap()).has_display_value("a.b");
+        let plain = read_ndkeys("a%2Eb".as_bytes(), KeyEncoding::Plain).next();
+        assert_that(plain.unwrap().is_err()).is_true();
+    }
+}
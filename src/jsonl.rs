@@ -0,0 +1,166 @@
+//! Reading and writing manifests as newline-delimited JSON: one
+//! `{"key": ..., "size": ..., "etag": ...}` record per line.
+//!
+//! Both directions stream, so manifests of huge buckets never need to be held as text in memory.
+//! Records do not carry modification times: entries read get `SystemTime::UNIX_EPOCH`, and
+//! modification times are not written. Further fields of records read are ignored.
+//!
+//! ```
+//! use s3_path::jsonl::{read_manifest_jsonl, write_manifest_jsonl};
+//! use s3_path::manifest::Manifest;
+//!
+//! let input = r#"{"key":"data/a.csv","size":42,"etag":"abc"}
+//! {"key":"data/b.csv","size":7,"etag":"def","storage_class":"GLACIER"}
+//! "#;
+//! let manifest = read_manifest_jsonl(input.as_bytes())
+//!     .collect::<Result<Manifest, _>>()
+//!     .unwrap();
+//! assert_eq!(manifest.len(), 2);
+//!
+//! let mut out = Vec::new();
+//! write_manifest_jsonl(&mut out, manifest.iter()).unwrap();
+//! assert!(String::from_utf8(out).unwrap().starts_with(r#"{"key":"data/a.csv","size":42,"#));
+//! ```
+
+use crate::error::ReadJsonlError;
+use crate::manifest::ObjectMeta;
+use crate::{S3Path, S3PathBuf};
+use std::io::{BufRead, Write};
+use std::time::SystemTime;
+
+#[derive(serde::Deserialize)]
+struct Record {
+    key: String,
+    size: u64,
+    etag: String,
+}
+
+#[derive(serde::Serialize)]
+struct RecordRef<'r> {
+    key: String,
+    size: u64,
+    etag: &'r str,
+}
+
+/// Writes one record per entry to `writer`, returning the number of records written.
+///
+/// Records are written as-is, so consider wrapping `writer` in a `BufWriter`.
+///
+/// # Errors
+///
+/// Returns `Err` if writing fails.
+pub fn write_manifest_jsonl<'p, 'm, P: AsRef<S3Path<'p>>>(
+    mut writer: impl Write,
+    entries: impl IntoIterator<Item = (P, &'m ObjectMeta)>,
+) -> std::io::Result<usize> {
+    let mut written = 0;
+    for (path, meta) in entries {
+        let record = RecordRef {
+            key: path.as_ref().to_string(),
+            size: meta.size,
+            etag: &meta.etag,
+        };
+        serde_json::to_writer(&mut writer, &record)?;
+        writer.write_all(b"\n")?;
+        written += 1;
+    }
+    Ok(written)
+}
+
+/// Reads records from `reader`, one per line, parsing each into a path and its metadata.
+/// Collect into a [`Manifest`](crate::manifest::Manifest) to read a whole manifest.
+///
+/// Blank lines are skipped. Keys must round-trip as defined by [`S3PathBuf::strict_parse`].
+pub fn read_manifest_jsonl(
+    reader: impl BufRead,
+) -> impl Iterator<Item = Result<(S3PathBuf, ObjectMeta), ReadJsonlError>> {
+    reader.lines().enumerate().filter_map(|(index, line)| {
+        let line = match line {
+            Ok(line) => line,
+            Err(err) => return Some(Err(ReadJsonlError::Io(err))),
+        };
+        if line.trim().is_empty() {
+            return None;
+        }
+        let line_number = index + 1;
+        Some(
+            serde_json::from_str::<Record>(&line)
+                .map_err(|err| ReadJsonlError::InvalidRecord {
+                    line: line_number,
+                    reason: err.to_string(),
+                })
+                .and_then(|record| {
+                    let path = S3PathBuf::strict_parse(&record.key).map_err(|err| {
+                        ReadJsonlError::InvalidKey {
+                            line: line_number,
+                            reason: err.to_string(),
+                        }
+                    })?;
+                    Ok((
+                        path,
+                        ObjectMeta {
+                            size: record.size,
+                            etag: record.etag,
+                            last_modified: SystemTime::UNIX_EPOCH,
+                        },
+                    ))
+                }),
+        )
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::ReadJsonlError;
+    use crate::jsonl::{read_manifest_jsonl, write_manifest_jsonl};
+    use crate::manifest::{Manifest, ObjectMeta};
+    use crate::S3PathBuf;
+    use assertr::prelude::*;
+    use std::time::SystemTime;
+
+    fn meta(size: u64, etag: &str) -> ObjectMeta {
+        ObjectMeta {
+            size,
+            etag: etag.to_owned(),
+            last_modified: SystemTime::UNIX_EPOCH,
+        }
+    }
+
+    #[test]
+    fn write_then_read_round_trips() {
+        let manifest = [("b/c", meta(2, "x\"y")), ("a", meta(1, "e"))]
+            .into_iter()
+            .map(|(key, meta)| (S3PathBuf::try_from_str(key).unwrap(), meta))
+            .collect::<Manifest>();
+        let mut out = Vec::new();
+        let written = write_manifest_jsonl(&mut out, manifest.iter()).unwrap();
+        assert_that(written).is_equal_to(2);
+        assert_that(String::from_utf8(out.clone()).unwrap()).is_equal_to(
+            "{\"key\":\"a\",\"size\":1,\"etag\":\"e\"}\n{\"key\":\"b/c\",\"size\":2,\"etag\":\"x\\\"y\"}\n"
+                .to_owned(),
+        );
+
+        let read = read_manifest_jsonl(out.as_slice())
+            .collect::<Result<Manifest, _>>()
+            .unwrap();
+        assert_that(read).is_equal_to(manifest);
+    }
+
+    #[test]
+    fn reports_line_of_invalid_records() {
+        let input = "{\"key\":\"a\",\"size\":1,\"etag\":\"e\"}\n\n{\"key\":\"a\"}\n{\"key\":\"a//b\",\"size\":1,\"etag\":\"e\"}\n";
+        let read = read_manifest_jsonl(input.as_bytes()).collect::<Vec<_>>();
+        assert_that(read.len()).is_equal_to(3);
+        assert_that(read[0].is_ok()).is_true();
+        assert_that(matches!(
+            read[1],
+            Err(ReadJsonlError::InvalidRecord { line: 3, .. })
+        ))
+        .is_true();
+        assert_that(matches!(
+            read[2],
+            Err(ReadJsonlError::InvalidKey { line: 4, .. })
+        ))
+        .is_true();
+    }
+}
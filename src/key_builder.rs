@@ -0,0 +1,167 @@
+/// Generates a type-state builder for keys of a fixed layout.
+///
+/// Declares a module containing a `KeyBuilder`, offering one setter per declared component, in
+/// declaration order. Each setter is only available after all previous ones were called, and
+/// `build` only after all setters were called. Building keys with components in the wrong order
+/// or with components missing is therefore a compile error, a stronger guarantee than checking
+/// keys against a [`KeyGrammar`](crate::grammar::KeyGrammar) at runtime.
+///
+/// String literals declare fixed components, pushed automatically.
+///
+/// ```
+/// s3_path::s3_key_builder! {
+///     /// Keys of daily reports.
+///     pub mod report_key {
+///         "tenants", tenant, date, file
+///     }
+/// }
+///
+/// let key = report_key::KeyBuilder::new()
+///     .tenant("acme")?
+///     .date("2024-01-31")?
+///     .file("report.csv")?
+///     .build();
+/// assert_eq!(key.to_string(), "tenants/acme/2024-01-31/report.csv");
+/// # Ok::<(), s3_path::error::InvalidS3PathComponent>(())
+/// ```
+///
+/// Calling setters out of order does not compile:
+///
+/// ```compile_fail
+/// s3_path::s3_key_builder! {
+///     pub mod report_key { tenant, date }
+/// }
+///
+/// let key = report_key::KeyBuilder::new().date("2024-01-31")?.tenant("acme")?.build();
+/// # Ok::<(), s3_path::error::InvalidS3PathComponent>(())
+/// ```
+///
+/// Neither does building with components missing:
+///
+/// ```compile_fail
+/// s3_path::s3_key_builder! {
+///     pub mod report_key { tenant, date }
+/// }
+///
+/// let key = report_key::KeyBuilder::new().tenant("acme")?.build();
+/// # Ok::<(), s3_path::error::InvalidS3PathComponent>(())
+/// ```
+///
+/// Literals which are not valid path components do not compile:
+///
+/// ```compile_fail
+/// s3_path::s3_key_builder! {
+///     pub mod report_key { "my reports", file }
+/// }
+/// ```
+#[macro_export]
+macro_rules! s3_key_builder {
+    ($(#[$meta:meta])* $vis:vis mod $name:ident { $($steps:tt)* }) => {
+        $(#[$meta])*
+        $vis mod $name {
+            /// State of a `KeyBuilder` before any setter was called.
+            pub struct Start;
+
+            /// State of a `KeyBuilder` after one more setter was called than in state `P`.
+            pub struct Step<P>(::std::marker::PhantomData<P>);
+
+            /// Builds keys, with setters available in declaration order only.
+            pub struct KeyBuilder<S> {
+                path: $crate::S3PathBuf,
+                state: ::std::marker::PhantomData<S>,
+            }
+
+            impl KeyBuilder<Start> {
+                /// Creates a builder, expecting the first declared component.
+                #[must_use]
+                pub fn new() -> Self {
+                    Self {
+                        path: $crate::S3PathBuf::new(),
+                        state: ::std::marker::PhantomData,
+                    }
+                }
+            }
+
+            impl ::std::default::Default for KeyBuilder<Start> {
+                fn default() -> Self {
+                    Self::new()
+                }
+            }
+
+            $crate::__s3_key_builder_steps!(Start; []; $($steps)*);
+        }
+    };
+}
+
+/// Implements the setters and `build` of [`s3_key_builder!`], one step at a time. Literals are
+/// checked at compile time, collected and pushed by the next setter, or by `build`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __s3_key_builder_steps {
+    ($state:ty; [$($literal:literal)*]; $next:literal $(, $($rest:tt)*)?) => {
+        const _: () = $crate::__check_literal_component($next);
+        $crate::__s3_key_builder_steps!($state; [$($literal)* $next]; $($($rest)*)?);
+    };
+    ($state:ty; [$($literal:literal)*]; $setter:ident $(, $($rest:tt)*)?) => {
+        impl KeyBuilder<$state> {
+            #[doc = concat!("Sets the `", stringify!($setter), "` component.")]
+            ///
+            /// # Errors
+            ///
+            /// Returns `Err` when `component` is not a valid path component.
+            pub fn $setter(
+                mut self,
+                component: impl ::std::convert::Into<::std::borrow::Cow<'static, str>>,
+            ) -> ::std::result::Result<KeyBuilder<Step<$state>>, $crate::error::InvalidS3PathComponent>
+            {
+                $(self.path.push($literal)?;)*
+                self.path.push(component)?;
+                ::std::result::Result::Ok(KeyBuilder {
+                    path: self.path,
+                    state: ::std::marker::PhantomData,
+                })
+            }
+        }
+
+        $crate::__s3_key_builder_steps!(Step<$state>; []; $($($rest)*)?);
+    };
+    ($state:ty; [$($literal:literal)*];) => {
+        impl KeyBuilder<$state> {
+            /// Returns the built key.
+            #[must_use]
+            #[allow(unused_mut)] // In case there are no trailing literals.
+            pub fn build(mut self) -> $crate::S3PathBuf {
+                $(self.path.push($literal).expect("literals are checked at compile time");)*
+                self.path
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use assertr::prelude::*;
+
+    crate::s3_key_builder! {
+        mod literal_key {
+            "exports", tenant, "daily", file, "done"
+        }
+    }
+
+    #[test]
+    fn pushes_literals_between_setters_and_on_build() {
+        let key = literal_key::KeyBuilder::new()
+            .tenant("acme")
+            .unwrap()
+            .file("a.csv")
+            .unwrap()
+            .build();
+        assert_that(key).has_display_value("exports/acme/daily/a.csv/done");
+    }
+
+    #[test]
+    fn setters_validate_components() {
+        let result = literal_key::KeyBuilder::new().tenant("..");
+        assert_that(result.is_err()).is_true();
+    }
+}
@@ -1,12 +1,186 @@
+pub mod annotate;
+pub mod anonymize;
+pub mod arn;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+pub mod batch;
+#[cfg(feature = "cache")]
+pub mod cache;
+pub mod cli;
+pub mod coverage;
+pub mod cow;
+#[cfg(feature = "csv")]
+pub mod csv;
+#[cfg(feature = "miette")]
+pub mod diagnostic;
+#[cfg(any(feature = "md5", feature = "sha2"))]
+mod digest;
+pub mod encoding;
 pub mod error;
+pub mod exact;
+pub mod fixtures;
+mod format;
+pub mod generators;
+pub mod glob;
+pub mod grammar;
+mod hash;
+pub mod heat;
+#[cfg(feature = "http")]
+pub mod http;
+#[cfg(feature = "io")]
+pub mod io;
+#[cfg(feature = "jsonl")]
+pub mod jsonl;
+mod key_builder;
+pub mod listing;
+pub mod manifest;
+pub mod merkle;
+pub mod migrate;
+#[cfg(feature = "otel")]
+pub mod otel;
+pub mod parse;
+pub mod patch;
+pub mod policy;
+pub mod prefix_table;
+pub mod raw;
+pub mod renames;
+pub mod retention;
+mod rng;
+pub mod sampling;
+pub mod sanitize;
+pub mod schedule;
+pub mod separator;
+#[cfg(feature = "serde")]
+pub mod serde;
+#[cfg(feature = "serde_with")]
+pub mod serde_with;
+pub mod storage_class;
+pub mod store;
+pub mod tagging;
+pub mod telemetry;
+#[cfg(feature = "test-util")]
+pub mod testing;
 mod validation;
 
-use crate::error::InvalidS3PathComponent;
+use crate::error::{InvalidComponentKind, InvalidS3PathComponent, UnsafeStdPath};
+#[doc(hidden)]
+pub use crate::format::check_template as __check_format_template;
+#[doc(hidden)]
+pub use crate::format::ComponentArg as __FormatComponentArg;
+#[doc(hidden)]
+pub use crate::format::FormatState as __FormatState;
+#[doc(hidden)]
+pub use crate::format::PathArg as __FormatPathArg;
+#[doc(hidden)]
+pub use crate::validation::check_literal_component as __check_literal_component;
 use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
 use std::fmt::Formatter;
 use std::ops::Deref;
 use std::path::PathBuf;
 
+/// Validates a single path component, applying the same rules as all constructors, but without
+/// allocating on failure.
+///
+/// Meant for probing untrusted input at a high rate. Unlike the constructors, failures are not
+/// reported to the [`telemetry`] observer.
+///
+/// ```
+/// use s3_path::error::InvalidComponentKind;
+/// use s3_path::validate_component_fast;
+///
+/// assert_eq!(validate_component_fast("foo.txt"), Ok(()));
+/// assert_eq!(
+///     validate_component_fast("foo$"),
+///     Err(InvalidComponentKind::DisallowedCharacter('$'))
+/// );
+/// ```
+///
+/// # Errors
+///
+/// Returns `Err` when `component`
+/// - is empty
+/// - contains characters other than: ascii alphanumeric characters, '-', '_' and '.'
+/// - is equal to `.` or `..`
+pub fn validate_component_fast(component: &str) -> Result<(), InvalidComponentKind> {
+    match validation::find_violation(component) {
+        None => Ok(()),
+        Some(kind) => Err(kind),
+    }
+}
+
+/// Concatenates all `paths` into a new `S3PathBuf`, e.g. a configured root, a layout fragment
+/// and a relative file path.
+///
+/// Use [`s3_concat!`] to mix paths and single components.
+///
+/// ```
+/// use s3_path::{concat, s3_path};
+///
+/// let root = s3_path!("data", "prod").unwrap();
+/// let file = s3_path!("exports", "report.csv").unwrap();
+/// assert_eq!(concat([root, file]).to_string(), "data/prod/exports/report.csv");
+/// ```
+#[must_use]
+pub fn concat<'p, P: AsRef<S3Path<'p>>>(paths: impl IntoIterator<Item = P>) -> S3PathBuf {
+    let mut concatenated = S3PathBuf::new();
+    for path in paths {
+        concatenated.components.extend(
+            path.as_ref()
+                .components_cow()
+                .map(|c| Cow::Owned(c.to_string())),
+        );
+    }
+    concatenated
+}
+
+/// A segment accepted by [`s3_concat!`]: either a path, contributing all of its components, or
+/// a single component, contributing itself after being validated.
+///
+/// Implemented for references to paths, and for the component types accepted by
+/// [`S3PathBuf::push`] having a `'static` lifetime.
+pub trait ConcatSegment {
+    /// Appends this segment to `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when this segment is an invalid component.
+    fn append_to(self, path: &mut S3PathBuf) -> Result<(), InvalidS3PathComponent>;
+}
+
+impl ConcatSegment for &S3Path<'_> {
+    fn append_to(self, path: &mut S3PathBuf) -> Result<(), InvalidS3PathComponent> {
+        path.components
+            .extend(self.components_cow().map(|c| Cow::Owned(c.to_string())));
+        Ok(())
+    }
+}
+
+impl ConcatSegment for &S3PathBuf {
+    fn append_to(self, path: &mut S3PathBuf) -> Result<(), InvalidS3PathComponent> {
+        self.as_path().append_to(path)
+    }
+}
+
+impl ConcatSegment for &'static str {
+    fn append_to(self, path: &mut S3PathBuf) -> Result<(), InvalidS3PathComponent> {
+        path.push(self).map(|_| ())
+    }
+}
+
+impl ConcatSegment for String {
+    fn append_to(self, path: &mut S3PathBuf) -> Result<(), InvalidS3PathComponent> {
+        path.push(self).map(|_| ())
+    }
+}
+
+impl ConcatSegment for Cow<'static, str> {
+    fn append_to(self, path: &mut S3PathBuf) -> Result<(), InvalidS3PathComponent> {
+        path.push(self).map(|_| ())
+    }
+}
+
 /// Var-arg macro to create an `S3Path`, borrowing from the given string literals.
 ///
 /// ```
@@ -60,11 +234,50 @@ macro_rules! s3_path_buf {
     }}
 }
 
+/// Var-arg macro to create an `S3PathBuf` from paths and individual components, in any order.
+///
+/// ```
+/// use s3_path::{s3_concat, s3_path, S3PathBuf};
+///
+/// let root = S3PathBuf::try_from_str("data/prod").unwrap();
+/// let layout = s3_path!("exports", "daily").unwrap();
+/// let file_name = String::from("report.csv");
+///
+/// let path = s3_concat!(&root, "tenant-42", layout, file_name).unwrap();
+/// assert_eq!(path.to_string(), "data/prod/tenant-42/exports/daily/report.csv");
+/// ```
+///
+/// Every segment passed into this macro must implement [`ConcatSegment`]. Components are
+/// validated, and the first invalid one is returned as error.
+#[macro_export]
+macro_rules! s3_concat {
+    ($($segment:expr),* $(,)?) => {{
+        #[allow(unused_mut)] // In case zero segments are passed in.
+        let mut path = $crate::S3PathBuf::new();
+        #[allow(unused_mut)] // In case zero segments are passed in.
+        let mut error = None;
+        $(
+            if error.is_none() {
+                if let Err(e) = $crate::ConcatSegment::append_to($segment, &mut path) {
+                    error = Some(e);
+                }
+            }
+        )*
+        match error {
+            Some(err) => Result::<$crate::S3PathBuf, $crate::error::InvalidS3PathComponent>::Err(err),
+            None => Result::<$crate::S3PathBuf, $crate::error::InvalidS3PathComponent>::Ok(path),
+        }
+    }}
+}
+
 /// A borrowed, unsized S3 storage path.
 ///
+/// Equality and hashing only consider the components, not whether they are borrowed or owned,
+/// and agree with [`S3PathBuf`], so both can be used interchangeably to look up map keys.
+///
 // Must be repr(transparent) to safely convert from the slice.
 #[repr(transparent)]
-#[derive(PartialEq, Eq)]
+#[derive(PartialEq, Eq, Hash)]
 pub struct S3Path<'i>([Cow<'i, str>]);
 
 /// An owned S3 storage path.
@@ -115,6 +328,104 @@ impl PartialEq<S3PathBuf> for &&S3Path<'_> {
     }
 }
 
+/// Hashes exactly like the borrowed [`S3Path`]: for every path `p`,
+/// `hash(p.as_path()) == hash(p)`. Together with the `PartialEq` impls between both types, this
+/// allows looking up owned keys using borrowed paths.
+impl std::hash::Hash for S3PathBuf {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.as_path().hash(state);
+    }
+}
+
+/// Allows looking up `S3PathBuf` keys of maps and sets using a borrowed `S3Path`, without
+/// allocating an owned key:
+///
+/// ```
+/// use s3_path::{S3Path, S3PathBuf};
+/// use std::borrow::Cow;
+/// use std::collections::HashMap;
+///
+/// let mut sizes = HashMap::new();
+/// sizes.insert(S3PathBuf::try_from_str("a/b").unwrap(), 42);
+///
+/// let components = [Cow::Borrowed("a"), Cow::Borrowed("b")];
+/// let borrowed = S3Path::new(&components).unwrap();
+/// assert_eq!(sizes.get(borrowed), Some(&42));
+/// ```
+///
+/// Through the blanket impl of `equivalent::Equivalent`, this also covers `hashbrown` and
+/// `indexmap` collections.
+impl<'i> std::borrow::Borrow<S3Path<'i>> for S3PathBuf {
+    fn borrow(&self) -> &S3Path<'i> {
+        self
+    }
+}
+
+/// Allows `Cow<S3Path>`, accepting either a borrowed or an owned path in one parameter:
+///
+/// ```
+/// use s3_path::{S3Path, S3PathBuf};
+/// use std::borrow::Cow;
+///
+/// fn describe(path: Cow<'_, S3Path<'_>>) -> String {
+///     format!("{} ({} components)", path, path.len())
+/// }
+///
+/// let path = S3PathBuf::try_from_str("a/b").unwrap();
+/// assert_eq!(describe(Cow::Borrowed(&path)), "a/b (2 components)");
+/// assert_eq!(describe(Cow::Owned(path)), "a/b (2 components)");
+/// ```
+impl ToOwned for S3Path<'_> {
+    type Owned = S3PathBuf;
+
+    fn to_owned(&self) -> S3PathBuf {
+        S3Path::to_owned(self)
+    }
+}
+
+/// Orders paths by the bytes of their rendered key, which is the order in which S3 lists keys.
+///
+/// This differs from comparing components one by one, as in `"a-b" < "a/b"`: '-' sorts before
+/// the delimiter '/'.
+impl Ord for S3Path<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.bytes().cmp(other.bytes())
+    }
+}
+
+impl PartialOrd for S3Path<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Orders paths like [`S3Path`], by the bytes of their rendered key.
+impl Ord for S3PathBuf {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_path().cmp(other.as_path())
+    }
+}
+
+impl PartialOrd for S3PathBuf {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Allow comparisons between `S3Path` and `S3PathBuf`.
+impl PartialOrd<S3Path<'_>> for S3PathBuf {
+    fn partial_cmp(&self, other: &S3Path<'_>) -> Option<Ordering> {
+        Some(self.as_path().cmp(other))
+    }
+}
+
+/// Allow comparisons between `S3Path` and `S3PathBuf`.
+impl PartialOrd<S3PathBuf> for S3Path<'_> {
+    fn partial_cmp(&self, other: &S3PathBuf) -> Option<Ordering> {
+        Some(self.cmp(other.as_path()))
+    }
+}
+
 impl<'i> AsRef<S3Path<'i>> for S3Path<'i> {
     fn as_ref(&self) -> &S3Path<'i> {
         self
@@ -233,6 +544,14 @@ impl<'i> S3Path<'i> {
         self.0.iter().map(std::convert::AsRef::as_ref)
     }
 
+    /// Returns an iterator over the components of this path, as stored.
+    ///
+    /// Unlike [`components`](Self::components), this tells whether a component is borrowed (e.g.
+    /// from a `'static` string) or owned, allowing callers to pass it on without copying.
+    pub fn components_cow(&self) -> std::slice::Iter<'_, Cow<'i, str>> {
+        self.0.iter()
+    }
+
     /// Returns the component at the given index, or None if the index is out of bounds.
     pub fn get(&'i self, index: usize) -> Option<&'i str> {
         self.0.get(index).map(std::convert::AsRef::as_ref)
@@ -243,6 +562,40 @@ impl<'i> S3Path<'i> {
         self.0.last().map(std::convert::AsRef::as_ref)
     }
 
+    /// Returns the first component of this path, or None if the path is empty.
+    #[must_use]
+    pub fn first(&self) -> Option<&str> {
+        self.0.first().map(std::convert::AsRef::as_ref)
+    }
+
+    /// Returns the top-level component of this path, the "directory" directly below the bucket
+    /// root, or None if the path is empty. Same as [`first`](Self::first).
+    #[must_use]
+    pub fn root(&self) -> Option<&str> {
+        self.first()
+    }
+
+    /// Returns the depth of this path below the bucket root, which is its number of components.
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns the `n`-th component counted from the end, or None if the path has not enough
+    /// components. `nth_from_end(0)` is the [`last`](Self::last) component.
+    ///
+    /// ```
+    /// use s3_path::S3PathBuf;
+    ///
+    /// let path = S3PathBuf::try_from(["tenant", "2024", "data.csv"]).unwrap();
+    /// assert_eq!(path.nth_from_end(1), Some("2024"));
+    /// assert_eq!(path.nth_from_end(3), None);
+    /// ```
+    #[must_use]
+    pub fn nth_from_end(&self, n: usize) -> Option<&str> {
+        self.0.iter().rev().nth(n).map(std::convert::AsRef::as_ref)
+    }
+
     /// Returns all but the last component of this path, or None if the path is empty.
     #[must_use]
     pub fn parent(&'i self) -> Option<&'i S3Path<'i>> {
@@ -259,12 +612,30 @@ impl<'i> S3Path<'i> {
         }
     }
 
+    /// Returns true if `base` is a prefix of this path, comparing whole components.
+    ///
+    /// ```
+    /// use s3_path::s3_path;
+    ///
+    /// let path = s3_path!("tenants", "acme", "data.csv").unwrap();
+    /// assert!(path.starts_with(s3_path!("tenants", "acme").unwrap()));
+    /// assert!(!path.starts_with(s3_path!("tenants", "ac").unwrap()));
+    /// ```
+    #[must_use]
+    pub fn starts_with(&self, base: &S3Path<'_>) -> bool {
+        self.0.len() >= base.0.len() && self.0[..base.0.len()] == base.0
+    }
+
     /// Convert this S3 path to a `std::path::PathBuf`, allowing you to use this S3 path as a
     /// system file path.
     ///
     /// Our strong guarantee that path components only consist of ascii alphanumeric characters,
     /// '-', '_' and '.' and that no path traversal components ('.' and '..') are allowed, makes
     /// this a safe operation.
+    ///
+    /// Components added using [`S3PathBuf::push_trusted`] are only checked for '/' and path
+    /// traversal in release builds. Use [`try_to_std_path_buf`](Self::try_to_std_path_buf) if such
+    /// components may contain other characters.
     #[must_use]
     pub fn to_std_path_buf(&self) -> PathBuf {
         let mut path = PathBuf::new();
@@ -273,8 +644,257 @@ impl<'i> S3Path<'i> {
         }
         path
     }
+
+    /// Like [`to_std_path_buf`](Self::to_std_path_buf), but checking every component first.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` listing all invalid components, which can only exist in release builds when
+    /// invalid components were added using [`S3PathBuf::push_trusted`].
+    pub fn try_to_std_path_buf(&self) -> Result<PathBuf, UnsafeStdPath> {
+        let components = self
+            .0
+            .iter()
+            .filter_map(|c| validation::find_violation(c).map(|kind| kind.into_error(c)))
+            .collect::<Vec<_>>();
+        if !components.is_empty() {
+            return Err(UnsafeStdPath { components });
+        }
+        Ok(self.to_std_path_buf())
+    }
+
+    /// Returns a stable 64-bit fingerprint of this path.
+    ///
+    /// The fingerprint is the 64-bit FNV-1a hash of the UTF-8 bytes of the rendered key (all
+    /// components joined by '/'). It is guaranteed to never change between releases, platforms or
+    /// processes, and can easily be reproduced in other languages.
+    ///
+    /// ```
+    /// use s3_path::s3_path;
+    ///
+    /// let path = s3_path!("foo", "bar").unwrap();
+    /// assert_eq!(path.fingerprint(), 0x571d_17d6_ef2d_ef0d);
+    /// ```
+    #[must_use]
+    pub fn fingerprint(&self) -> u64 {
+        hash::fnv1a_64(self.0.iter())
+    }
+
+    /// Deterministically assigns this path to one of `n_shards` shards, returning a shard index in
+    /// `0..n_shards`.
+    ///
+    /// Uses jump consistent hashing over the `fingerprint` of this path, so that
+    /// - every deployment (and any other implementation of the same algorithm) partitions the key
+    ///   space identically and
+    /// - changing the number of shards from `n` to `n + 1` only moves about `1 / (n + 1)` of all
+    ///   keys to a different shard.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n_shards` is zero.
+    #[must_use]
+    pub fn shard_for(&self, n_shards: u32) -> u32 {
+        assert!(n_shards > 0, "n_shards must be greater than zero");
+        hash::jump_consistent_hash(self.fingerprint(), n_shards)
+    }
+
+    /// Renders this path as a metrics label of bounded cardinality.
+    ///
+    /// The first `max_cardinality_depth` components are kept as is. Should the path have further
+    /// components, these are replaced by a single `#xx` component, `xx` being one of 256 hash
+    /// buckets (two hex digits) derived from the replaced components. Labels therefore never
+    /// exceed 256 distinct values per distinct `max_cardinality_depth`-prefix.
+    ///
+    /// ```
+    /// use s3_path::s3_path;
+    ///
+    /// let path = s3_path!("tenants", "42", "exports", "2024-01-01.csv").unwrap();
+    /// assert_eq!(path.to_metric_label(2), "tenants/42/#67");
+    /// assert_eq!(path.to_metric_label(4), "tenants/42/exports/2024-01-01.csv");
+    /// ```
+    #[must_use]
+    pub fn to_metric_label(&self, max_cardinality_depth: usize) -> String {
+        if self.0.len() <= max_cardinality_depth {
+            return self.to_string();
+        }
+        let (kept, rest) = self.0.split_at(max_cardinality_depth);
+        let bucket = hash::fnv1a_64(rest.iter()) & 0xff;
+        let mut label = String::new();
+        for c in kept {
+            label.push_str(c);
+            label.push('/');
+        }
+        label.push_str(&format!("#{bucket:02x}"));
+        label
+    }
+
+    /// Derives a KMS encryption context binding the first `depth` components of this path.
+    ///
+    /// The context holds the single entry `"prefix"`, mapping to these components joined by '/'
+    /// (all components, if the path is not as deep). Objects encrypted with this context can only
+    /// be decrypted by supplying the same prefix, e.g. to tie data keys to a tenant.
+    ///
+    /// ```
+    /// use s3_path::s3_path;
+    ///
+    /// let path = s3_path!("tenants", "123", "exports", "data.csv").unwrap();
+    /// let context = path.encryption_context(2);
+    /// assert_eq!(context["prefix"], "tenants/123");
+    /// ```
+    #[must_use]
+    pub fn encryption_context(&self, depth: usize) -> BTreeMap<String, String> {
+        let prefix = self.0[..depth.min(self.0.len())].join("/");
+        BTreeMap::from([("prefix".to_string(), prefix)])
+    }
+
+    /// Renders the change from this path to `new` compactly, as in `common/{old→new}/rest`.
+    ///
+    /// The components both paths start and end with are rendered once, the differing components
+    /// in between are rendered in braces. Equal paths are rendered as-is.
+    ///
+    /// ```
+    /// use s3_path::s3_path;
+    ///
+    /// let old = s3_path!("data", "2024", "report.csv").unwrap();
+    /// let new = s3_path!("data", "archive", "2024", "report.csv").unwrap();
+    /// assert_eq!(old.diff_display(new), "data/{→archive}/2024/report.csv");
+    /// ```
+    #[must_use]
+    pub fn diff_display(&self, new: &S3Path<'_>) -> String {
+        let prefix = self
+            .0
+            .iter()
+            .zip(new.0.iter())
+            .take_while(|(l, r)| l == r)
+            .count();
+        let (old_rest, new_rest) = (&self.0[prefix..], &new.0[prefix..]);
+        let suffix = old_rest
+            .iter()
+            .rev()
+            .zip(new_rest.iter().rev())
+            .take_while(|(l, r)| l == r)
+            .count();
+        let old_middle = &old_rest[..old_rest.len() - suffix];
+        let new_middle = &new_rest[..new_rest.len() - suffix];
+
+        let mut parts = self.0[..prefix]
+            .iter()
+            .map(|c| c.to_string())
+            .collect::<Vec<_>>();
+        if !old_middle.is_empty() || !new_middle.is_empty() {
+            parts.push(format!(
+                "{{{}→{}}}",
+                old_middle.join("/"),
+                new_middle.join("/")
+            ));
+        }
+        parts.extend(old_rest[old_middle.len()..].iter().map(|c| c.to_string()));
+        parts.join("/")
+    }
+
+    /// Returns the first of this path, `name-1.ext`, `name-2.ext`, ... for which `exists` returns
+    /// false, numbering the last component before its extension. Standardizes the "file already
+    /// exists" handling of uploads.
+    ///
+    /// `exists` is called with candidates in ascending order, until it returns false.
+    ///
+    /// ```
+    /// use s3_path::{s3_path, S3Path};
+    ///
+    /// let path = s3_path!("uploads", "report.csv").unwrap();
+    /// let existing = ["uploads/report.csv", "uploads/report-1.csv"];
+    /// let free = path.disambiguate(|candidate: &S3Path<'_>| {
+    ///     existing.contains(&candidate.to_string().as_str())
+    /// });
+    /// assert_eq!(free.to_string(), "uploads/report-2.csv");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if this path is empty.
+    pub fn disambiguate(&self, mut exists: impl FnMut(&S3Path<'_>) -> bool) -> S3PathBuf {
+        let mut candidate = S3PathBuf {
+            components: self.0.iter().map(|c| Cow::Owned(c.to_string())).collect(),
+        };
+        if !exists(&candidate) {
+            return candidate;
+        }
+        let last = self.0.last().expect("path must not be empty");
+        // A leading '.' (as in ".gitignore") does not start an extension.
+        let (stem, extension) = match last.rfind('.') {
+            Some(i) if i > 0 => last.split_at(i),
+            _ => (last.as_ref(), ""),
+        };
+        for n in 1.. {
+            // Digits and '-' keep the component valid.
+            *candidate.components.last_mut().expect("not empty") =
+                Cow::Owned(format!("{stem}-{n}{extension}"));
+            if !exists(&candidate) {
+                break;
+            }
+        }
+        candidate
+    }
+
+    /// Returns an iterator over the UTF-8 bytes of the rendered key, including the '/'
+    /// separators, without allocating the key.
+    ///
+    /// ```
+    /// use s3_path::s3_path;
+    ///
+    /// let path = s3_path!("foo", "bar").unwrap();
+    /// assert_eq!(path.bytes().len(), 7);
+    /// assert!(path.bytes().eq(*b"foo/bar"));
+    /// ```
+    pub fn bytes(&self) -> Bytes<'_, 'i> {
+        let len = self.0.iter().map(|c| c.len()).sum::<usize>() + self.0.len().saturating_sub(1);
+        Bytes {
+            components: self.0.iter(),
+            current: [].iter(),
+            started: false,
+            remaining: len,
+        }
+    }
+}
+
+/// Iterator over the bytes of a rendered key. Created by [`S3Path::bytes`].
+#[derive(Debug, Clone)]
+pub struct Bytes<'p, 'i> {
+    components: std::slice::Iter<'p, Cow<'i, str>>,
+    current: std::slice::Iter<'p, u8>,
+    started: bool,
+    remaining: usize,
+}
+
+impl Iterator for Bytes<'_, '_> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        loop {
+            if let Some(byte) = self.current.next() {
+                self.remaining -= 1;
+                return Some(*byte);
+            }
+            let component = self.components.next()?;
+            self.current = component.as_bytes().iter();
+            // Every component but the first is preceded by a separator.
+            if self.started {
+                self.remaining -= 1;
+                return Some(b'/');
+            }
+            self.started = true;
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
 }
 
+impl ExactSizeIterator for Bytes<'_, '_> {}
+
+impl std::iter::FusedIterator for Bytes<'_, '_> {}
+
 // Deref - NameBuf can be automatically converted to &Name<'static>
 impl Deref for S3PathBuf {
     type Target = S3Path<'static>;
@@ -300,6 +920,10 @@ impl S3PathBuf {
     ///
     /// NO component is parsed for slashes ('/') to be split up further!
     ///
+    /// Calls of `S3PathBuf::try_from` resolve to this method instead of the `TryFrom`
+    /// implementations, which are meant for generic code. Note that `TryFrom<&str>` and
+    /// `TryFrom<String>` parse keys like `try_from_str`, splitting at slashes.
+    ///
     /// # Errors
     ///
     /// Returns `Err` when any given component
@@ -337,6 +961,77 @@ impl S3PathBuf {
         Ok(path)
     }
 
+    /// Like `try_from_str`, but also accepts backslashes ('\') as separators, as in
+    /// Windows-style paths like `foo\bar\baz.txt` pasted by users.
+    ///
+    /// The resulting path is still rendered using forward slashes. Consecutive separators, of
+    /// either kind, are treated as one.
+    ///
+    /// ```
+    /// use s3_path::S3PathBuf;
+    ///
+    /// let path = S3PathBuf::try_from_str_any_separator("foo\\bar/baz.txt").unwrap();
+    /// assert_eq!(path.to_string(), "foo/bar/baz.txt");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when any component read
+    /// - contains characters other than: ascii alphanumeric characters, '-', '_' and '.'
+    /// - is equal to `.` or `..`
+    pub fn try_from_str_any_separator(
+        string: impl AsRef<str>,
+    ) -> Result<Self, InvalidS3PathComponent> {
+        let mut path = S3PathBuf::new();
+        for c in string.as_ref().split(['/', '\\']) {
+            // Skip empty components from consecutive separators
+            if !c.is_empty() {
+                path.push(Cow::Owned(c.to_string()))?;
+            }
+        }
+        Ok(path)
+    }
+
+    /// Like `try_from_str`, but rejects any input which would not round-trip, i.e. that would
+    /// not be rendered back to exactly `string`.
+    ///
+    /// The empty string is parsed as the empty path. Any other input must not contain leading,
+    /// trailing or repeated slashes.
+    ///
+    /// For every path `p`, `S3PathBuf::strict_parse(p.to_string())` returns a path equal to `p`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when any component read
+    /// - is empty (caused by a leading, trailing or repeated slash)
+    /// - contains characters other than: ascii alphanumeric characters, '-', '_' and '.'
+    /// - is equal to `.` or `..`
+    pub fn strict_parse(string: impl AsRef<str>) -> Result<Self, InvalidS3PathComponent> {
+        let string = string.as_ref();
+        let mut path = S3PathBuf::new();
+        if !string.is_empty() {
+            for c in string.split('/') {
+                validation::validate_component(c)?;
+                path.components.push(Cow::Owned(c.to_string()));
+            }
+        }
+        Ok(path)
+    }
+
+    /// Returns true if `string` is a valid path which is rendered back to exactly `string`,
+    /// meaning that parse, display and parse again is the identity for it.
+    ///
+    /// ```
+    /// use s3_path::S3PathBuf;
+    ///
+    /// assert!(S3PathBuf::round_trips("foo/bar"));
+    /// assert!(!S3PathBuf::round_trips("/foo//bar/"));
+    /// ```
+    #[must_use]
+    pub fn round_trips(string: impl AsRef<str>) -> bool {
+        Self::strict_parse(string).is_ok()
+    }
+
     /// Extend the last component of the path, if there is one, with [addition].
     ///
     /// If this path is empty, pushes [addition] the initial path component instead.
@@ -392,6 +1087,71 @@ impl S3PathBuf {
         Ok(self)
     }
 
+    /// Like [`push`](Self::push), for hot loops generating keys from trusted input: release
+    /// builds only reject empty components, `.`/`..` and components containing '/', skipping the
+    /// scan for other disallowed characters. Debug builds validate fully.
+    ///
+    /// The caller is responsible for `component` only containing ascii alphanumeric characters,
+    /// '-', '_' and '.'. Otherwise, the path may render to a key which `strict_parse` rejects, and
+    /// only [`try_to_std_path_buf`](S3Path::try_to_std_path_buf) safely converts it to a system
+    /// file path.
+    ///
+    /// ```
+    /// use s3_path::S3PathBuf;
+    ///
+    /// let mut path = S3PathBuf::try_from(["shards"]).unwrap();
+    /// for shard in 0..3 {
+    ///     path.push_trusted(format!("shard-{shard}")).unwrap();
+    /// }
+    /// assert_eq!(path.to_string(), "shards/shard-0/shard-1/shard-2");
+    /// assert!(path.push_trusted("a/b").is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when the given component
+    /// - is empty
+    /// - contains '/' (or, in debug builds, any other disallowed character)
+    /// - is equal to `.` or `..`
+    pub fn push_trusted(
+        &mut self,
+        component: impl Into<Cow<'static, str>>,
+    ) -> Result<&mut Self, InvalidS3PathComponent> {
+        let comp = component.into();
+        validation::validate_trusted_component(&comp)?;
+        self.components.push(comp);
+        Ok(self)
+    }
+
+    /// Formats `value` into a new component, which is validated and added to the path.
+    ///
+    /// The value is written into a single buffer, which is validated in place and then becomes the
+    /// component, so adding ids, numbers or dates allocates exactly once.
+    ///
+    /// ```
+    /// use s3_path::S3PathBuf;
+    ///
+    /// let mut path = S3PathBuf::try_from(["users"]).unwrap();
+    /// path.push_value(42).unwrap();
+    /// assert_eq!(path.to_string(), "users/42");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` under the same conditions as [`push`](Self::push).
+    pub fn push_value(
+        &mut self,
+        value: impl std::fmt::Display,
+    ) -> Result<&mut Self, InvalidS3PathComponent> {
+        use std::fmt::Write;
+
+        let mut component = String::new();
+        write!(component, "{value}").expect("writing to a String can not fail");
+        validation::validate_component(&component)?;
+        self.components.push(Cow::Owned(component));
+        Ok(self)
+    }
+
     /// Clones this path and pushes [component] onto it.
     ///
     /// Leaves this path untouched. Great for quickly creating multiple paths having the same root.
@@ -414,6 +1174,122 @@ impl S3PathBuf {
     pub fn pop(&mut self) -> Option<Cow<'static, str>> {
         self.components.pop()
     }
+
+    /// Shortens the component at `index` to at most `max_bytes` bytes, cutting on a char
+    /// boundary. Returns whether the component was shortened.
+    ///
+    /// With `hash_suffix`, a shortened component ends in '-' followed by 8 hex digits of a hash
+    /// of the original component (included in `max_bytes`), keeping components which only differ
+    /// after the cut distinct.
+    ///
+    /// ```
+    /// use s3_path::S3PathBuf;
+    ///
+    /// let mut path = S3PathBuf::try_from(["uploads", "a-very-long-file-name.txt"]).unwrap();
+    /// path.truncate_component_to_bytes(1, 6, false).unwrap();
+    /// assert_eq!(path.to_string(), "uploads/a-very");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err`, leaving this path untouched, when the shortened component would not be
+    /// valid, e.g. because it is empty or `..`, or when the hash suffix does not fit into
+    /// `max_bytes`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn truncate_component_to_bytes(
+        &mut self,
+        index: usize,
+        max_bytes: usize,
+        hash_suffix: bool,
+    ) -> Result<bool, InvalidS3PathComponent> {
+        let component = &self.components[index];
+        if component.len() <= max_bytes {
+            return Ok(false);
+        }
+        let suffix = if hash_suffix {
+            #[allow(clippy::cast_possible_truncation)]
+            let hash = hash::fnv1a_64(std::iter::once(component)) as u32;
+            format!("-{hash:08x}")
+        } else {
+            String::new()
+        };
+        if suffix.len() > max_bytes {
+            return Err(InvalidS3PathComponent {
+                component: component.to_string(),
+                reason: format!("Cannot fit hash suffix into {max_bytes} bytes"),
+            });
+        }
+        let mut end = max_bytes - suffix.len();
+        while !component.is_char_boundary(end) {
+            end -= 1;
+        }
+        let truncated = format!("{}{suffix}", &component[..end]);
+        validation::validate_component(&truncated)?;
+        self.components[index] = Cow::Owned(truncated);
+        Ok(true)
+    }
+}
+
+impl From<S3PathBuf> for Vec<String> {
+    fn from(path: S3PathBuf) -> Self {
+        path.components.into_iter().map(Cow::into_owned).collect()
+    }
+}
+
+/// Validates every string as one component. NO component is split at slashes ('/')!
+impl TryFrom<Vec<String>> for S3PathBuf {
+    type Error = InvalidS3PathComponent;
+
+    fn try_from(components: Vec<String>) -> Result<Self, Self::Error> {
+        // Resolves to the inherent `S3PathBuf::try_from`.
+        S3PathBuf::try_from(components)
+    }
+}
+
+/// Validates every string as one component. NO component is split at slashes ('/')!
+impl TryFrom<&[&str]> for S3PathBuf {
+    type Error = InvalidS3PathComponent;
+
+    fn try_from(components: &[&str]) -> Result<Self, Self::Error> {
+        S3PathBuf::try_from(components.iter().map(|c| (*c).to_owned()))
+    }
+}
+
+/// Parses like [`S3PathBuf::try_from_str`], splitting at slashes ('/').
+impl TryFrom<&str> for S3PathBuf {
+    type Error = InvalidS3PathComponent;
+
+    fn try_from(string: &str) -> Result<Self, Self::Error> {
+        S3PathBuf::try_from_str(string)
+    }
+}
+
+/// Parses like [`S3PathBuf::try_from_str`], splitting at slashes ('/').
+impl TryFrom<String> for S3PathBuf {
+    type Error = InvalidS3PathComponent;
+
+    fn try_from(string: String) -> Result<Self, Self::Error> {
+        S3PathBuf::try_from_str(string)
+    }
+}
+
+/// Parses like [`S3PathBuf::try_from_str`], so paths can be parsed using `str::parse`.
+///
+/// ```
+/// use s3_path::S3PathBuf;
+///
+/// let path = "foo/bar".parse::<S3PathBuf>().unwrap();
+/// assert_eq!(path, S3PathBuf::try_from(["foo", "bar"]).unwrap());
+/// ```
+impl std::str::FromStr for S3PathBuf {
+    type Err = InvalidS3PathComponent;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from_str(s)
+    }
 }
 
 #[cfg(test)]
@@ -431,6 +1307,7 @@ mod test {
     mod s3_path_buf {
         use crate::S3PathBuf;
         use assertr::prelude::*;
+        use std::borrow::Cow;
 
         #[test]
         fn new_is_initially_empty() {
@@ -464,12 +1341,67 @@ mod test {
             assert_that(path).has_display_value("foo/bar");
         }
 
+        #[test]
+        fn from_str_parses_like_try_from_str() {
+            let path = "/foo//bar/".parse::<S3PathBuf>().unwrap();
+            assert_that(path).has_display_value("foo/bar");
+            assert_that("foo/b@r".parse::<S3PathBuf>()).is_err();
+        }
+
+        #[test]
+        fn converts_through_try_into_bounds() {
+            fn convert<T: TryInto<S3PathBuf>>(value: T) -> Option<S3PathBuf> {
+                value.try_into().ok()
+            }
+            let expected = Some(S3PathBuf::try_from_str("foo/bar").unwrap());
+            assert_that(convert("foo/bar")).is_equal_to(expected.clone());
+            assert_that(convert("/foo//bar".to_owned())).is_equal_to(expected.clone());
+            assert_that(convert(["foo", "bar"].as_slice())).is_equal_to(expected.clone());
+            assert_that(convert(vec!["foo".to_owned(), "bar".to_owned()])).is_equal_to(expected);
+            // Slices and vectors hold components, which are not split at slashes.
+            assert_that(convert(["foo/bar"].as_slice())).is_none();
+        }
+
         #[test]
         fn try_from_str_ignores_repeated_slashes() {
             let path = S3PathBuf::try_from_str("foo/////bar").unwrap();
             assert_that(path).has_display_value("foo/bar");
         }
 
+        #[test]
+        fn try_from_str_any_separator_accepts_backslashes() {
+            let path = S3PathBuf::try_from_str_any_separator("\\foo\\\\bar/baz\\").unwrap();
+            assert_that(path).has_display_value("foo/bar/baz");
+            assert_that(S3PathBuf::try_from_str_any_separator("foo\\..")).is_err();
+        }
+
+        #[test]
+        fn strict_parse_parses_empty_string_as_empty_path() {
+            let path = S3PathBuf::strict_parse("").unwrap();
+            assert_that(path).has_display_value("");
+        }
+
+        #[test]
+        fn strict_parse_rejects_inputs_which_would_not_round_trip() {
+            assert_that(S3PathBuf::strict_parse("foo/bar"))
+                .is_ok()
+                .has_display_value("foo/bar");
+            assert_that(S3PathBuf::strict_parse("/")).is_err();
+            assert_that(S3PathBuf::strict_parse("/foo")).is_err();
+            assert_that(S3PathBuf::strict_parse("foo/")).is_err();
+            assert_that(S3PathBuf::strict_parse("foo//bar")).is_err();
+        }
+
+        #[test]
+        fn round_trips_holds_for_rendered_paths() {
+            let path = S3PathBuf::try_from(["foo", "bar.txt"]).unwrap();
+            assert_that(S3PathBuf::round_trips(path.to_string())).is_true();
+            assert_that(S3PathBuf::strict_parse(path.to_string()))
+                .is_ok()
+                .is_equal_to(path);
+            assert_that(S3PathBuf::round_trips("foo/../bar")).is_false();
+        }
+
         #[test]
         fn construct_using_try_from_given_str() {
             let path = S3PathBuf::try_from_str("foo/bar").unwrap();
@@ -494,6 +1426,19 @@ mod test {
             assert_that(path).has_display_value("foo/bar");
         }
 
+        #[test]
+        fn converts_to_and_from_string_vec() {
+            let path = S3PathBuf::try_from(["foo", "bar"]).unwrap();
+            let strings = Vec::<String>::from(path.clone());
+            assert_that(strings.clone()).contains_exactly(["foo", "bar"]);
+
+            let back: Result<S3PathBuf, _> = strings.try_into();
+            assert_that(back).is_ok().is_equal_to(path);
+
+            let invalid: Result<S3PathBuf, _> = vec![String::from("..")].try_into();
+            assert_that(invalid).is_err();
+        }
+
         #[test]
         fn reject_invalid_characters() {
             let mut path = S3PathBuf::new();
@@ -504,6 +1449,17 @@ mod test {
             assert_that(result).is_err();
         }
 
+        #[test]
+        fn push_trusted_always_rejects_separators_and_traversal() {
+            let mut path = S3PathBuf::new();
+            assert_that(path.push_trusted("foo").is_ok()).is_true();
+            assert_that(path.push_trusted("a/b").is_err()).is_true();
+            assert_that(path.push_trusted("..").is_err()).is_true();
+            assert_that(path.push_trusted("").is_err()).is_true();
+            // Only debug builds scan for other disallowed characters.
+            assert_that(path.push_trusted("b$r").is_err()).is_equal_to(cfg!(debug_assertions));
+        }
+
         #[test]
         fn extend_mutates_original() {
             let mut foo = S3PathBuf::try_from_str("foo").unwrap();
@@ -521,6 +1477,41 @@ mod test {
             assert_that(foo).has_display_value("foo/bar");
         }
 
+        #[test]
+        fn push_value_formats_and_validates_value() {
+            let mut foo = S3PathBuf::try_from_str("foo").unwrap();
+            foo.push_value(42).unwrap().push_value('x').unwrap();
+            assert_that(foo).has_display_value("foo/42/x");
+
+            assert_that(S3PathBuf::new().push_value("..").is_err()).is_true();
+        }
+
+        #[test]
+        fn truncate_component_to_bytes_shortens_long_components() {
+            let mut path = S3PathBuf::try_from(["foo", "abcdefghijklmnop"]).unwrap();
+            assert_that(path.truncate_component_to_bytes(0, 3, true))
+                .is_ok()
+                .is_false();
+            assert_that(path.truncate_component_to_bytes(1, 12, true))
+                .is_ok()
+                .is_true();
+            let truncated = path.last().unwrap().to_string();
+            assert_that(truncated.len()).is_equal_to(12);
+            assert_that(truncated.starts_with("abc-")).is_true();
+
+            let mut other = S3PathBuf::try_from(["foo", "abcdefghijklmnoq"]).unwrap();
+            other.truncate_component_to_bytes(1, 12, true).unwrap();
+            assert_that(other == path).is_false();
+        }
+
+        #[test]
+        fn truncate_component_to_bytes_rejects_invalid_results() {
+            let mut path = S3PathBuf::try_from(["..a", "abcdefghijk"]).unwrap();
+            assert_that(path.truncate_component_to_bytes(0, 2, false)).is_err();
+            assert_that(path.truncate_component_to_bytes(1, 8, true)).is_err();
+            assert_that(path).has_display_value("..a/abcdefghijk");
+        }
+
         #[test]
         fn join_creates_clone() {
             let foo = S3PathBuf::try_from_str("foo").unwrap();
@@ -564,6 +1555,14 @@ mod test {
             assert_that(path_buf.components()).contains_exactly(["foo", "bar"]);
         }
 
+        #[test] // Function `components_cow` inherited through deref to S3Path!
+        fn components_cow_retains_borrowed_and_owned_components() {
+            let path_buf = s3_path_buf!("foo", String::from("bar")).unwrap();
+            let cows = path_buf.components_cow().collect::<Vec<_>>();
+            assert_that(matches!(cows[0], Cow::Borrowed("foo"))).is_true();
+            assert_that(matches!(cows[1], Cow::Owned(_))).is_true();
+        }
+
         #[test] // Function `get` inherited through deref to S3Path!
         fn get_returns_component_at_index() {
             let path_buf = S3PathBuf::try_from(["foo", "bar"]).unwrap();
@@ -576,6 +1575,75 @@ mod test {
             assert_that(path_buf.last()).is_some().is_equal_to("bar");
         }
 
+        #[test] // Functions `first`, `root` and `depth` inherited through deref to S3Path!
+        fn first_root_and_depth_address_top_level() {
+            let path_buf = S3PathBuf::try_from(["foo", "bar"]).unwrap();
+            assert_that(path_buf.first()).is_some().is_equal_to("foo");
+            assert_that(path_buf.root()).is_some().is_equal_to("foo");
+            assert_that(path_buf.depth()).is_equal_to(2);
+            assert_that(S3PathBuf::new().root()).is_none();
+        }
+
+        #[test] // Function `starts_with` inherited through deref to S3Path!
+        fn starts_with_compares_whole_components() {
+            let path_buf = S3PathBuf::try_from(["foo", "bar"]).unwrap();
+            assert_that(path_buf.starts_with(&S3PathBuf::new())).is_true();
+            assert_that(path_buf.starts_with(&path_buf)).is_true();
+            assert_that(path_buf.starts_with(&S3PathBuf::try_from(["foo"]).unwrap())).is_true();
+            assert_that(path_buf.starts_with(&S3PathBuf::try_from(["fo"]).unwrap())).is_false();
+            assert_that(S3PathBuf::new().starts_with(&path_buf)).is_false();
+        }
+
+        #[test] // Function `bytes` inherited through deref to S3Path!
+        fn bytes_iterates_rendered_key() {
+            let path_buf = S3PathBuf::try_from(["foo", "b", "baz.txt"]).unwrap();
+            let bytes = path_buf.bytes().collect::<Vec<_>>();
+            assert_that(bytes).is_equal_to(path_buf.to_string().into_bytes());
+            assert_that(path_buf.bytes().len()).is_equal_to(13);
+
+            let mut bytes = path_buf.bytes();
+            bytes.nth(4);
+            assert_that(bytes.len()).is_equal_to(8);
+            assert_that(S3PathBuf::new().bytes().next()).is_none();
+            assert_that(S3PathBuf::new().bytes().len()).is_equal_to(0);
+        }
+
+        #[test] // Function `disambiguate` inherited through deref to S3Path!
+        fn disambiguate_numbers_last_component() {
+            let taken = ["a/b", "a/b-1", "a/.env", "a/x.tar.gz", "a/x.tar-1.gz"];
+            let free = |key: &str| {
+                let path = S3PathBuf::try_from_str(key).unwrap();
+                path.disambiguate(|candidate| taken.contains(&candidate.to_string().as_str()))
+                    .to_string()
+            };
+            assert_that(free("a/c")).is_equal_to("a/c".to_owned());
+            assert_that(free("a/b")).is_equal_to("a/b-2".to_owned());
+            assert_that(free("a/.env")).is_equal_to("a/.env-1".to_owned());
+            assert_that(free("a/x.tar.gz")).is_equal_to("a/x.tar-2.gz".to_owned());
+        }
+
+        #[test] // Function `encryption_context` inherited through deref to S3Path!
+        fn encryption_context_binds_prefix_of_given_depth() {
+            let path_buf = S3PathBuf::try_from(["foo", "bar", "baz"]).unwrap();
+            let context = path_buf.encryption_context(2);
+            assert_that(context.len()).is_equal_to(1);
+            assert_that(context["prefix"].clone()).is_equal_to("foo/bar".to_owned());
+            assert_that(path_buf.encryption_context(5)["prefix"].clone())
+                .is_equal_to("foo/bar/baz".to_owned());
+        }
+
+        #[test] // Function `nth_from_end` inherited through deref to S3Path!
+        fn nth_from_end_counts_from_last_component() {
+            let path_buf = S3PathBuf::try_from(["foo", "bar", "baz"]).unwrap();
+            assert_that(path_buf.nth_from_end(0))
+                .is_some()
+                .is_equal_to("baz");
+            assert_that(path_buf.nth_from_end(2))
+                .is_some()
+                .is_equal_to("foo");
+            assert_that(path_buf.nth_from_end(3)).is_none();
+        }
+
         #[test] // Function `parent` inherited through deref to S3Path!
         fn parent_returns_none_when_path_has_no_components() {
             let path_buf = S3PathBuf::new();
@@ -610,6 +1678,182 @@ mod test {
             assert_that(path_buf.to_std_path_buf().display()).has_display_value("foo/bar");
         }
 
+        #[test] // Function `try_to_std_path_buf` inherited through deref to S3Path!
+        fn try_to_std_path_buf_agrees_with_to_std_path_buf_for_valid_paths() {
+            let path_buf = S3PathBuf::try_from(["foo", "bar"]).unwrap();
+            assert_that(path_buf.try_to_std_path_buf())
+                .is_ok()
+                .is_equal_to(path_buf.to_std_path_buf());
+        }
+
+        #[test]
+        fn unsafe_std_path_lists_all_invalid_components() {
+            let err = crate::error::UnsafeStdPath {
+                components: vec![
+                    crate::error::InvalidS3PathComponent {
+                        component: "a/b".to_owned(),
+                        reason: "Character '/' is not allowed".to_owned(),
+                    },
+                    crate::error::InvalidS3PathComponent {
+                        component: "c d".to_owned(),
+                        reason: "Character ' ' is not allowed".to_owned(),
+                    },
+                ],
+            };
+            assert_that(err).has_display_value(
+                "Path contains unsafe components: 'a/b' (Character '/' is not allowed), \
+                 'c d' (Character ' ' is not allowed)",
+            );
+        }
+
+        #[test] // Function `fingerprint` inherited through deref to S3Path!
+        fn fingerprint_is_fnv1a_of_rendered_key() {
+            assert_that(S3PathBuf::new().fingerprint()).is_equal_to(0xcbf2_9ce4_8422_2325);
+            assert_that(S3PathBuf::try_from(["foo"]).unwrap().fingerprint())
+                .is_equal_to(0xdcb2_7518_fed9_d577);
+            assert_that(S3PathBuf::try_from(["foo", "bar"]).unwrap().fingerprint())
+                .is_equal_to(0x571d_17d6_ef2d_ef0d);
+        }
+
+        #[test] // Function `shard_for` inherited through deref to S3Path!
+        fn shard_for_is_stable() {
+            let path_buf = S3PathBuf::try_from(["foo", "bar"]).unwrap();
+            assert_that(path_buf.shard_for(1)).is_equal_to(0);
+            assert_that(path_buf.shard_for(10)).is_equal_to(4);
+            assert_that(path_buf.shard_for(1000)).is_equal_to(757);
+        }
+
+        #[test] // Function `to_metric_label` inherited through deref to S3Path!
+        fn to_metric_label_hashes_components_beyond_depth() {
+            let path_buf = S3PathBuf::try_from(["foo", "bar", "baz"]).unwrap();
+            assert_that(path_buf.to_metric_label(0)).is_equal_to("#77".to_string());
+            assert_that(path_buf.to_metric_label(2)).is_equal_to("foo/bar/#82".to_string());
+            assert_that(path_buf.to_metric_label(3)).is_equal_to("foo/bar/baz".to_string());
+            assert_that(path_buf.to_metric_label(4)).is_equal_to("foo/bar/baz".to_string());
+        }
+
+        #[test] // Function `diff_display` inherited through deref to S3Path!
+        fn diff_display_braces_differing_components() {
+            let diff = |old: &str, new: &str| {
+                let (old, new) = (S3PathBuf::try_from_str(old), S3PathBuf::try_from_str(new));
+                old.unwrap().diff_display(&new.unwrap())
+            };
+            assert_that(diff("a/b/c/d", "a/x/c/d")).is_equal_to("a/{b→x}/c/d".to_string());
+            assert_that(diff("a/b", "a/b/c")).is_equal_to("a/b/{→c}".to_string());
+            assert_that(diff("a/b", "c/d")).is_equal_to("{a/b→c/d}".to_string());
+            assert_that(diff("a/b", "b")).is_equal_to("{a→}/b".to_string());
+            assert_that(diff("a/a", "a")).is_equal_to("a/{a→}".to_string());
+            assert_that(diff("a/b", "a/b")).is_equal_to("a/b".to_string());
+        }
+
+        #[test]
+        #[should_panic = "n_shards must be greater than zero"]
+        fn shard_for_panics_on_zero_shards() {
+            let _ = S3PathBuf::try_from(["foo"]).unwrap().shard_for(0);
+        }
+
+        #[test]
+        fn orders_by_rendered_key() {
+            let mut paths = ["a/b", "a-b", "a", "a/b/c", "b"]
+                .map(|key| S3PathBuf::try_from_str(key).unwrap())
+                .to_vec();
+            paths.sort();
+            let keys = paths.iter().map(ToString::to_string).collect::<Vec<_>>();
+            assert_that(keys)
+                .is_equal_to(["a", "a-b", "a/b", "a/b/c", "b"].map(String::from).to_vec());
+        }
+
+        #[test]
+        fn orders_like_paginated_listings() {
+            let keys = ["a", "a-b", "a.b", "a/b", "a/b/c", "a0", "aB", "b"];
+            let mut paths = keys
+                .iter()
+                .rev()
+                .map(|key| S3PathBuf::try_from_str(key).unwrap())
+                .collect::<Vec<_>>();
+            paths.sort();
+
+            let mut request = crate::listing::ListRequest {
+                max_keys: 3,
+                ..crate::listing::ListRequest::default()
+            };
+            let mut listed = Vec::new();
+            loop {
+                let page = crate::listing::list_objects_v2(&keys, &request);
+                listed.extend(page.contents);
+                request.continuation_token = page.next_continuation_token;
+                if request.continuation_token.is_none() {
+                    break;
+                }
+            }
+            let sorted = paths.iter().map(ToString::to_string).collect::<Vec<_>>();
+            assert_that(sorted)
+                .is_equal_to(listed.into_iter().map(String::from).collect::<Vec<_>>());
+
+            let components = [Cow::Borrowed("a"), Cow::Borrowed("b")];
+            let borrowed = crate::S3Path::new(&components).unwrap();
+            assert_that(paths[2] < *borrowed).is_true();
+            assert_that(*borrowed < paths[4]).is_true();
+        }
+
+        #[test]
+        fn hashes_like_borrowed_path() {
+            use std::hash::BuildHasher;
+
+            let hasher = std::collections::hash_map::RandomState::new();
+            for key in ["", "a", "a/b", "ab"] {
+                let path = S3PathBuf::try_from_str(key).unwrap();
+                assert_that(hasher.hash_one(&path)).is_equal_to(hasher.hash_one(path.as_path()));
+            }
+            let split = S3PathBuf::try_from_str("a/b").unwrap();
+            let joined = S3PathBuf::try_from_str("ab").unwrap();
+            assert_that(hasher.hash_one(&split)).is_not_equal_to(hasher.hash_one(&joined));
+        }
+
+        #[test]
+        fn hashes_independent_of_component_ownership() {
+            let set = std::collections::HashSet::from([
+                s3_path_buf!("a", "b").unwrap(),
+                S3PathBuf::try_from_str("a/b").unwrap(),
+                S3PathBuf::try_from(vec!["a".to_owned(), "b".to_owned()]).unwrap(),
+            ]);
+            assert_that(set.len()).is_equal_to(1);
+        }
+
+        #[test]
+        fn looks_up_owned_keys_by_borrowed_path() {
+            let key = S3PathBuf::try_from_str("a/b").unwrap();
+            let mut map = hashbrown::HashMap::new();
+            map.insert(key.clone(), 42);
+
+            let components = [Cow::Borrowed("a"), Cow::Borrowed("b")];
+            let borrowed = crate::S3Path::new(&components).unwrap();
+            assert_that(map.get(borrowed)).is_some().is_equal_to(&42);
+        }
+
+        #[test]
+        fn looks_up_std_collections_by_short_lived_borrowed_path() {
+            let set = std::collections::HashSet::from([S3PathBuf::try_from_str("a/b").unwrap()]);
+            let map =
+                std::collections::BTreeMap::from([(S3PathBuf::try_from_str("a/b").unwrap(), 1)]);
+
+            let segment = String::from("b");
+            let components = [Cow::Borrowed("a"), Cow::Borrowed(segment.as_str())];
+            let borrowed = crate::S3Path::new(&components).unwrap();
+            assert_that(set.contains(borrowed)).is_true();
+            assert_that(map.get(borrowed)).is_some().is_equal_to(&1);
+        }
+
+        #[test]
+        fn converts_into_owned_through_cow() {
+            let components = [Cow::Borrowed("a"), Cow::Borrowed("b")];
+            let borrowed = crate::S3Path::new(&components).unwrap();
+            let mut cow = Cow::Borrowed(borrowed);
+            cow.to_mut().push("c").unwrap();
+            assert_that(matches!(cow, Cow::Owned(_))).is_true();
+            assert_that(cow.into_owned()).is_equal_to(S3PathBuf::try_from_str("a/b/c").unwrap());
+        }
+
         mod s3_path_buf_macro {
             use assertr::prelude::*;
             use std::borrow::Cow;
@@ -743,6 +1987,43 @@ mod test {
         }
     }
 
+    mod concat {
+        use crate::{concat, S3PathBuf};
+        use assertr::prelude::*;
+        use std::borrow::Cow;
+
+        #[test]
+        fn concat_joins_all_components_in_order() {
+            let a = S3PathBuf::try_from_str("a/b").unwrap();
+            let b = S3PathBuf::new();
+            let c = S3PathBuf::try_from_str("c").unwrap();
+            assert_that(concat([&a, &b, &c])).has_display_value("a/b/c");
+            assert_that(concat::<&S3PathBuf>([])).is_equal_to(S3PathBuf::new());
+        }
+
+        #[test]
+        fn s3_concat_mixes_paths_and_components() {
+            let root = S3PathBuf::try_from_str("root").unwrap();
+            let path = s3_concat!(
+                &root,
+                s3_path!("x", "y").unwrap(),
+                String::from("z"),
+                Cow::Borrowed("w"),
+            );
+            assert_that(path).is_ok().has_display_value("root/x/y/z/w");
+            assert_that(s3_concat!())
+                .is_ok()
+                .is_equal_to(S3PathBuf::new());
+        }
+
+        #[test]
+        fn s3_concat_rejects_invalid_components() {
+            let root = S3PathBuf::try_from_str("root").unwrap();
+            assert_that(s3_concat!(&root, "a/b")).is_err();
+            assert_that(s3_concat!(&root, "..", "ok")).is_err();
+        }
+    }
+
     mod take_any_path {
         use crate::{S3Path, S3PathBuf};
 
@@ -756,6 +2037,7 @@ mod test {
         }
 
         #[test]
+        #[allow(clippy::needless_borrows_for_generic_args)]
         fn takes_borrowed_s3_path_buf() {
             take_any_path(&S3PathBuf::new());
         }
@@ -0,0 +1,343 @@
+//! Simulation of S3 `ListObjectsV2` over an in-memory set of keys, for testing pagination logic
+//! deterministically and without a server.
+//!
+//! ```
+//! use s3_path::listing::{list_objects_v2, ListRequest};
+//!
+//! let keys = ["a/1", "a/2", "b", "c/1"];
+//! let mut request = ListRequest {
+//!     delimiter: Some("/".to_owned()),
+//!     max_keys: 2,
+//!     ..ListRequest::default()
+//! };
+//!
+//! let first = list_objects_v2(&keys, &request);
+//! assert_eq!(first.common_prefixes, ["a/"]);
+//! assert_eq!(first.contents, ["b"]);
+//! assert!(first.is_truncated());
+//!
+//! request.continuation_token = first.next_continuation_token;
+//! let second = list_objects_v2(&keys, &request);
+//! assert_eq!(second.common_prefixes, ["c/"]);
+//! assert!(!second.is_truncated());
+//! ```
+
+use crate::S3PathBuf;
+
+/// Parameters of a single `ListObjectsV2` request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListRequest {
+    /// Only list keys starting with this string. Unlike paths, prefixes need not end at a
+    /// component boundary, e.g. `logs/2024-` lists `logs/2024-01/a` and `logs/2024-02`.
+    pub prefix: String,
+
+    /// Group keys containing this string after the prefix into common prefixes, usually `/`.
+    /// Empty delimiters are ignored.
+    pub delimiter: Option<String>,
+
+    /// Maximum number of keys and common prefixes per page. Defaults to 1000, like in S3.
+    pub max_keys: usize,
+
+    /// Only list keys sorting after this key. Ignored when a continuation token is given.
+    pub start_after: Option<String>,
+
+    /// The `next_continuation_token` of the previous page.
+    pub continuation_token: Option<String>,
+}
+
+impl Default for ListRequest {
+    fn default() -> Self {
+        Self {
+            prefix: String::new(),
+            delimiter: None,
+            max_keys: 1000,
+            start_after: None,
+            continuation_token: None,
+        }
+    }
+}
+
+/// A single page of a listing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListPage<K = S3PathBuf> {
+    /// The listed keys, in the order in which S3 lists keys.
+    pub contents: Vec<K>,
+
+    /// Distinct prefixes up to and including the first delimiter after the request prefix,
+    /// e.g. `logs/`. Not valid paths, as they end with the delimiter.
+    pub common_prefixes: Vec<String>,
+
+    /// Opaque token requesting the next page, `None` on the last page.
+    pub next_continuation_token: Option<String>,
+}
+
+impl<K> Default for ListPage<K> {
+    fn default() -> Self {
+        Self {
+            contents: Vec::new(),
+            common_prefixes: Vec::new(),
+            next_continuation_token: None,
+        }
+    }
+}
+
+impl<K> ListPage<K> {
+    /// Returns true if more pages follow.
+    #[must_use]
+    pub fn is_truncated(&self) -> bool {
+        self.next_continuation_token.is_some()
+    }
+
+    /// Returns the number of keys and common prefixes on this page.
+    #[must_use]
+    pub fn key_count(&self) -> usize {
+        self.contents.len() + self.common_prefixes.len()
+    }
+}
+
+/// An entry of a listing page. Continuation tokens encode the last entry returned, so that
+/// the keys grouped below a returned common prefix are skipped on the next page.
+enum ListEntry {
+    Key(String),
+    CommonPrefix(String),
+}
+
+impl ListEntry {
+    fn token(&self) -> String {
+        match self {
+            Self::Key(key) => format!("k:{key}"),
+            Self::CommonPrefix(prefix) => format!("p:{prefix}"),
+        }
+    }
+
+    fn from_token(token: &str) -> Option<Self> {
+        if let Some(key) = token.strip_prefix("k:") {
+            Some(Self::Key(key.to_owned()))
+        } else {
+            token
+                .strip_prefix("p:")
+                .map(|prefix| Self::CommonPrefix(prefix.to_owned()))
+        }
+    }
+
+    /// Returns the smallest key not yet listed up to and including this entry, or `None` if no
+    /// key can follow.
+    fn next_key(&self) -> Option<String> {
+        match self {
+            Self::Key(last) => Some(format!("{last}\0")),
+            // Keys sort by their UTF-8 bytes, which is the order of their chars. All keys starting
+            // with the prefix sort before the prefix with its last char incremented.
+            Self::CommonPrefix(prefix) => {
+                let mut chars = prefix.chars();
+                let last = chars.next_back()?;
+                let next = (u32::from(last) + 1..=u32::from(char::MAX)).find_map(char::from_u32)?;
+                let mut key = chars.as_str().to_owned();
+                key.push(next);
+                Some(key)
+            }
+        }
+    }
+}
+
+/// Lists a single page of `keys` like `ListObjectsV2`: keys are filtered by prefix and ordered
+/// by their UTF-8 bytes, keys sharing a prefix up to the delimiter are grouped into a single
+/// common prefix, and each common prefix counts as one key towards `max_keys`.
+///
+/// `keys` must be sorted by their bytes (as `str` and `String` sort) and free of duplicates.
+///
+/// An unknown continuation token lists from the start, where S3 would reject the request. A
+/// `max_keys` of zero returns an empty, non-truncated page.
+///
+/// Takes `O(max_keys * log(keys.len()))` time, regardless of the position of the page.
+#[must_use]
+pub fn list_objects_v2<K: AsRef<str> + Clone>(keys: &[K], request: &ListRequest) -> ListPage<K> {
+    list_sorted(
+        |from| {
+            let index = keys.partition_point(|key| key.as_ref() < from);
+            keys.get(index).map(|key| (key.as_ref(), key))
+        },
+        request,
+    )
+}
+
+/// Lists a single page like [`list_objects_v2`], from any sorted collection of keys.
+/// `first_from` must return the first key greater than or equal to the given key, along with the
+/// item listed for it.
+pub(crate) fn list_sorted<'k, K: Clone + 'k>(
+    mut first_from: impl FnMut(&str) -> Option<(&'k str, &'k K)>,
+    request: &ListRequest,
+) -> ListPage<K> {
+    let mut page = ListPage::default();
+    if request.max_keys == 0 {
+        return page;
+    }
+    let after = match &request.continuation_token {
+        Some(token) => ListEntry::from_token(token),
+        None => request.start_after.clone().map(ListEntry::Key),
+    };
+    let mut from = request.prefix.clone();
+    if let Some(after) = after {
+        match after.next_key() {
+            Some(next) => from = from.max(next),
+            None => return page,
+        }
+    }
+    let delimiter = request.delimiter.as_deref().filter(|it| !it.is_empty());
+    let mut last: Option<ListEntry> = None;
+    while let Some((rendered, key)) = first_from(&from) {
+        if !rendered.starts_with(&request.prefix) {
+            break;
+        }
+        if page.key_count() == request.max_keys {
+            page.next_continuation_token = last.as_ref().map(ListEntry::token);
+            break;
+        }
+        let delimited = delimiter.and_then(|delimiter| {
+            rendered[request.prefix.len()..]
+                .find(delimiter)
+                .map(|at| &rendered[..request.prefix.len() + at + delimiter.len()])
+        });
+        let entry = match delimited {
+            Some(prefix) => {
+                page.common_prefixes.push(prefix.to_owned());
+                ListEntry::CommonPrefix(prefix.to_owned())
+            }
+            None => {
+                page.contents.push(key.clone());
+                ListEntry::Key(rendered.to_owned())
+            }
+        };
+        // Skips all keys grouped into a returned common prefix at once.
+        let next = entry.next_key();
+        last = Some(entry);
+        match next {
+            Some(next) => from = next,
+            None => break,
+        }
+    }
+    page
+}
+
+#[cfg(test)]
+mod test {
+    use crate::listing::{list_objects_v2, ListPage, ListRequest};
+    use assertr::prelude::*;
+
+    fn list_all<'k>(keys: &[&'k str], mut request: ListRequest) -> Vec<ListPage<&'k str>> {
+        let mut pages = Vec::new();
+        loop {
+            let page = list_objects_v2(keys, &request);
+            request.continuation_token = page.next_continuation_token.clone();
+            pages.push(page);
+            if request.continuation_token.is_none() {
+                return pages;
+            }
+        }
+    }
+
+    #[test]
+    fn paginates_keys() {
+        let keys = ["a", "a-b", "a/b", "b"];
+        let pages = list_all(
+            &keys,
+            ListRequest {
+                max_keys: 3,
+                ..ListRequest::default()
+            },
+        );
+        assert_that(pages.len()).is_equal_to(2);
+        assert_that(pages[0].is_truncated()).is_true();
+        let listed = pages
+            .into_iter()
+            .flat_map(|page| page.contents)
+            .collect::<Vec<_>>();
+        assert_that(listed).is_equal_to(keys.to_vec());
+
+        let exact = list_objects_v2(
+            &keys,
+            &ListRequest {
+                max_keys: 4,
+                ..ListRequest::default()
+            },
+        );
+        assert_that(exact.is_truncated()).is_false();
+    }
+
+    #[test]
+    fn groups_keys_by_delimiter_after_prefix() {
+        let keys = [
+            "logs/2024-01/a",
+            "logs/2024-01/b",
+            "logs/2024-02",
+            "logs/2025/x",
+            "other",
+        ];
+        let page = list_objects_v2(
+            &keys,
+            &ListRequest {
+                prefix: "logs/2024-".to_owned(),
+                delimiter: Some("/".to_owned()),
+                ..ListRequest::default()
+            },
+        );
+        assert_that(page).is_equal_to(ListPage {
+            contents: vec!["logs/2024-02"],
+            common_prefixes: vec!["logs/2024-01/".to_owned()],
+            next_continuation_token: None,
+        });
+    }
+
+    #[test]
+    fn continues_after_common_prefixes() {
+        let keys = ["a/1", "a/2", "a/3", "b", "c/1"];
+        let pages = list_all(
+            &keys,
+            ListRequest {
+                delimiter: Some("/".to_owned()),
+                max_keys: 1,
+                ..ListRequest::default()
+            },
+        );
+        let entries = pages
+            .iter()
+            .map(|page| (page.common_prefixes.clone(), page.contents.clone()))
+            .collect::<Vec<_>>();
+        assert_that(entries).is_equal_to(vec![
+            (vec!["a/".to_owned()], Vec::new()),
+            (Vec::new(), vec!["b"]),
+            (vec!["c/".to_owned()], Vec::new()),
+        ]);
+    }
+
+    #[test]
+    fn starts_after_key_unless_continuing() {
+        let keys = ["a/1", "a/2", "b", "c"];
+        let mut request = ListRequest {
+            delimiter: Some("/".to_owned()),
+            start_after: Some("a/1".to_owned()),
+            max_keys: 2,
+            ..ListRequest::default()
+        };
+        let first = list_objects_v2(&keys, &request);
+        assert_that(first.common_prefixes.clone()).is_equal_to(vec!["a/".to_owned()]);
+        assert_that(first.contents.clone()).is_equal_to(vec!["b"]);
+
+        request.continuation_token = first.next_continuation_token;
+        let second = list_objects_v2(&keys, &request);
+        assert_that(second.contents).is_equal_to(vec!["c"]);
+        assert_that(second.common_prefixes).is_empty();
+    }
+
+    #[test]
+    fn lists_nothing_for_zero_max_keys() {
+        let page = list_objects_v2(
+            &["a"],
+            &ListRequest {
+                max_keys: 0,
+                ..ListRequest::default()
+            },
+        );
+        assert_that(page.key_count()).is_equal_to(0);
+        assert_that(page.is_truncated()).is_false();
+    }
+}
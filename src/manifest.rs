@@ -0,0 +1,230 @@
+//! A listing of objects and their metadata, keyed by path.
+
+use crate::{S3Path, S3PathBuf};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::time::SystemTime;
+
+/// Metadata of a single object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ObjectMeta {
+    /// Size of the object in bytes.
+    pub size: u64,
+
+    /// Entity tag of the object, as reported by S3, e.g. an MD5 digest of its content.
+    pub etag: String,
+
+    /// Time of the last modification of the object.
+    pub last_modified: SystemTime,
+}
+
+/// A set of objects, mapping each path to the metadata of the object stored under it.
+///
+/// Iteration always yields entries in the order in which S3 lists keys.
+///
+/// With the `serde` feature, manifests are represented as a map from rendered keys to metadata.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Manifest {
+    entries: BTreeMap<S3PathBuf, ObjectMeta>,
+}
+
+/// The differences between two manifests, as computed by [`Manifest::diff`].
+///
+/// All paths are in the order in which S3 lists keys.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ManifestDiff {
+    /// Paths only present in the new manifest.
+    pub added: Vec<S3PathBuf>,
+
+    /// Paths only present in the old manifest.
+    pub removed: Vec<S3PathBuf>,
+
+    /// Paths present in both manifests, but with differing metadata.
+    pub changed: Vec<S3PathBuf>,
+}
+
+impl ManifestDiff {
+    /// Returns true if the compared manifests were equal.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl Manifest {
+    /// Creates an empty manifest.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of entries in this manifest.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if this manifest has no entries.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Inserts an entry, returning the metadata previously stored for `path`, if any.
+    pub fn insert(&mut self, path: S3PathBuf, meta: ObjectMeta) -> Option<ObjectMeta> {
+        self.entries.insert(path, meta)
+    }
+
+    /// Returns the metadata stored for `path`, if any.
+    #[must_use]
+    pub fn get(&self, path: &S3Path<'_>) -> Option<&ObjectMeta> {
+        self.entries.get(path)
+    }
+
+    /// Removes the entry for `path`, returning its metadata, if any.
+    pub fn remove(&mut self, path: &S3Path<'_>) -> Option<ObjectMeta> {
+        self.entries.remove(path)
+    }
+
+    /// Returns an iterator over all entries, in the order in which S3 lists keys.
+    pub fn iter(&self) -> impl Iterator<Item = (&S3PathBuf, &ObjectMeta)> {
+        self.entries.iter()
+    }
+
+    /// Computes which paths were added, removed or changed when going from this manifest to
+    /// `newer`.
+    ///
+    /// Runs in linear time, walking both manifests in order.
+    #[must_use]
+    pub fn diff(&self, newer: &Manifest) -> ManifestDiff {
+        let mut diff = ManifestDiff::default();
+        let mut old = self.entries.iter().peekable();
+        let mut new = newer.entries.iter().peekable();
+        loop {
+            match (old.peek(), new.peek()) {
+                (None, None) => break,
+                (Some(&(old_key, _)), None) => {
+                    diff.removed.push(old_key.clone());
+                    old.next();
+                }
+                (None, Some(&(new_key, _))) => {
+                    diff.added.push(new_key.clone());
+                    new.next();
+                }
+                (Some(&(old_key, old_meta)), Some(&(new_key, new_meta))) => {
+                    match old_key.cmp(new_key) {
+                        Ordering::Less => {
+                            diff.removed.push(old_key.clone());
+                            old.next();
+                        }
+                        Ordering::Greater => {
+                            diff.added.push(new_key.clone());
+                            new.next();
+                        }
+                        Ordering::Equal => {
+                            if old_meta != new_meta {
+                                diff.changed.push(old_key.clone());
+                            }
+                            old.next();
+                            new.next();
+                        }
+                    }
+                }
+            }
+        }
+        diff
+    }
+}
+
+impl FromIterator<(S3PathBuf, ObjectMeta)> for Manifest {
+    fn from_iter<T: IntoIterator<Item = (S3PathBuf, ObjectMeta)>>(iter: T) -> Self {
+        Self {
+            entries: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::manifest::{Manifest, ObjectMeta};
+    use crate::S3PathBuf;
+    use assertr::prelude::*;
+    use std::time::SystemTime;
+
+    fn meta(etag: &str) -> ObjectMeta {
+        ObjectMeta {
+            size: 42,
+            etag: etag.to_owned(),
+            last_modified: SystemTime::UNIX_EPOCH,
+        }
+    }
+
+    fn path(key: &str) -> S3PathBuf {
+        S3PathBuf::try_from_str(key).unwrap()
+    }
+
+    #[test]
+    fn iterates_in_s3_list_order() {
+        let manifest = Manifest::from_iter([
+            (path("foo/bar"), meta("a")),
+            (path("foo-bar"), meta("b")),
+            (path("foo"), meta("c")),
+        ]);
+        let keys = manifest
+            .iter()
+            .map(|(path, _)| path.to_string())
+            .collect::<Vec<_>>();
+        // '-' sorts before '/' in S3 listings.
+        assert_that(keys).contains_exactly(["foo", "foo-bar", "foo/bar"]);
+    }
+
+    #[test]
+    fn insert_get_and_remove() {
+        let mut manifest = Manifest::new();
+        assert_that(manifest.insert(path("foo/bar"), meta("a"))).is_none();
+        assert_that(manifest.insert(path("foo/bar"), meta("b")))
+            .is_some()
+            .is_equal_to(meta("a"));
+        assert_that(manifest.get(&path("foo/bar")))
+            .is_some()
+            .is_equal_to(&meta("b"));
+        assert_that(manifest.remove(&path("foo/bar"))).is_some();
+        assert_that(manifest.is_empty()).is_true();
+    }
+
+    #[test]
+    fn diff_reports_added_removed_and_changed_paths() {
+        let old = Manifest::from_iter([
+            (path("a"), meta("1")),
+            (path("b"), meta("1")),
+            (path("c"), meta("1")),
+        ]);
+        let new = Manifest::from_iter([
+            (path("b"), meta("2")),
+            (path("c"), meta("1")),
+            (path("d"), meta("1")),
+        ]);
+
+        let diff = old.diff(&new);
+
+        assert_that(diff.added).contains_exactly([path("d")]);
+        assert_that(diff.removed).contains_exactly([path("a")]);
+        assert_that(diff.changed).contains_exactly([path("b")]);
+        assert_that(new.diff(&new).is_empty()).is_true();
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serializes_with_serde() {
+        let manifest = Manifest::from_iter([(path("a/b"), meta("1")), (path("c"), meta("2"))]);
+        let json = serde_json::to_string(&manifest).unwrap();
+        assert_that(json.starts_with(r#"{"a/b":{"size":42,"etag":"1","#)).is_true();
+        assert_that(serde_json::from_str::<Manifest>(&json))
+            .is_ok()
+            .is_equal_to(manifest);
+    }
+}
@@ -0,0 +1,193 @@
+//! Merkle trees over manifests, for cheaply verifying that two key sets are equal.
+//!
+//! The tree mirrors the "directory" structure of the keys: each prefix is a node, hashing the
+//! names and hashes of its children, and the etag of the object stored under the prefix itself,
+//! if any. Two sides of a replication link compare root hashes first, and only descend into the
+//! prefixes whose hashes differ.
+//!
+//! Hashes are computed using SipHash-2-4 with a fixed key and are stable across releases,
+//! platforms and processes. They detect accidental divergence, not deliberate tampering.
+//!
+//! ```
+//! use s3_path::manifest::{Manifest, ObjectMeta};
+//! use s3_path::S3PathBuf;
+//! use std::time::SystemTime;
+//!
+//! let meta = |etag: &str| ObjectMeta {
+//!     size: 1,
+//!     etag: etag.to_string(),
+//!     last_modified: SystemTime::UNIX_EPOCH,
+//! };
+//! let path = |key: &str| S3PathBuf::try_from_str(key).unwrap();
+//!
+//! let ours = Manifest::from_iter([(path("a/x"), meta("1")), (path("b/y"), meta("1"))]);
+//! let theirs = Manifest::from_iter([(path("a/x"), meta("1")), (path("b/y"), meta("2"))]);
+//! assert_ne!(ours.merkle_root(), theirs.merkle_root());
+//!
+//! let (ours, theirs) = (ours.merkle_tree(), theirs.merkle_tree());
+//! assert_eq!(ours.hash(&path("a")), theirs.hash(&path("a")));
+//! assert_ne!(ours.hash(&path("b")), theirs.hash(&path("b")));
+//! ```
+
+use crate::hash;
+use crate::manifest::Manifest;
+use crate::S3Path;
+use std::collections::BTreeMap;
+
+/// Key of the SipHash function, fixed as part of the hash format.
+const KEY: &[u8; 16] = b"s3-path-merkle-1";
+
+/// A node of a [`MerkleTree`], representing a prefix.
+#[derive(Debug, Clone, Default)]
+struct Node {
+    hash: u64,
+    /// Etag of the object stored under this exact prefix, if any.
+    etag: Option<String>,
+    children: BTreeMap<String, Node>,
+}
+
+impl Node {
+    fn compute_hash(&mut self) -> u64 {
+        // Length prefixes keep the encoding unambiguous.
+        let mut data = Vec::new();
+        if let Some(etag) = &self.etag {
+            data.push(b'o');
+            data.extend_from_slice(&(etag.len() as u64).to_le_bytes());
+            data.extend_from_slice(etag.as_bytes());
+        }
+        for (name, child) in &mut self.children {
+            data.push(b'c');
+            data.extend_from_slice(&(name.len() as u64).to_le_bytes());
+            data.extend_from_slice(name.as_bytes());
+            data.extend_from_slice(&child.compute_hash().to_le_bytes());
+        }
+        self.hash = hash::siphash24(KEY, &data);
+        self.hash
+    }
+}
+
+/// Hashes of all prefixes of a [`Manifest`]. Created by [`Manifest::merkle_tree`].
+#[derive(Debug, Clone)]
+pub struct MerkleTree {
+    root: Node,
+}
+
+impl MerkleTree {
+    /// Returns the hash over all keys and etags.
+    #[must_use]
+    pub fn root(&self) -> u64 {
+        self.root.hash
+    }
+
+    fn node(&self, prefix: &S3Path<'_>) -> Option<&Node> {
+        prefix
+            .components()
+            .try_fold(&self.root, |node, component| node.children.get(component))
+    }
+
+    /// Returns the hash over all keys starting with `prefix`, or None if there are none.
+    #[must_use]
+    pub fn hash(&self, prefix: &S3Path<'_>) -> Option<u64> {
+        self.node(prefix).map(|node| node.hash)
+    }
+
+    /// Returns the names and hashes of all child prefixes of `prefix`, sorted by name.
+    #[must_use]
+    pub fn children(&self, prefix: &S3Path<'_>) -> Vec<(&str, u64)> {
+        self.node(prefix)
+            .map(|node| {
+                node.children
+                    .iter()
+                    .map(|(name, child)| (name.as_str(), child.hash))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl Manifest {
+    /// Builds the Merkle tree over the keys and etags of this manifest. Other metadata is not
+    /// hashed. See the [module documentation](crate::merkle).
+    #[must_use]
+    pub fn merkle_tree(&self) -> MerkleTree {
+        let mut root = Node::default();
+        for (path, meta) in self.iter() {
+            let node = path.components().fold(&mut root, |node, component| {
+                node.children.entry(component.to_string()).or_default()
+            });
+            node.etag = Some(meta.etag.clone());
+        }
+        root.compute_hash();
+        MerkleTree { root }
+    }
+
+    /// Returns the root hash of the [`merkle_tree`](Self::merkle_tree) of this manifest.
+    #[must_use]
+    pub fn merkle_root(&self) -> u64 {
+        self.merkle_tree().root()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::manifest::{Manifest, ObjectMeta};
+    use crate::S3PathBuf;
+    use assertr::prelude::*;
+    use std::time::{Duration, SystemTime};
+
+    fn meta(etag: &str) -> ObjectMeta {
+        ObjectMeta {
+            size: 42,
+            etag: etag.to_owned(),
+            last_modified: SystemTime::UNIX_EPOCH,
+        }
+    }
+
+    fn path(key: &str) -> S3PathBuf {
+        S3PathBuf::try_from_str(key).unwrap()
+    }
+
+    #[test]
+    fn root_depends_on_keys_and_etags_only() {
+        let manifest = Manifest::from_iter([(path("a/b"), meta("1")), (path("a-b"), meta("1"))]);
+        let mut touched = meta("1");
+        touched.size = 7;
+        touched.last_modified += Duration::from_secs(1);
+        let same = Manifest::from_iter([(path("a-b"), touched), (path("a/b"), meta("1"))]);
+        assert_that(manifest.merkle_root()).is_equal_to(same.merkle_root());
+
+        let renamed = Manifest::from_iter([(path("a/c"), meta("1")), (path("a-b"), meta("1"))]);
+        assert_that(manifest.merkle_root() == renamed.merkle_root()).is_false();
+    }
+
+    #[test]
+    fn distinguishes_objects_from_prefixes() {
+        let object = Manifest::from_iter([(path("a"), meta("1"))]);
+        let prefix = Manifest::from_iter([(path("a/b"), meta("1"))]);
+        let both = Manifest::from_iter([(path("a"), meta("1")), (path("a/b"), meta("1"))]);
+        assert_that(object.merkle_root() == prefix.merkle_root()).is_false();
+        assert_that(object.merkle_root() == both.merkle_root()).is_false();
+        assert_that(Manifest::new().merkle_root() == object.merkle_root()).is_false();
+    }
+
+    #[test]
+    fn exposes_subtree_hashes() {
+        let manifest = Manifest::from_iter([
+            (path("a/x"), meta("1")),
+            (path("a/y"), meta("2")),
+            (path("b"), meta("3")),
+        ]);
+        let tree = manifest.merkle_tree();
+        let children = tree.children(&path("a"));
+        assert_that(children.len()).is_equal_to(2);
+        assert_that(children[0].0).is_equal_to("x");
+        assert_that(tree.hash(&path("a/x")))
+            .is_some()
+            .is_equal_to(children[0].1);
+        assert_that(tree.hash(&path("c"))).is_none();
+        assert_that(tree.children(&path("c")).is_empty()).is_true();
+        assert_that(tree.hash(&S3PathBuf::new()))
+            .is_some()
+            .is_equal_to(tree.root());
+    }
+}
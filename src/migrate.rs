@@ -0,0 +1,364 @@
+//! Detection and migration of historical key layouts.
+//!
+//! Buckets written over a long time often contain keys of multiple layout versions. A
+//! [`LayoutRegistry`] knows all of them, detects the version of a key and rewrites keys from one
+//! version to another.
+//!
+//! Layouts are described by templates, written like [`KeyGrammar`]s, but with named placeholders:
+//! `{name}` matches any component, `{name:kind}` matches like the grammar placeholder `{kind}`,
+//! e.g. `{day:date}` or `{format:enum:csv|json}`. Components with equal names are carried over
+//! when migrating a key.
+//!
+//! ```
+//! use s3_path::migrate::LayoutRegistry;
+//! use s3_path::S3PathBuf;
+//!
+//! let mut registry = LayoutRegistry::new();
+//! registry.register(1, "{tenant}/{day:date}/{file}").unwrap();
+//! registry.register(2, "tenants/{tenant}/{day:date}/files/{file}").unwrap();
+//!
+//! let old = S3PathBuf::try_from_str("acme/2024-01-31/report.csv").unwrap();
+//! assert_eq!(registry.detect_version(&old), Some(1));
+//!
+//! let new = registry.upgrade(&old, 2).unwrap();
+//! assert_eq!(new.to_string(), "tenants/acme/2024-01-31/files/report.csv");
+//! ```
+
+use crate::error::{GrammarViolation, InvalidKeyGrammar, LayoutMigrationError};
+use crate::grammar::{ComponentSpec, KeyGrammar};
+use crate::{validation, S3Path, S3PathBuf};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+
+/// A single component of a [`LayoutTemplate`].
+#[derive(Debug, Clone)]
+struct Segment {
+    /// Name of the placeholder, `None` for literals.
+    name: Option<String>,
+    spec: ComponentSpec,
+}
+
+/// A key layout with named placeholders. See the [module documentation](crate::migrate).
+#[derive(Debug, Clone)]
+pub struct LayoutTemplate {
+    template: String,
+    segments: Vec<Segment>,
+}
+
+impl LayoutTemplate {
+    /// Parses a template written in the syntax described in the
+    /// [module documentation](crate::migrate).
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when the template is not a valid grammar once names are removed, contains an
+    /// empty component, or when a placeholder name is empty or used twice.
+    pub fn parse(template: &str) -> Result<Self, InvalidKeyGrammar> {
+        let invalid = |reason: String| InvalidKeyGrammar {
+            grammar: template.to_string(),
+            reason,
+        };
+        // Parses a grammar of exactly one component.
+        let parse_spec = |segment: &str| match KeyGrammar::parse(segment) {
+            Ok(grammar) => match grammar.specs() {
+                [spec] => Ok(spec.clone()),
+                _ => Err(invalid("Empty component".to_owned())),
+            },
+            Err(err) => Err(invalid(err.reason)),
+        };
+        let mut segments = Vec::new();
+        if !template.is_empty() {
+            for segment in template.split('/') {
+                if segment.is_empty() {
+                    return Err(invalid("Empty component".to_owned()));
+                }
+                let Some(placeholder) = segment
+                    .strip_prefix('{')
+                    .and_then(|it| it.strip_suffix('}'))
+                else {
+                    segments.push(Segment {
+                        name: None,
+                        spec: parse_spec(segment)?,
+                    });
+                    continue;
+                };
+                let (name, spec) = match placeholder.split_once(':') {
+                    Some((name, kind)) => (name, parse_spec(&format!("{{{kind}}}"))?),
+                    None => (placeholder, ComponentSpec::Any),
+                };
+                if name.is_empty() {
+                    return Err(invalid(format!("Unnamed placeholder '{segment}'")));
+                }
+                if segments
+                    .iter()
+                    .any(|it: &Segment| it.name.as_deref() == Some(name))
+                {
+                    return Err(invalid(format!("Duplicate placeholder name '{name}'")));
+                }
+                segments.push(Segment {
+                    name: Some(name.to_string()),
+                    spec,
+                });
+            }
+        }
+        Ok(Self {
+            template: template.to_string(),
+            segments,
+        })
+    }
+
+    /// Returns the values of all named placeholders if `path` matches this template.
+    fn captures<'p>(&self, path: &'p S3Path<'_>) -> Option<HashMap<&str, &'p str>> {
+        if path.len() != self.segments.len() {
+            return None;
+        }
+        let mut captures = HashMap::new();
+        for (i, segment) in self.segments.iter().enumerate() {
+            let component = path.0[i].as_ref();
+            if !segment.spec.matches(component) {
+                return None;
+            }
+            if let Some(name) = &segment.name {
+                captures.insert(name.as_str(), component);
+            }
+        }
+        Some(captures)
+    }
+
+    /// Returns the specifications of all components, ignoring placeholder names.
+    pub(crate) fn specs(&self) -> impl Iterator<Item = &ComponentSpec> {
+        self.segments.iter().map(|segment| &segment.spec)
+    }
+
+    /// Returns true if `path` matches this template.
+    #[must_use]
+    pub fn matches(&self, path: &S3Path<'_>) -> bool {
+        self.captures(path).is_some()
+    }
+
+    /// Renders this template into a path, taking the value of each named placeholder from
+    /// `values`.
+    ///
+    /// ```
+    /// use s3_path::migrate::LayoutTemplate;
+    ///
+    /// let template = LayoutTemplate::parse("exports/{tenant}/{day:date}").unwrap();
+    /// let path = template
+    ///     .render(|name| match name {
+    ///         "tenant" => Some("acme"),
+    ///         "day" => Some("2024-01-31"),
+    ///         _ => None,
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(path.to_string(), "exports/acme/2024-01-31");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when `values` has no value for a placeholder, or when a value is not a
+    /// valid component or does not match its placeholder.
+    pub fn render<'v>(
+        &self,
+        mut values: impl FnMut(&str) -> Option<&'v str>,
+    ) -> Result<S3PathBuf, GrammarViolation> {
+        let mut rendered = S3PathBuf::new();
+        for (index, segment) in self.segments.iter().enumerate() {
+            let component = match (&segment.name, &segment.spec) {
+                (Some(name), _) => values(name).ok_or_else(|| GrammarViolation {
+                    index,
+                    reason: format!("No value for placeholder '{name}'"),
+                })?,
+                (None, ComponentSpec::Literal(literal)) => literal.as_str(),
+                (None, _) => unreachable!("unnamed segments are literals"),
+            };
+            validation::validate_component(component).map_err(|err| GrammarViolation {
+                index,
+                reason: err.reason,
+            })?;
+            if !segment.spec.matches(component) {
+                return Err(GrammarViolation {
+                    index,
+                    reason: format!("Expected {}, got '{component}'", segment.spec.describe()),
+                });
+            }
+            rendered.components.push(Cow::Owned(component.to_string()));
+        }
+        Ok(rendered)
+    }
+}
+
+impl std::fmt::Display for LayoutTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.template)
+    }
+}
+
+/// All known layout versions of a bucket. See the [module documentation](crate::migrate).
+#[derive(Debug, Clone, Default)]
+pub struct LayoutRegistry {
+    layouts: BTreeMap<u32, LayoutTemplate>,
+}
+
+impl LayoutRegistry {
+    /// Creates an empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the layout of `version`, replacing any layout previously registered for it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when `template` is invalid, see [`LayoutTemplate::parse`].
+    pub fn register(
+        &mut self,
+        version: u32,
+        template: &str,
+    ) -> Result<&mut Self, InvalidKeyGrammar> {
+        self.layouts
+            .insert(version, LayoutTemplate::parse(template)?);
+        Ok(self)
+    }
+
+    /// Returns all registered layouts, ordered by version.
+    pub(crate) fn layouts(&self) -> impl Iterator<Item = (u32, &LayoutTemplate)> {
+        self.layouts
+            .iter()
+            .map(|(version, layout)| (*version, layout))
+    }
+
+    /// Returns the layout registered for `version`, if any.
+    #[must_use]
+    pub fn layout(&self, version: u32) -> Option<&LayoutTemplate> {
+        self.layouts.get(&version)
+    }
+
+    /// Returns the version of the layout `path` matches. If multiple layouts match, the highest
+    /// version is returned.
+    #[must_use]
+    pub fn detect_version(&self, path: &S3Path<'_>) -> Option<u32> {
+        self.layouts
+            .iter()
+            .rev()
+            .find(|(_, layout)| layout.matches(path))
+            .map(|(version, _)| *version)
+    }
+
+    /// Rewrites `path` from its detected layout into the layout of `to_version`, carrying over
+    /// the values of all named placeholders.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when
+    /// - no layout is registered for `to_version`
+    /// - `path` matches no registered layout
+    /// - the target layout has a placeholder not present in the detected layout
+    /// - a carried over value does not match its placeholder in the target layout
+    pub fn upgrade(
+        &self,
+        path: &S3Path<'_>,
+        to_version: u32,
+    ) -> Result<S3PathBuf, LayoutMigrationError> {
+        let error = |reason: String| LayoutMigrationError {
+            path: path.to_string(),
+            reason,
+        };
+        let target = self
+            .layouts
+            .get(&to_version)
+            .ok_or_else(|| error(format!("Unknown layout version {to_version}")))?;
+        let from_version = self
+            .detect_version(path)
+            .ok_or_else(|| error("Path matches no known layout".to_string()))?;
+        let captures = self.layouts[&from_version]
+            .captures(path)
+            .expect("detected layout matches");
+        target
+            .render(|name| captures.get(name).copied())
+            .map_err(|err| error(format!("Layout version {to_version}: {}", err.reason)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::migrate::{LayoutRegistry, LayoutTemplate};
+    use crate::S3PathBuf;
+    use assertr::prelude::*;
+
+    fn path(key: &str) -> S3PathBuf {
+        S3PathBuf::try_from_str(key).unwrap()
+    }
+
+    fn registry() -> LayoutRegistry {
+        let mut registry = LayoutRegistry::new();
+        registry
+            .register(1, "{tenant}/{file}")
+            .unwrap()
+            .register(2, "v2/{tenant}/{day:date}/{file}")
+            .unwrap()
+            .register(3, "v3/{tenant}/{year:year}/{file}")
+            .unwrap();
+        registry
+    }
+
+    #[test]
+    fn parse_rejects_invalid_templates() {
+        assert_that(LayoutTemplate::parse("{a}/{a}")).is_err();
+        assert_that(LayoutTemplate::parse("{:date}")).is_err();
+        assert_that(LayoutTemplate::parse("{a:unknown}")).is_err();
+        assert_that(LayoutTemplate::parse("a$/{b}")).is_err();
+        assert_that(LayoutTemplate::parse("a//b")).is_err();
+        assert_that(LayoutTemplate::parse("a/")).is_err();
+        assert_that(LayoutTemplate::parse("/{a}")).is_err();
+    }
+
+    #[test]
+    fn detects_versions() {
+        let registry = registry();
+        assert_that(registry.detect_version(&path("acme/a.csv")))
+            .is_some()
+            .is_equal_to(1);
+        assert_that(registry.detect_version(&path("v2/acme/2024-01-31/a.csv")))
+            .is_some()
+            .is_equal_to(2);
+        assert_that(registry.detect_version(&path("a/b/c"))).is_none();
+    }
+
+    #[test]
+    fn upgrade_carries_over_named_values() {
+        let registry = registry();
+        let downgraded = registry
+            .upgrade(&path("v2/acme/2024-01-31/a.csv"), 1)
+            .unwrap();
+        assert_that(downgraded).has_display_value("acme/a.csv");
+        assert_that(registry.upgrade(&path("acme/a.csv"), 1))
+            .is_ok()
+            .is_equal_to(path("acme/a.csv"));
+    }
+
+    #[test]
+    fn upgrade_fails_for_missing_or_mismatching_values() {
+        let registry = registry();
+        // Version 1 has no day.
+        assert_that(registry.upgrade(&path("acme/a.csv"), 2)).is_err();
+        assert_that(registry.upgrade(&path("acme/a.csv"), 4)).is_err();
+        assert_that(registry.upgrade(&path("a/b/c"), 1)).is_err();
+
+        let mut registry = registry;
+        registry.register(4, "{tenant:uuid}/{file}").unwrap();
+        assert_that(registry.upgrade(&path("v3/acme/2024/a.csv"), 4)).is_err();
+    }
+
+    #[test]
+    fn render_rejects_missing_and_invalid_values() {
+        let template = LayoutTemplate::parse("logs/{day:date}/{file}").unwrap();
+        let err = template.render(|_| None).unwrap_err();
+        assert_that(err.index).is_equal_to(1);
+        let err = template
+            .render(|name| Some(if name == "day" { "2024-01-31" } else { "a b" }))
+            .unwrap_err();
+        assert_that(err.index).is_equal_to(2);
+        assert_that(template.render(|_| Some("today"))).is_err();
+    }
+}
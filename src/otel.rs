@@ -0,0 +1,37 @@
+//! Attributes of the OpenTelemetry semantic conventions for S3, for consistent tracing
+//! instrumentation around S3 calls.
+//!
+//! Attributes are returned as name-value pairs, without depending on a particular
+//! `opentelemetry` release. Convert them using e.g. `KeyValue::new(name, value)`.
+//!
+//! ```
+//! use s3_path::otel::{key_attribute, s3_attributes, AWS_S3_KEY};
+//! use s3_path::S3PathBuf;
+//!
+//! let path = S3PathBuf::try_from_str("tenants/acme/data.csv").unwrap();
+//! assert_eq!(key_attribute(&path), (AWS_S3_KEY, "tenants/acme/data.csv".to_owned()));
+//!
+//! let [bucket, key] = s3_attributes("my-bucket", &path);
+//! assert_eq!(bucket, ("aws.s3.bucket", "my-bucket".to_owned()));
+//! assert_eq!(key.1, "tenants/acme/data.csv");
+//! ```
+
+use crate::S3Path;
+
+/// Name of the attribute holding the bucket name.
+pub const AWS_S3_BUCKET: &str = "aws.s3.bucket";
+
+/// Name of the attribute holding the key of the object an operation is performed on.
+pub const AWS_S3_KEY: &str = "aws.s3.key";
+
+/// Returns the `aws.s3.key` attribute for `path`, with the rendered key as its value.
+#[must_use]
+pub fn key_attribute(path: &S3Path<'_>) -> (&'static str, String) {
+    (AWS_S3_KEY, path.to_string())
+}
+
+/// Returns the `aws.s3.bucket` and `aws.s3.key` attributes for the object at `path` in `bucket`.
+#[must_use]
+pub fn s3_attributes(bucket: &str, path: &S3Path<'_>) -> [(&'static str, String); 2] {
+    [(AWS_S3_BUCKET, bucket.to_owned()), key_attribute(path)]
+}
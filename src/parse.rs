@@ -0,0 +1,391 @@
+//! Low-level, non-allocating scanning of user-supplied keys.
+//!
+//! Meant for editors, linters and forms wanting to point at exactly the part of a key which is
+//! invalid, rather than only learning *that* it is invalid.
+
+use crate::validation;
+use std::ops::Range;
+
+/// Iterates over the components of an input string, yielding each component together with its
+/// byte range in the input.
+///
+/// Unlike the constructors of [`S3PathBuf`](crate::S3PathBuf), nothing is validated or skipped:
+/// empty components, as produced by leading, trailing or repeated slashes, are yielded as well.
+/// The empty input yields no components.
+///
+/// ```
+/// use s3_path::parse::Scanner;
+///
+/// let spans = Scanner::new("foo//b r").collect::<Vec<_>>();
+/// assert_eq!(spans, [("foo", 0..3), ("", 4..4), ("b r", 5..8)]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Scanner<'a> {
+    input: &'a str,
+    /// Start of the next component, or `None` when done.
+    position: Option<usize>,
+}
+
+impl<'a> Scanner<'a> {
+    /// Creates a scanner over the components of `input`.
+    #[must_use]
+    pub fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            position: if input.is_empty() { None } else { Some(0) },
+        }
+    }
+
+    /// Returns the scanned input.
+    #[must_use]
+    pub fn input(&self) -> &'a str {
+        self.input
+    }
+}
+
+impl<'a> Iterator for Scanner<'a> {
+    type Item = (&'a str, Range<usize>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.position?;
+        let end = self.input[start..]
+            .find('/')
+            .map_or(self.input.len(), |offset| start + offset);
+        self.position = if end < self.input.len() {
+            Some(end + 1)
+        } else {
+            None
+        };
+        Some((&self.input[start..end], start..end))
+    }
+}
+
+impl std::iter::FusedIterator for Scanner<'_> {}
+
+/// The kind of a token found by [`classify`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    /// A '/' separating two components.
+    Separator,
+
+    /// A '/' which leads to an empty component: a leading, trailing or repeated slash.
+    RedundantSeparator,
+
+    /// A run of characters allowed in components.
+    Component,
+
+    /// A run of characters not allowed in components.
+    InvalidCharacters,
+
+    /// A `.` or `..` component.
+    Traversal,
+}
+
+/// Splits `input` into labeled, non-overlapping tokens covering all of it, in input order.
+///
+/// The input is valid, as defined by [`S3PathBuf::strict_parse`](crate::S3PathBuf::strict_parse),
+/// if all tokens are either a [`TokenKind::Separator`] or a [`TokenKind::Component`].
+///
+/// ```
+/// use s3_path::parse::{classify, TokenKind};
+///
+/// assert_eq!(
+///     classify("a//b c"),
+///     [
+///         (0..1, TokenKind::Component),
+///         (1..2, TokenKind::Separator),
+///         (2..3, TokenKind::RedundantSeparator),
+///         (3..4, TokenKind::Component),
+///         (4..5, TokenKind::InvalidCharacters),
+///         (5..6, TokenKind::Component),
+///     ]
+/// );
+/// ```
+#[must_use]
+pub fn classify(input: &str) -> Vec<(Range<usize>, TokenKind)> {
+    let mut tokens = Vec::new();
+    let mut previous_empty = false;
+    for (component, range) in Scanner::new(input) {
+        if range.start > 0 {
+            // For an empty component, flag the separator following it, or the one preceding it
+            // if it is the last component.
+            let at_end = range.end == input.len();
+            let kind = if previous_empty || (component.is_empty() && at_end) {
+                TokenKind::RedundantSeparator
+            } else {
+                TokenKind::Separator
+            };
+            tokens.push((range.start - 1..range.start, kind));
+        }
+        previous_empty = component.is_empty();
+
+        if component == "." || component == ".." {
+            tokens.push((range, TokenKind::Traversal));
+            continue;
+        }
+        for (offset, c) in component.char_indices() {
+            let kind = if validation::is_allowed_char(c) {
+                TokenKind::Component
+            } else {
+                TokenKind::InvalidCharacters
+            };
+            let start = range.start + offset;
+            let end = start + c.len_utf8();
+            match tokens.last_mut() {
+                Some((last, last_kind)) if *last_kind == kind && last.end == start => {
+                    last.end = end;
+                }
+                _ => tokens.push((start..end, kind)),
+            }
+        }
+    }
+    tokens
+}
+
+/// A suggested edit of an input string, as returned by [`suggest_fixes`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Fix {
+    /// Byte range in the input to replace.
+    pub range: Range<usize>,
+
+    /// The text to put in place of `range`.
+    pub replacement: String,
+
+    /// Human-readable description of this fix.
+    pub description: String,
+}
+
+/// Suggests fixes turning `input` into a valid key, as defined by
+/// [`S3PathBuf::strict_parse`](crate::S3PathBuf::strict_parse).
+///
+/// - Whitespace is replaced with '-', other invalid characters are removed.
+/// - Components which would still be invalid, like `..` or empty components caused by redundant
+///   slashes, are dropped together with one adjacent '/'.
+///
+/// The returned fixes are ordered and do not overlap. Applying all of them using
+/// [`apply_fixes`] always results in a valid key.
+///
+/// ```
+/// use s3_path::parse::{apply_fixes, suggest_fixes};
+///
+/// let input = "/my files//../report$.pdf";
+/// let fixes = suggest_fixes(input);
+/// assert_eq!(apply_fixes(input, &fixes), "my-files/report.pdf");
+/// ```
+#[must_use]
+pub fn suggest_fixes(input: &str) -> Vec<Fix> {
+    let mut fixes = Vec::new();
+    // Index of the fix dropping the first of the directly preceding dropped components, if any.
+    let mut dropped_run = None;
+    let components = Scanner::new(input).collect::<Vec<_>>();
+    for (i, (component, range)) in components.iter().enumerate() {
+        let (inner_fixes, fixed) = fix_characters(component, range.start);
+        if !fixed.is_empty() && fixed != "." && fixed != ".." {
+            dropped_run = None;
+            fixes.extend(inner_fixes);
+            continue;
+        }
+
+        let description = if component.is_empty() {
+            "Remove redundant '/'".to_owned()
+        } else {
+            format!("Drop invalid component '{component}'")
+        };
+        let range = if i + 1 < components.len() {
+            dropped_run.get_or_insert(fixes.len());
+            range.start..range.end + 1
+        } else if let Some(first) = dropped_run {
+            // The separators following all dropped components were removed, so the one preceding
+            // the first of them remains and must be removed instead.
+            let first = &mut fixes[first];
+            first.range.start = first.range.start.saturating_sub(1);
+            range.clone()
+        } else if i > 0 {
+            range.start - 1..range.end
+        } else {
+            range.clone()
+        };
+        if !range.is_empty() {
+            fixes.push(Fix {
+                range,
+                replacement: String::new(),
+                description,
+            });
+        }
+    }
+    fixes
+}
+
+/// Returns fixes for all runs of invalid characters in `component`, which starts at byte `offset`
+/// of the input, together with the fixed component.
+fn fix_characters(component: &str, offset: usize) -> (Vec<Fix>, String) {
+    let mut fixes = Vec::<Fix>::new();
+    let mut fixed = String::with_capacity(component.len());
+    for (i, c) in component.char_indices() {
+        if validation::is_allowed_char(c) {
+            fixed.push(c);
+            continue;
+        }
+        let start = offset + i;
+        let end = start + c.len_utf8();
+        let fix = match fixes.last_mut() {
+            Some(fix) if fix.range.end == start => {
+                fix.range.end = end;
+                fix
+            }
+            _ => {
+                fixes.push(Fix {
+                    range: start..end,
+                    replacement: String::new(),
+                    description: String::new(),
+                });
+                fixes.last_mut().expect("just pushed")
+            }
+        };
+        if c.is_whitespace() && fix.replacement.is_empty() {
+            fix.replacement.push('-');
+            fixed.push('-');
+        }
+    }
+    for fix in &mut fixes {
+        let original = &component[fix.range.start - offset..fix.range.end - offset];
+        fix.description = if fix.replacement.is_empty() {
+            format!("Remove invalid characters '{original}'")
+        } else {
+            format!("Replace '{original}' with '{}'", fix.replacement)
+        };
+    }
+    (fixes, fixed)
+}
+
+/// Applies `fixes`, which must be ordered and must not overlap, to `input`.
+///
+/// # Panics
+///
+/// Panics if any fix range is out of bounds of `input`, not on a char boundary, or overlaps a
+/// previous fix.
+#[must_use]
+pub fn apply_fixes(input: &str, fixes: &[Fix]) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut position = 0;
+    for fix in fixes {
+        assert!(
+            fix.range.start >= position,
+            "fixes must be ordered and not overlap"
+        );
+        output.push_str(&input[position..fix.range.start]);
+        output.push_str(&fix.replacement);
+        position = fix.range.end;
+    }
+    output.push_str(&input[position..]);
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parse::{apply_fixes, classify, suggest_fixes, Scanner, TokenKind};
+    use crate::S3PathBuf;
+    use assertr::prelude::*;
+
+    #[test]
+    fn yields_nothing_for_empty_input() {
+        assert_that(Scanner::new("").next()).is_none();
+    }
+
+    #[test]
+    fn yields_empty_components_at_the_edges() {
+        let spans = Scanner::new("/a/").collect::<Vec<_>>();
+        assert_that(spans).contains_exactly([("", 0..0), ("a", 1..2), ("", 3..3)]);
+    }
+
+    #[test]
+    fn ranges_index_into_input() {
+        let input = "fö/bär/baz";
+        for (component, range) in Scanner::new(input) {
+            assert_that(&input[range]).is_equal_to(component);
+        }
+        assert_that(Scanner::new(input).count()).is_equal_to(3);
+    }
+
+    #[test]
+    fn classify_flags_one_separator_per_empty_component() {
+        let kinds = |input| {
+            classify(input)
+                .into_iter()
+                .map(|(_, kind)| kind)
+                .collect::<Vec<_>>()
+        };
+        assert_that(kinds("/a/")).is_equal_to(vec![
+            TokenKind::RedundantSeparator,
+            TokenKind::Component,
+            TokenKind::RedundantSeparator,
+        ]);
+        assert_that(kinds("//")).is_equal_to(vec![TokenKind::RedundantSeparator; 2]);
+    }
+
+    #[test]
+    fn classify_merges_runs_and_flags_traversals() {
+        assert_that(classify("ä€b/..")).is_equal_to(vec![
+            (0..5, TokenKind::InvalidCharacters),
+            (5..6, TokenKind::Component),
+            (6..7, TokenKind::Separator),
+            (7..9, TokenKind::Traversal),
+        ]);
+    }
+
+    #[test]
+    fn classify_agrees_with_strict_parse() {
+        for input in [
+            "", "a", "a/b", "/a", "a/", "a//b", "a/$", "a/.", "a/...", "a b",
+        ] {
+            let valid = classify(input)
+                .iter()
+                .all(|(_, kind)| matches!(kind, TokenKind::Separator | TokenKind::Component));
+            assert_that(valid).is_equal_to(S3PathBuf::strict_parse(input).is_ok());
+        }
+    }
+
+    #[test]
+    fn suggest_fixes_describes_each_fix() {
+        let fixes = suggest_fixes("a b$/..");
+        let descriptions = fixes
+            .iter()
+            .map(|fix| fix.description.as_str())
+            .collect::<Vec<_>>();
+        assert_that(descriptions).contains_exactly([
+            "Replace ' ' with '-'",
+            "Remove invalid characters '$'",
+            "Drop invalid component '..'",
+        ]);
+        assert_that(apply_fixes("a b$/..", &fixes)).is_equal_to("a-b".to_owned());
+    }
+
+    #[test]
+    fn applying_all_fixes_yields_valid_keys() {
+        for input in [
+            "",
+            "/",
+            "//",
+            "a",
+            "/a/",
+            "a//b",
+            "../..",
+            "a/../b",
+            "$/a",
+            "a/$",
+            ".$",
+            "x/ä/. /y",
+            "a//",
+            "a///",
+            "x/../",
+            "a/../..",
+            "a/./.",
+            "a/$/$",
+            "//a//",
+        ] {
+            let fixed = apply_fixes(input, &suggest_fixes(input));
+            assert_that(S3PathBuf::strict_parse(&fixed).is_ok()).is_true();
+        }
+        assert_that(suggest_fixes("a/b.txt")).is_empty();
+    }
+}
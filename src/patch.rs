@@ -0,0 +1,206 @@
+//! Incremental updates of manifests.
+//!
+//! A [`Patch`] describes the changes between two versions of a [`Manifest`], allowing agents to
+//! ship incremental inventory updates instead of full key lists. With the `serde` feature,
+//! patches can be serialized.
+//!
+//! ```
+//! use s3_path::manifest::{Manifest, ObjectMeta};
+//! use s3_path::patch::Patch;
+//! use s3_path::S3PathBuf;
+//! use std::time::SystemTime;
+//!
+//! let meta = |etag: &str| ObjectMeta {
+//!     size: 1,
+//!     etag: etag.to_string(),
+//!     last_modified: SystemTime::UNIX_EPOCH,
+//! };
+//! let path = |key: &str| S3PathBuf::try_from_str(key).unwrap();
+//!
+//! let old = Manifest::from_iter([(path("a"), meta("1")), (path("b"), meta("1"))]);
+//! let new = Manifest::from_iter([(path("b"), meta("2")), (path("c"), meta("1"))]);
+//!
+//! let patch = Patch::between(&old, &new);
+//! let mut replica = old.clone();
+//! replica.apply(&patch).unwrap();
+//! assert_eq!(replica, new);
+//! ```
+
+use crate::error::PatchConflict;
+use crate::manifest::{Manifest, ObjectMeta};
+use crate::S3PathBuf;
+use std::collections::HashSet;
+
+/// Changes to apply to a [`Manifest`] using [`Manifest::apply`].
+///
+/// Removed paths and the sources of renames refer to the manifest before applying the patch.
+/// Renames are applied first, then removals, then additions.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Patch {
+    /// Entries to insert, replacing existing entries of equal paths.
+    pub added: Vec<(S3PathBuf, ObjectMeta)>,
+
+    /// Paths of entries to remove.
+    pub removed: Vec<S3PathBuf>,
+
+    /// Pairs of `(from, to)` paths, moving the metadata of `from` to `to`.
+    pub renamed: Vec<(S3PathBuf, S3PathBuf)>,
+}
+
+impl Patch {
+    /// Creates the patch turning `old` into `new`, consisting of additions and removals only.
+    #[must_use]
+    pub fn between(old: &Manifest, new: &Manifest) -> Self {
+        let diff = old.diff(new);
+        let added = diff
+            .added
+            .into_iter()
+            .chain(diff.changed)
+            .map(|path| {
+                let meta = new.get(&path).expect("diffed path exists").clone();
+                (path, meta)
+            })
+            .collect();
+        Self {
+            added,
+            removed: diff.removed,
+            renamed: Vec::new(),
+        }
+    }
+
+    /// Returns true if this patch changes nothing.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.renamed.is_empty()
+    }
+}
+
+impl Manifest {
+    /// Applies `patch` to this manifest.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err`, leaving this manifest untouched, when a removed path or the source of a
+    /// rename does not exist, or when a path is removed or renamed more than once.
+    pub fn apply(&mut self, patch: &Patch) -> Result<(), PatchConflict> {
+        let sources = patch
+            .renamed
+            .iter()
+            .map(|(from, _)| from)
+            .chain(&patch.removed);
+        let mut seen = HashSet::new();
+        for path in sources {
+            let conflict = |reason: &str| PatchConflict {
+                path: path.to_string(),
+                reason: reason.to_string(),
+            };
+            if self.get(path).is_none() {
+                return Err(conflict("Path does not exist"));
+            }
+            if !seen.insert(path) {
+                return Err(conflict("Path is removed or renamed more than once"));
+            }
+        }
+
+        let moved = patch
+            .renamed
+            .iter()
+            .map(|(from, to)| (to.clone(), self.remove(from).expect("checked to exist")))
+            .collect::<Vec<_>>();
+        for path in &patch.removed {
+            self.remove(path);
+        }
+        for (path, meta) in moved.into_iter().chain(patch.added.iter().cloned()) {
+            self.insert(path, meta);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::manifest::{Manifest, ObjectMeta};
+    use crate::patch::Patch;
+    use crate::S3PathBuf;
+    use assertr::prelude::*;
+    use std::time::SystemTime;
+
+    fn meta(etag: &str) -> ObjectMeta {
+        ObjectMeta {
+            size: 42,
+            etag: etag.to_owned(),
+            last_modified: SystemTime::UNIX_EPOCH,
+        }
+    }
+
+    fn path(key: &str) -> S3PathBuf {
+        S3PathBuf::try_from_str(key).unwrap()
+    }
+
+    #[test]
+    fn between_and_apply_round_trip() {
+        let old = Manifest::from_iter([(path("a"), meta("1")), (path("b"), meta("1"))]);
+        let new = Manifest::from_iter([(path("b"), meta("2")), (path("c/d"), meta("3"))]);
+        let patch = Patch::between(&old, &new);
+        assert_that(patch.removed.clone()).is_equal_to(vec![path("a")]);
+        assert_that(patch.added.len()).is_equal_to(2);
+
+        let mut manifest = old.clone();
+        manifest.apply(&patch).unwrap();
+        assert_that(manifest).is_equal_to(new.clone());
+        assert_that(Patch::between(&new, &new).is_empty()).is_true();
+    }
+
+    #[test]
+    fn renames_move_metadata() {
+        let mut manifest = Manifest::from_iter([(path("a"), meta("1")), (path("b"), meta("2"))]);
+        let patch = Patch {
+            renamed: vec![(path("a"), path("b")), (path("b"), path("a"))],
+            ..Patch::default()
+        };
+        manifest.apply(&patch).unwrap();
+        assert_that(manifest.get(&path("a")))
+            .is_some()
+            .is_equal_to(&meta("2"));
+        assert_that(manifest.get(&path("b")))
+            .is_some()
+            .is_equal_to(&meta("1"));
+    }
+
+    #[test]
+    fn conflicting_patches_leave_manifest_untouched() {
+        let original = Manifest::from_iter([(path("a"), meta("1"))]);
+        let mut manifest = original.clone();
+
+        let missing = Patch {
+            added: vec![(path("x"), meta("1"))],
+            removed: vec![path("b")],
+            ..Patch::default()
+        };
+        assert_that(manifest.apply(&missing)).is_err();
+
+        let twice = Patch {
+            removed: vec![path("a")],
+            renamed: vec![(path("a"), path("c"))],
+            ..Patch::default()
+        };
+        assert_that(manifest.apply(&twice)).is_err();
+        assert_that(manifest).is_equal_to(original);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serializes_with_serde() {
+        let patch = Patch {
+            added: vec![(path("a/b"), meta("1"))],
+            removed: vec![path("c")],
+            renamed: vec![(path("d"), path("e/f"))],
+        };
+        let json = serde_json::to_string(&patch).unwrap();
+        assert_that(json.contains("\"renamed\":[[\"d\",\"e/f\"]]")).is_true();
+        assert_that(serde_json::from_str::<Patch>(&json))
+            .is_ok()
+            .is_equal_to(patch);
+    }
+}
@@ -0,0 +1,248 @@
+//! Allow- and denylisting of keys.
+//!
+//! An [`AccessPolicy`] combines allowed prefixes, denied prefixes and glob exceptions from the
+//! denied prefixes. Paths are checked in a fixed order:
+//!
+//! 1. A path below a denied prefix is denied, unless it matches an exception glob.
+//! 2. A path below an allowed prefix is allowed.
+//! 3. Any other path is denied.
+//!
+//! Prefixes are matched component-wise: `tenants/acme` covers `tenants/acme/data.csv`, but not
+//! `tenants/acme2/data.csv`.
+//!
+//! Policies can be written in a line-based configuration format. Each line holds a rule keyword
+//! followed by a prefix, optionally ending in '/', or a glob. Blank lines and lines starting with
+//! '#' are ignored.
+//!
+//! ```
+//! use s3_path::policy::{AccessPolicy, Decision};
+//! use s3_path::S3PathBuf;
+//!
+//! let policy = AccessPolicy::parse(
+//!     "# Tenant data, without internals.
+//!      allow tenants
+//!      deny tenants/acme/internal
+//!      except tenants/acme/internal/**/*.pub",
+//! )
+//! .unwrap();
+//!
+//! let check = |key: &str| policy.check(&S3PathBuf::try_from_str(key).unwrap());
+//! assert_eq!(check("tenants/acme/data.csv"), Decision::Allow);
+//! assert_eq!(check("tenants/acme/internal/keys"), Decision::ExplicitDeny);
+//! assert_eq!(check("tenants/acme/internal/2024/keys.pub"), Decision::Allow);
+//! assert_eq!(check("backups/all.tar"), Decision::ImplicitDeny);
+//! ```
+
+use crate::error::InvalidAccessPolicy;
+use crate::glob::Glob;
+use crate::{S3Path, S3PathBuf};
+use std::fmt::Formatter;
+
+/// The outcome of [`AccessPolicy::check`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Decision {
+    /// The path is below an allowed prefix and not denied.
+    Allow,
+
+    /// The path is below a denied prefix and matches no exception.
+    ExplicitDeny,
+
+    /// The path is below no allowed prefix.
+    ImplicitDeny,
+}
+
+impl Decision {
+    /// Returns true for [`Decision::Allow`].
+    #[must_use]
+    pub fn is_allowed(self) -> bool {
+        self == Decision::Allow
+    }
+}
+
+/// Allowed prefixes, denied prefixes and exceptions. See the
+/// [module documentation](crate::policy).
+///
+/// With the `serde` feature, policies are represented as a map from the rule keywords to lists of
+/// prefixes or globs, e.g. `{"allow": ["tenants/"], "except": ["**/*.pub"]}`. Missing rules are
+/// empty.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct AccessPolicy {
+    #[cfg_attr(feature = "serde", serde(rename = "allow"))]
+    allowed: Vec<S3PathBuf>,
+    #[cfg_attr(feature = "serde", serde(rename = "deny"))]
+    denied: Vec<S3PathBuf>,
+    #[cfg_attr(feature = "serde", serde(rename = "except"))]
+    exceptions: Vec<Glob>,
+}
+
+impl AccessPolicy {
+    /// Creates a policy denying everything.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allows all paths below `prefix`. The empty path allows all paths.
+    pub fn allow(&mut self, prefix: S3PathBuf) -> &mut Self {
+        self.allowed.push(prefix);
+        self
+    }
+
+    /// Denies all paths below `prefix`, even if they are below an allowed prefix.
+    pub fn deny(&mut self, prefix: S3PathBuf) -> &mut Self {
+        self.denied.push(prefix);
+        self
+    }
+
+    /// Excepts all paths matching `glob` from being denied.
+    pub fn except(&mut self, glob: Glob) -> &mut Self {
+        self.exceptions.push(glob);
+        self
+    }
+
+    /// Parses a policy from the configuration format described in the
+    /// [module documentation](crate::policy). Rule keywords are `allow`, `deny` and `except`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when a line has an unknown keyword, no argument or an invalid prefix or glob.
+    pub fn parse(config: &str) -> Result<Self, InvalidAccessPolicy> {
+        let mut policy = Self::new();
+        for (index, line) in config.lines().enumerate() {
+            let invalid = |reason: String| InvalidAccessPolicy {
+                line: index + 1,
+                reason,
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (keyword, argument) = line
+                .split_once(char::is_whitespace)
+                .map_or((line, ""), |(keyword, argument)| (keyword, argument.trim()));
+            let prefix = || {
+                // Prefixes are commonly written with a trailing slash, marking a "directory".
+                S3PathBuf::strict_parse(argument.strip_suffix('/').unwrap_or(argument))
+                    .map_err(|err| invalid(format!("Invalid prefix '{argument}': {err}")))
+            };
+            match keyword {
+                "allow" => policy.allow(prefix()?),
+                "deny" => policy.deny(prefix()?),
+                "except" => {
+                    policy.except(Glob::parse(argument).map_err(|err| invalid(err.to_string()))?)
+                }
+                _ => return Err(invalid(format!("Unknown rule '{keyword}'"))),
+            };
+        }
+        Ok(policy)
+    }
+
+    /// Decides whether `path` is allowed by this policy.
+    #[must_use]
+    pub fn check(&self, path: &S3Path<'_>) -> Decision {
+        let below = |prefix: &S3PathBuf| path.starts_with(prefix);
+        if self.denied.iter().any(below) && !self.exceptions.iter().any(|it| it.matches(path)) {
+            Decision::ExplicitDeny
+        } else if self.allowed.iter().any(below) {
+            Decision::Allow
+        } else {
+            Decision::ImplicitDeny
+        }
+    }
+}
+
+/// Renders the policy in the configuration format accepted by [`AccessPolicy::parse`].
+impl std::fmt::Display for AccessPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for prefix in &self.allowed {
+            writeln!(f, "allow {prefix}")?;
+        }
+        for prefix in &self.denied {
+            writeln!(f, "deny {prefix}")?;
+        }
+        for glob in &self.exceptions {
+            writeln!(f, "except {glob}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::policy::{AccessPolicy, Decision};
+    use crate::S3PathBuf;
+    use assertr::prelude::*;
+
+    fn check(policy: &AccessPolicy, key: &str) -> Decision {
+        policy.check(&S3PathBuf::try_from_str(key).unwrap())
+    }
+
+    #[test]
+    fn prefixes_match_whole_components() {
+        let policy = AccessPolicy::parse("allow tenants/acme/").unwrap();
+        assert_that(check(&policy, "tenants/acme")).is_equal_to(Decision::Allow);
+        assert_that(check(&policy, "tenants/acme/a")).is_equal_to(Decision::Allow);
+        assert_that(check(&policy, "tenants/acme2/a")).is_equal_to(Decision::ImplicitDeny);
+        assert_that(check(&policy, "tenants")).is_equal_to(Decision::ImplicitDeny);
+    }
+
+    #[test]
+    fn deny_wins_over_allow_regardless_of_order() {
+        let policy = AccessPolicy::parse("deny a/b\nallow a").unwrap();
+        assert_that(check(&policy, "a/b/c")).is_equal_to(Decision::ExplicitDeny);
+        assert_that(check(&policy, "a/c")).is_equal_to(Decision::Allow);
+    }
+
+    #[test]
+    fn exceptions_do_not_allow_by_themselves() {
+        let policy = AccessPolicy::parse("allow a\ndeny a/b\nexcept **/*.pub").unwrap();
+        assert_that(check(&policy, "a/b/c.pub")).is_equal_to(Decision::Allow);
+        assert_that(check(&policy, "x/c.pub")).is_equal_to(Decision::ImplicitDeny);
+    }
+
+    #[test]
+    fn empty_prefix_allows_everything() {
+        let mut policy = AccessPolicy::new();
+        policy.allow(S3PathBuf::new());
+        assert_that(check(&policy, "any/key")).is_equal_to(Decision::Allow);
+    }
+
+    #[test]
+    fn parse_reports_line_of_error() {
+        let err = AccessPolicy::parse("allow a\n\npermit b").unwrap_err();
+        assert_that(err.line).is_equal_to(3);
+        assert_that(AccessPolicy::parse("allow a//b")).is_err();
+        assert_that(AccessPolicy::parse("except a/**b")).is_err();
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let policy = AccessPolicy::parse("allow a\ndeny a/b\nexcept a/b/*.pub").unwrap();
+        let rendered = policy.to_string();
+        assert_that(AccessPolicy::parse(&rendered).unwrap().to_string()).is_equal_to(rendered);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn deserializes_from_config() {
+        let json =
+            r#"{"allow": ["tenants/"], "deny": ["tenants/acme/internal"], "except": ["**/*.pub"]}"#;
+        let policy = serde_json::from_str::<AccessPolicy>(json).unwrap();
+        assert_that(check(&policy, "tenants/acme/data.csv")).is_equal_to(Decision::Allow);
+        assert_that(check(&policy, "tenants/acme/internal/keys"))
+            .is_equal_to(Decision::ExplicitDeny);
+        assert_that(check(&policy, "tenants/acme/internal/keys.pub")).is_equal_to(Decision::Allow);
+
+        let round_tripped =
+            serde_json::from_str::<AccessPolicy>(&serde_json::to_string(&policy).unwrap());
+        assert_that(round_tripped.unwrap().to_string()).is_equal_to(policy.to_string());
+        assert_that(serde_json::from_str::<AccessPolicy>(r#"{"deny": ["a"]}"#)).is_ok();
+        assert_that(serde_json::from_str::<AccessPolicy>(
+            r#"{"except": ["a b"]}"#,
+        ))
+        .is_err();
+        assert_that(serde_json::from_str::<AccessPolicy>(r#"{"permit": []}"#)).is_err();
+    }
+}
@@ -0,0 +1,245 @@
+//! Interning of well-known prefixes.
+//!
+//! Services working with a fixed set of roots register each of them once in a
+//! [`StaticPrefixTable`] and refer to them by [`PrefixId`]. Paths created from a registered
+//! prefix borrow its components instead of allocating them, making these paths cheaper to create
+//! and clone.
+//!
+//! Registered prefixes are leaked to be borrowed for `'static`, and never freed. Tables are meant
+//! for a static registry of a bounded set of prefixes, known at startup or from configuration.
+//! Registering prefixes derived from requests or data makes a long-running process leak memory
+//! without bound!
+//!
+//! ```
+//! use s3_path::prefix_table::StaticPrefixTable;
+//! use s3_path::{s3_path, S3PathBuf};
+//!
+//! let exports = StaticPrefixTable::global().register(s3_path!("tenants", "exports").unwrap());
+//!
+//! let path = S3PathBuf::from_prefix_id(exports, ["2024", "data.csv"]).unwrap();
+//! assert_eq!(path.to_string(), "tenants/exports/2024/data.csv");
+//! ```
+
+use crate::error::InvalidS3PathComponent;
+use crate::{S3Path, S3PathBuf};
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{OnceLock, RwLock};
+
+/// Identifies a prefix registered in a [`StaticPrefixTable`]. Ids also identify the table which
+/// issued them, so they are never resolved by another table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PrefixId {
+    table: u32,
+    index: u32,
+}
+
+impl PrefixId {
+    /// Returns the registration index of the prefix within its table.
+    #[must_use]
+    pub fn as_u32(self) -> u32 {
+        self.index
+    }
+}
+
+/// Source of the identifiers of all tables of this process.
+static NEXT_TABLE_ID: AtomicU32 = AtomicU32::new(0);
+
+static GLOBAL: StaticPrefixTable = StaticPrefixTable::new();
+
+/// A concurrently usable table of interned prefixes. See the
+/// [module documentation](crate::prefix_table).
+///
+/// # Memory
+///
+/// Registered prefixes are leaked and never freed, even when the table is dropped. Only register
+/// a bounded set of prefixes.
+#[derive(Debug)]
+pub struct StaticPrefixTable {
+    /// Identifier of this table, assigned on first registration.
+    id: OnceLock<u32>,
+    prefixes: RwLock<Vec<&'static [Cow<'static, str>]>>,
+}
+
+impl Default for StaticPrefixTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StaticPrefixTable {
+    /// Creates an empty table.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            id: OnceLock::new(),
+            prefixes: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Returns the process-wide table used by [`S3PathBuf::from_prefix_id`].
+    #[must_use]
+    pub fn global() -> &'static StaticPrefixTable {
+        &GLOBAL
+    }
+
+    /// Registers `prefix`, returning its id. Registering an equal prefix again returns the same
+    /// id.
+    ///
+    /// The components of every newly registered prefix are leaked, see the
+    /// [type documentation](StaticPrefixTable#memory).
+    ///
+    /// # Panics
+    ///
+    /// Panics if more than `u32::MAX` prefixes are registered, or more than `u32::MAX` tables
+    /// register prefixes.
+    pub fn register(&self, prefix: &S3Path<'_>) -> PrefixId {
+        let table = *self.id.get_or_init(|| {
+            NEXT_TABLE_ID
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |id| id.checked_add(1))
+                .expect("at most u32::MAX prefix tables")
+        });
+        let id = |index: usize| PrefixId {
+            table,
+            index: u32::try_from(index).expect("at most u32::MAX prefixes"),
+        };
+        let mut prefixes = self.prefixes.write().unwrap_or_else(|it| it.into_inner());
+        if let Some(index) = prefixes.iter().position(|it| **it == prefix.0) {
+            return id(index);
+        }
+        let id = id(prefixes.len());
+        let components = prefix
+            .components()
+            .map(|c| Cow::Borrowed(&*Box::leak(c.to_string().into_boxed_str())))
+            .collect::<Vec<_>>();
+        prefixes.push(Box::leak(components.into_boxed_slice()));
+        id
+    }
+
+    /// Returns the prefix registered as `id`, or None if `id` was issued by another table.
+    #[must_use]
+    pub fn get(&self, id: PrefixId) -> Option<&'static S3Path<'static>> {
+        if self.id.get() != Some(&id.table) {
+            return None;
+        }
+        let prefixes = self.prefixes.read().unwrap_or_else(|it| it.into_inner());
+        let components: &'static [Cow<'static, str>] = prefixes.get(id.index as usize)?;
+        // Safety: S3Path is repr(transparent) over [Cow<'i, str>]
+        Some(unsafe {
+            &*(std::ptr::from_ref::<[Cow<'static, str>]>(components) as *const S3Path<'static>)
+        })
+    }
+
+    /// Creates a path from the prefix registered as `id`, followed by the validated `suffix`
+    /// components. The prefix components are borrowed, not copied.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when a suffix component
+    /// - is empty
+    /// - contains characters other than: ascii alphanumeric characters, '-', '_' and '.'
+    /// - is equal to `.` or `..`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` was issued by another table.
+    pub fn path<C: Into<Cow<'static, str>>>(
+        &self,
+        id: PrefixId,
+        suffix: impl IntoIterator<Item = C>,
+    ) -> Result<S3PathBuf, InvalidS3PathComponent> {
+        let prefix = self
+            .get(id)
+            .expect("prefix id must be issued by this table");
+        let mut path = S3PathBuf {
+            components: prefix.0.to_vec(),
+        };
+        for component in suffix {
+            path.push(component)?;
+        }
+        Ok(path)
+    }
+}
+
+impl S3PathBuf {
+    /// Creates a path from the prefix registered as `id` in the
+    /// [global table](StaticPrefixTable::global), followed by the validated `suffix` components.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when a suffix component is invalid, see [`StaticPrefixTable::path`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` was issued by another table than the global table.
+    pub fn from_prefix_id<C: Into<Cow<'static, str>>>(
+        id: PrefixId,
+        suffix: impl IntoIterator<Item = C>,
+    ) -> Result<Self, InvalidS3PathComponent> {
+        StaticPrefixTable::global().path(id, suffix)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::prefix_table::StaticPrefixTable;
+    use crate::S3PathBuf;
+    use assertr::prelude::*;
+    use std::borrow::Cow;
+
+    fn path(key: &str) -> S3PathBuf {
+        S3PathBuf::try_from_str(key).unwrap()
+    }
+
+    #[test]
+    fn registers_prefixes_once() {
+        let table = StaticPrefixTable::new();
+        let a = table.register(&path("a/b"));
+        let c = table.register(&path("c"));
+        assert_that(table.register(&path("a/b"))).is_equal_to(a);
+        assert_that(a == c).is_false();
+        assert_that(table.get(c).map(ToString::to_string)).is_equal_to(Some("c".to_owned()));
+    }
+
+    #[test]
+    fn rejects_ids_of_other_tables() {
+        let first = StaticPrefixTable::new();
+        let second = StaticPrefixTable::new();
+        let a = first.register(&path("a"));
+        let b = second.register(&path("b"));
+        assert_that(a.as_u32()).is_equal_to(b.as_u32());
+        assert_that(a == b).is_false();
+        assert_that(second.get(a)).is_none();
+        assert_that(StaticPrefixTable::new().get(a)).is_none();
+        assert_that(std::panic::catch_unwind(|| second.path(a, ["c"]))).is_err();
+    }
+
+    #[test]
+    fn creates_paths_borrowing_prefix_components() {
+        let table = StaticPrefixTable::new();
+        let id = table.register(&path("a/b"));
+        let created = table.path(id, ["c"]).unwrap();
+        assert_that(created.clone()).is_equal_to(path("a/b/c"));
+        assert_that(matches!(
+            created.components_cow().next(),
+            Some(Cow::Borrowed("a"))
+        ))
+        .is_true();
+        assert_that(table.path(id, [".."])).is_err();
+    }
+
+    #[test]
+    fn is_usable_from_multiple_threads() {
+        let table = StaticPrefixTable::new();
+        let ids = std::thread::scope(|scope| {
+            let handles = (0..4)
+                .map(|_| scope.spawn(|| table.register(&path("shared"))))
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+        assert_that(ids.iter().all(|id| *id == ids[0])).is_true();
+    }
+}
@@ -0,0 +1,292 @@
+//! Representations of arbitrary keys, not restricted by the strict validation of [`S3PathBuf`].
+
+use crate::error::{InvalidS3Key, InvalidS3PathComponent};
+use crate::validation::MAX_KEY_LEN;
+use crate::{validation, S3Path, S3PathBuf};
+use std::borrow::Cow;
+use std::fmt::Formatter;
+
+/// A path allowing for empty components and arbitrary characters (except '/') in its
+/// components, as found in keys like `foo//bar` or `/foo`.
+///
+/// Any key can be represented, and always renders back to exactly the key it was parsed from.
+/// Use this for tools which must faithfully mirror arbitrary existing buckets. Prefer
+/// [`S3PathBuf`] wherever possible, as none of its guarantees hold for a `RawS3Path`.
+///
+/// ```
+/// use s3_path::raw::RawS3Path;
+///
+/// let raw = RawS3Path::parse("/foo//bar");
+/// assert_eq!(raw.components().collect::<Vec<_>>(), ["", "foo", "", "bar"]);
+/// assert_eq!(raw.to_string(), "/foo//bar");
+/// assert!(raw.to_strict().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RawS3Path {
+    components: Vec<String>,
+}
+
+impl RawS3Path {
+    /// Splits `key` at each occurrence of a '/', keeping all empty components.
+    ///
+    /// The empty string is parsed as the path without any components.
+    #[must_use]
+    pub fn parse(key: &str) -> Self {
+        if key.is_empty() {
+            return Self::default();
+        }
+        Self {
+            components: key.split('/').map(str::to_string).collect(),
+        }
+    }
+
+    /// Returns the number of components in this path.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.components.len()
+    }
+
+    /// Returns true if this path has no components.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+
+    /// Returns an iterator over the components of this path.
+    pub fn components(&self) -> impl Iterator<Item = &str> {
+        self.components.iter().map(String::as_str)
+    }
+
+    /// Returns true if any component of this path is empty.
+    #[must_use]
+    pub fn has_empty_components(&self) -> bool {
+        self.components.iter().any(String::is_empty)
+    }
+
+    /// Converts this path into a strictly validated `S3PathBuf`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when any component
+    /// - is empty
+    /// - contains characters other than: ascii alphanumeric characters, '-', '_' and '.'
+    /// - is equal to `.` or `..`
+    pub fn to_strict(&self) -> Result<S3PathBuf, InvalidS3PathComponent> {
+        let mut path = S3PathBuf::new();
+        for c in &self.components {
+            validation::validate_component(c)?;
+            path.components.push(Cow::Owned(c.clone()));
+        }
+        Ok(path)
+    }
+}
+
+impl From<&S3Path<'_>> for RawS3Path {
+    fn from(path: &S3Path<'_>) -> Self {
+        Self {
+            components: path.components().map(str::to_string).collect(),
+        }
+    }
+}
+
+impl From<S3PathBuf> for RawS3Path {
+    fn from(path: S3PathBuf) -> Self {
+        Self {
+            components: path.components.into_iter().map(Cow::into_owned).collect(),
+        }
+    }
+}
+
+impl std::fmt::Display for RawS3Path {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.components.join("/"))
+    }
+}
+
+/// Any key accepted by S3: a non-empty string of at most 1024 bytes.
+///
+/// Unlike [`S3PathBuf`], no further restrictions apply, making this the type to use when handling
+/// keys of buckets not exclusively written through this crate. Strict paths can always be
+/// recovered where possible using [`RawS3Key::to_s3_path_buf`].
+///
+/// ```
+/// use s3_path::raw::RawS3Key;
+/// use s3_path::S3PathBuf;
+///
+/// let key = RawS3Key::new("photos/2024/Me & You.jpg").unwrap();
+/// assert!(key.to_s3_path_buf().is_err());
+///
+/// let path = S3PathBuf::try_from_str("photos/2024/me.jpg").unwrap();
+/// let key = RawS3Key::try_from(path.clone()).unwrap();
+/// assert_eq!(key.as_str(), "photos/2024/me.jpg");
+/// assert_eq!(key.to_s3_path_buf().unwrap(), path);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RawS3Key(String);
+
+impl RawS3Key {
+    /// Creates a key from any string accepted by S3.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when `key` is empty or longer than 1024 bytes.
+    pub fn new(key: impl Into<String>) -> Result<Self, InvalidS3Key> {
+        let key = key.into();
+        if key.is_empty() {
+            return Err(InvalidS3Key {
+                key,
+                reason: "Empty key is not allowed".to_string(),
+            });
+        }
+        if key.len() > MAX_KEY_LEN {
+            let reason = format!(
+                "Key is {} bytes long, exceeding the maximum of {MAX_KEY_LEN} bytes",
+                key.len()
+            );
+            return Err(InvalidS3Key { key, reason });
+        }
+        Ok(Self(key))
+    }
+
+    /// Returns the key as a string slice.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Consumes this key, returning the underlying string.
+    #[must_use]
+    pub fn into_string(self) -> String {
+        self.0
+    }
+
+    /// Splits this key into its (possibly empty) components.
+    #[must_use]
+    pub fn to_raw_path(&self) -> RawS3Path {
+        RawS3Path::parse(&self.0)
+    }
+
+    /// Upgrades this key to a strictly validated `S3PathBuf`.
+    ///
+    /// Succeeds exactly if the resulting path renders to this key again.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when any component of this key
+    /// - is empty
+    /// - contains characters other than: ascii alphanumeric characters, '-', '_' and '.'
+    /// - is equal to `.` or `..`
+    pub fn to_s3_path_buf(&self) -> Result<S3PathBuf, InvalidS3PathComponent> {
+        self.to_raw_path().to_strict()
+    }
+}
+
+impl TryFrom<String> for RawS3Key {
+    type Error = InvalidS3Key;
+
+    fn try_from(key: String) -> Result<Self, Self::Error> {
+        Self::new(key)
+    }
+}
+
+impl TryFrom<&str> for RawS3Key {
+    type Error = InvalidS3Key;
+
+    fn try_from(key: &str) -> Result<Self, Self::Error> {
+        Self::new(key)
+    }
+}
+
+/// Fails for the empty path and for paths rendering to more than 1024 bytes.
+impl TryFrom<&S3Path<'_>> for RawS3Key {
+    type Error = InvalidS3Key;
+
+    fn try_from(path: &S3Path<'_>) -> Result<Self, Self::Error> {
+        Self::new(path.to_string())
+    }
+}
+
+/// Fails for the empty path and for paths rendering to more than 1024 bytes.
+impl TryFrom<S3PathBuf> for RawS3Key {
+    type Error = InvalidS3Key;
+
+    fn try_from(path: S3PathBuf) -> Result<Self, Self::Error> {
+        Self::new(path.to_string())
+    }
+}
+
+impl TryFrom<RawS3Key> for S3PathBuf {
+    type Error = InvalidS3PathComponent;
+
+    fn try_from(key: RawS3Key) -> Result<Self, Self::Error> {
+        key.to_s3_path_buf()
+    }
+}
+
+impl AsRef<str> for RawS3Key {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for RawS3Key {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::raw::{RawS3Key, RawS3Path};
+    use crate::S3PathBuf;
+    use assertr::prelude::*;
+
+    #[test]
+    fn parse_round_trips_any_key() {
+        for key in ["", "/", "//", "foo", "/foo", "foo/", "foo//bar", "f o/ö$"] {
+            assert_that(RawS3Path::parse(key)).has_display_value(key);
+        }
+    }
+
+    #[test]
+    fn parse_keeps_empty_components() {
+        let raw = RawS3Path::parse("foo//bar/");
+        assert_that(raw.len()).is_equal_to(4);
+        assert_that(raw.has_empty_components()).is_true();
+        assert_that(raw.components()).contains_exactly(["foo", "", "bar", ""]);
+    }
+
+    #[test]
+    fn converts_from_and_to_strict_paths() {
+        let path = S3PathBuf::try_from(["foo", "bar"]).unwrap();
+        let raw = RawS3Path::from(path.as_path());
+        assert_that(raw.has_empty_components()).is_false();
+        assert_that(raw.to_strict())
+            .is_ok()
+            .is_equal_to(path.clone());
+        assert_that(RawS3Path::from(path.clone())).is_equal_to(raw);
+
+        assert_that(RawS3Path::parse("foo//bar").to_strict()).is_err();
+        assert_that(RawS3Path::parse("foo/b r").to_strict()).is_err();
+    }
+
+    #[test]
+    fn raw_key_accepts_any_key_within_s3_limits() {
+        assert_that(RawS3Key::new("foo//b r/ö")).is_ok();
+        assert_that(RawS3Key::new("x".repeat(1024))).is_ok();
+        assert_that(RawS3Key::new("")).is_err();
+        assert_that(RawS3Key::new("x".repeat(1025))).is_err();
+    }
+
+    #[test]
+    fn raw_key_converts_losslessly_where_possible() {
+        let path = S3PathBuf::try_from(["foo", "bar.txt"]).unwrap();
+        let key = RawS3Key::try_from(path.clone()).unwrap();
+        assert_that(key.as_str()).is_equal_to("foo/bar.txt");
+        let upgraded: Result<S3PathBuf, _> = key.try_into();
+        assert_that(upgraded).is_ok().is_equal_to(path);
+
+        assert_that(RawS3Key::try_from(S3PathBuf::new())).is_err();
+        assert_that(RawS3Key::new("foo//bar").unwrap().to_s3_path_buf()).is_err();
+    }
+}
@@ -0,0 +1,249 @@
+//! Detection of renamed objects between two manifests.
+//!
+//! Diffing two manifests reports a renamed object as one removed and one added path. Uploading
+//! the added object again is wasteful, as S3 can copy it server-side. [`detect_renames`] pairs
+//! removed and added paths holding equal content, producing a [`SyncPlan`] of copies, uploads
+//! and deletions.
+//!
+//! ```
+//! use s3_path::manifest::{Manifest, ObjectMeta};
+//! use s3_path::renames::{detect_renames, RenameMatcher, SyncAction};
+//! use s3_path::S3PathBuf;
+//! use std::time::SystemTime;
+//!
+//! let meta = |etag: &str| ObjectMeta {
+//!     size: 1,
+//!     etag: etag.to_string(),
+//!     last_modified: SystemTime::UNIX_EPOCH,
+//! };
+//! let path = |key: &str| S3PathBuf::try_from_str(key).unwrap();
+//!
+//! let old = Manifest::from_iter([(path("inbox/a.csv"), meta("1"))]);
+//! let new = Manifest::from_iter([
+//!     (path("done/a.csv"), meta("1")),
+//!     (path("inbox/b.csv"), meta("2")),
+//! ]);
+//!
+//! let plan = detect_renames(&old, &new, &RenameMatcher::default());
+//! assert_eq!(
+//!     plan.actions(),
+//!     [
+//!         SyncAction::Copy {
+//!             from: path("inbox/a.csv"),
+//!             to: path("done/a.csv"),
+//!         },
+//!         SyncAction::Upload(path("inbox/b.csv")),
+//!         SyncAction::Delete(path("inbox/a.csv")),
+//!     ]
+//! );
+//! ```
+
+use crate::manifest::{Manifest, ObjectMeta};
+use crate::{S3Path, S3PathBuf};
+use std::collections::{HashMap, HashSet};
+
+/// Conditions under which a removed and an added path are considered a rename. Equal etags are
+/// always required.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenameMatcher {
+    /// Require equal sizes. Guards against etag collisions of multipart uploads. Defaults to true.
+    pub same_size: bool,
+
+    /// Require equal last components, only pairing moves between prefixes. Defaults to false.
+    pub same_file_name: bool,
+}
+
+impl Default for RenameMatcher {
+    fn default() -> Self {
+        Self {
+            same_size: true,
+            same_file_name: false,
+        }
+    }
+}
+
+impl RenameMatcher {
+    fn matches(&self, old: (&S3Path<'_>, &ObjectMeta), new: (&S3Path<'_>, &ObjectMeta)) -> bool {
+        old.1.etag == new.1.etag
+            && (!self.same_size || old.1.size == new.1.size)
+            && (!self.same_file_name || old.0.last() == new.0.last())
+    }
+}
+
+/// A single step of a [`SyncPlan`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncAction {
+    /// Copy the object at `from` to `to`, server-side.
+    Copy { from: S3PathBuf, to: S3PathBuf },
+
+    /// Upload the object at the given path.
+    Upload(S3PathBuf),
+
+    /// Delete the object at the given path.
+    Delete(S3PathBuf),
+}
+
+/// The work needed to turn one manifest into another. Created by [`detect_renames`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SyncPlan {
+    /// Pairs of `(from, to)` paths of renamed objects.
+    pub renamed: Vec<(S3PathBuf, S3PathBuf)>,
+
+    /// Paths of added or changed objects which are no rename.
+    pub uploaded: Vec<S3PathBuf>,
+
+    /// Paths of removed objects which are no rename.
+    pub deleted: Vec<S3PathBuf>,
+}
+
+impl SyncPlan {
+    /// Returns the actions executing this plan: all copies, then all uploads, then all deletions,
+    /// including the sources of renames. Deleting last never loses data if execution is
+    /// interrupted.
+    #[must_use]
+    pub fn actions(&self) -> Vec<SyncAction> {
+        let copies = self.renamed.iter().map(|(from, to)| SyncAction::Copy {
+            from: from.clone(),
+            to: to.clone(),
+        });
+        let uploads = self.uploaded.iter().cloned().map(SyncAction::Upload);
+        let deletes = self
+            .renamed
+            .iter()
+            .map(|(from, _)| from)
+            .chain(&self.deleted)
+            .cloned()
+            .map(SyncAction::Delete);
+        copies.chain(uploads).chain(deletes).collect()
+    }
+}
+
+/// Returns how well `to` fits as the new path of `from`: equal file names first, then the
+/// number of equal trailing components.
+fn similarity(from: &S3Path<'_>, to: &S3Path<'_>) -> (bool, usize) {
+    let common_suffix = from
+        .components_cow()
+        .rev()
+        .zip(to.components_cow().rev())
+        .take_while(|(l, r)| l == r)
+        .count();
+    (from.last() == to.last(), common_suffix)
+}
+
+/// Diffs `old` and `new`, pairing removed with added paths accepted by `matcher`.
+///
+/// Each removed path is paired with at most one added path, preferring paths with equal file
+/// names and more equal trailing components. Removed paths are paired in listing order.
+#[must_use]
+pub fn detect_renames(old: &Manifest, new: &Manifest, matcher: &RenameMatcher) -> SyncPlan {
+    let diff = old.diff(new);
+    let mut added_by_etag: HashMap<&str, Vec<(&S3PathBuf, &ObjectMeta)>> = HashMap::new();
+    for path in &diff.added {
+        let meta = new.get(path).expect("added path exists");
+        added_by_etag
+            .entry(&meta.etag)
+            .or_default()
+            .push((path, meta));
+    }
+
+    let mut plan = SyncPlan::default();
+    for from in diff.removed {
+        let from_meta = old.get(&from).expect("removed path exists");
+        let best = added_by_etag
+            .get_mut(from_meta.etag.as_str())
+            .and_then(|candidates| {
+                let (index, _) = candidates
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, to)| matcher.matches((&from, from_meta), (to.0, to.1)))
+                    // Prefer the first of equally similar candidates.
+                    .min_by_key(|(i, to)| (std::cmp::Reverse(similarity(&from, to.0)), *i))?;
+                Some(candidates.remove(index).0.clone())
+            });
+        match best {
+            Some(to) => plan.renamed.push((from, to)),
+            None => plan.deleted.push(from),
+        }
+    }
+
+    let renamed_to = plan
+        .renamed
+        .iter()
+        .map(|(_, to)| to)
+        .collect::<HashSet<_>>();
+    plan.uploaded = diff
+        .added
+        .iter()
+        .filter(|path| !renamed_to.contains(path))
+        .chain(&diff.changed)
+        .cloned()
+        .collect();
+    plan
+}
+
+#[cfg(test)]
+mod test {
+    use crate::manifest::{Manifest, ObjectMeta};
+    use crate::renames::{detect_renames, RenameMatcher};
+    use crate::S3PathBuf;
+    use assertr::prelude::*;
+    use std::time::SystemTime;
+
+    fn meta(etag: &str, size: u64) -> ObjectMeta {
+        ObjectMeta {
+            size,
+            etag: etag.to_owned(),
+            last_modified: SystemTime::UNIX_EPOCH,
+        }
+    }
+
+    fn path(key: &str) -> S3PathBuf {
+        S3PathBuf::try_from_str(key).unwrap()
+    }
+
+    #[test]
+    fn pairs_most_similar_paths() {
+        let old = Manifest::from_iter([
+            (path("a/x.csv"), meta("1", 1)),
+            (path("a/y.csv"), meta("1", 1)),
+        ]);
+        let new = Manifest::from_iter([
+            (path("b/y.csv"), meta("1", 1)),
+            (path("b/x.csv"), meta("1", 1)),
+        ]);
+        let plan = detect_renames(&old, &new, &RenameMatcher::default());
+        assert_that(plan.renamed).is_equal_to(vec![
+            (path("a/x.csv"), path("b/x.csv")),
+            (path("a/y.csv"), path("b/y.csv")),
+        ]);
+        assert_that(plan.uploaded.is_empty()).is_true();
+        assert_that(plan.deleted.is_empty()).is_true();
+    }
+
+    #[test]
+    fn respects_matcher() {
+        let old = Manifest::from_iter([(path("a/x"), meta("1", 1)), (path("a/y"), meta("2", 1))]);
+        let new = Manifest::from_iter([(path("b/z"), meta("1", 1)), (path("b/y"), meta("2", 2))]);
+
+        let plan = detect_renames(&old, &new, &RenameMatcher::default());
+        assert_that(plan.renamed.clone()).is_equal_to(vec![(path("a/x"), path("b/z"))]);
+        assert_that(plan.uploaded).is_equal_to(vec![path("b/y")]);
+        assert_that(plan.deleted).is_equal_to(vec![path("a/y")]);
+
+        let matcher = RenameMatcher {
+            same_size: false,
+            same_file_name: true,
+        };
+        let plan = detect_renames(&old, &new, &matcher);
+        assert_that(plan.renamed).is_equal_to(vec![(path("a/y"), path("b/y"))]);
+    }
+
+    #[test]
+    fn changed_objects_are_uploaded() {
+        let old = Manifest::from_iter([(path("a"), meta("1", 1))]);
+        let new = Manifest::from_iter([(path("a"), meta("2", 1))]);
+        let plan = detect_renames(&old, &new, &RenameMatcher::default());
+        assert_that(plan.uploaded).is_equal_to(vec![path("a")]);
+        assert_that(plan.renamed.is_empty()).is_true();
+    }
+}
@@ -0,0 +1,237 @@
+//! Computing the prefixes of date-partitioned keys eligible for deletion.
+//!
+//! A [`DatePartitioning`] is derived from the [`KeyGrammar`] used to write the data, starting
+//! with literal components followed by either a `{date}` component or `{year}`, optionally
+//! followed by `{month}` and `{day}` components. All components after the date are ignored.
+//!
+//! ```
+//! use s3_path::grammar::KeyGrammar;
+//! use s3_path::retention::DatePartitioning;
+//! use std::time::{Duration, SystemTime};
+//!
+//! let grammar = KeyGrammar::parse("logs/{year}/{month}/{day}/{any}").unwrap();
+//! let partitioning = DatePartitioning::new(&grammar, (2022, 11, 1)).unwrap();
+//!
+//! // 2024-03-04 12:00 UTC, keeping 2 days of logs.
+//! let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_709_553_600);
+//! let expired = partitioning
+//!     .expired_prefixes(now, Duration::from_secs(2 * 86_400))
+//!     .into_iter()
+//!     .map(|prefix| prefix.to_string())
+//!     .collect::<Vec<_>>();
+//! assert_eq!(
+//!     expired,
+//!     ["logs/2022", "logs/2023", "logs/2024/01", "logs/2024/02", "logs/2024/03/01"]
+//! );
+//! ```
+
+use crate::error::InvalidKeyGrammar;
+use crate::grammar::{days_in_month, ComponentSpec, KeyGrammar};
+use crate::S3PathBuf;
+use std::borrow::Cow;
+use std::time::{Duration, SystemTime};
+
+/// How dates are laid out in keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Granularity {
+    /// A single `YYYY-MM-DD` component.
+    Date,
+    Year,
+    YearMonth,
+    YearMonthDay,
+}
+
+/// The date partitions of a key layout. See the [module documentation](crate::retention).
+#[derive(Debug, Clone)]
+pub struct DatePartitioning {
+    prefix: Vec<String>,
+    granularity: Granularity,
+    earliest: (u32, u32, u32),
+}
+
+impl DatePartitioning {
+    /// Derives the date partitioning of `grammar`. `earliest` is the `(year, month, day)` of the
+    /// oldest data possibly present, bounding the enumeration of expired prefixes.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when `grammar` does not start with literals followed by a date, or when
+    /// `earliest` is not a valid date.
+    pub fn new(grammar: &KeyGrammar, earliest: (u32, u32, u32)) -> Result<Self, InvalidKeyGrammar> {
+        let invalid = |reason: &str| InvalidKeyGrammar {
+            grammar: grammar.to_string(),
+            reason: reason.to_string(),
+        };
+        let specs = grammar.specs();
+        let prefix = specs
+            .iter()
+            .map_while(|spec| match spec {
+                ComponentSpec::Literal(literal) => Some(literal.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let next = |offset: usize| specs.get(prefix.len() + offset);
+        let granularity = match (next(0), next(1), next(2)) {
+            (Some(ComponentSpec::Date), _, _) => Granularity::Date,
+            (Some(ComponentSpec::Year), Some(ComponentSpec::Month), Some(ComponentSpec::Day)) => {
+                Granularity::YearMonthDay
+            }
+            (Some(ComponentSpec::Year), Some(ComponentSpec::Month), _) => Granularity::YearMonth,
+            (Some(ComponentSpec::Year), _, _) => Granularity::Year,
+            _ => return Err(invalid("Expected literals followed by a date")),
+        };
+        let (year, month, day) = earliest;
+        if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+            return Err(invalid("Invalid earliest date"));
+        }
+        Ok(Self {
+            prefix,
+            granularity,
+            earliest,
+        })
+    }
+
+    /// Returns the prefixes of all partitions holding only data older than `retention` at time
+    /// `now`, in ascending date order.
+    ///
+    /// Partitions are coarsened where possible: for `{year}/{month}/{day}` layouts, a whole year
+    /// is returned as a single prefix once all of its days expired.
+    #[must_use]
+    pub fn expired_prefixes(&self, now: SystemTime, retention: Duration) -> Vec<S3PathBuf> {
+        // Data of a day expired once all of it is older than the retention, meaning all days
+        // before the day of the cutoff.
+        let cutoff = now
+            .checked_sub(retention)
+            .and_then(|it| it.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .map_or((1970, 1, 1), |it| civil_from_days(it.as_secs() / 86_400));
+        let mut prefixes = Vec::new();
+        let mut push = |components: &[String]| {
+            prefixes.push(S3PathBuf {
+                components: self
+                    .prefix
+                    .iter()
+                    .chain(components)
+                    .map(|it| Cow::Owned(it.clone()))
+                    .collect(),
+            });
+        };
+
+        let (cutoff_year, cutoff_month, cutoff_day) = cutoff;
+        let (earliest_year, ..) = self.earliest;
+        if self.granularity == Granularity::Date {
+            let mut date = self.earliest;
+            while date < cutoff {
+                let (year, month, day) = date;
+                push(&[format!("{year:04}-{month:02}-{day:02}")]);
+                date = next_day(date);
+            }
+            return prefixes;
+        }
+        for year in earliest_year..cutoff_year {
+            push(&[format!("{year:04}")]);
+        }
+        if self.granularity == Granularity::Year || earliest_year > cutoff_year {
+            return prefixes;
+        }
+        for month in 1..cutoff_month {
+            push(&[format!("{cutoff_year:04}"), format!("{month:02}")]);
+        }
+        if self.granularity == Granularity::YearMonthDay {
+            for day in 1..cutoff_day {
+                push(&[
+                    format!("{cutoff_year:04}"),
+                    format!("{cutoff_month:02}"),
+                    format!("{day:02}"),
+                ]);
+            }
+        }
+        prefixes
+    }
+}
+
+fn next_day((year, month, day): (u32, u32, u32)) -> (u32, u32, u32) {
+    if day < days_in_month(year, month) {
+        (year, month, day + 1)
+    } else if month < 12 {
+        (year, month + 1, 1)
+    } else {
+        (year + 1, 1, 1)
+    }
+}
+
+/// Converts days since 1970-01-01 into a `(year, month, day)` date.
+///
+/// See <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+#[allow(clippy::cast_possible_truncation)]
+fn civil_from_days(days: u64) -> (u32, u32, u32) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    (year as u32, month as u32, day as u32)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::grammar::KeyGrammar;
+    use crate::retention::{civil_from_days, DatePartitioning};
+    use assertr::prelude::*;
+    use std::time::{Duration, SystemTime};
+
+    const DAY: u64 = 86_400;
+
+    fn expired(grammar: &str, earliest: (u32, u32, u32), now_days: u64, days: u64) -> Vec<String> {
+        DatePartitioning::new(&KeyGrammar::parse(grammar).unwrap(), earliest)
+            .unwrap()
+            .expired_prefixes(
+                SystemTime::UNIX_EPOCH + Duration::from_secs(now_days * DAY + 3600),
+                Duration::from_secs(days * DAY),
+            )
+            .into_iter()
+            .map(|it| it.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn converts_days_to_dates() {
+        assert_that(civil_from_days(0)).is_equal_to((1970, 1, 1));
+        assert_that(civil_from_days(19_782)).is_equal_to((2024, 2, 29));
+        assert_that(civil_from_days(11_016)).is_equal_to((2000, 2, 29));
+    }
+
+    #[test]
+    fn rejects_grammars_without_leading_date() {
+        let grammar = KeyGrammar::parse("{any}/{date}").unwrap();
+        assert_that(DatePartitioning::new(&grammar, (2024, 1, 1))).is_err();
+        let grammar = KeyGrammar::parse("logs/{month}").unwrap();
+        assert_that(DatePartitioning::new(&grammar, (2024, 1, 1))).is_err();
+        let grammar = KeyGrammar::parse("logs/{date}").unwrap();
+        assert_that(DatePartitioning::new(&grammar, (2023, 2, 29))).is_err();
+    }
+
+    #[test]
+    fn enumerates_single_component_dates() {
+        // 2024-03-02, keeping one day: 2024-02-28 and 2024-02-29 expired.
+        let prefixes = expired("logs/{date}/{any}", (2024, 2, 28), 19_784, 1);
+        assert_that(prefixes).is_equal_to(vec![
+            "logs/2024-02-28".to_string(),
+            "logs/2024-02-29".to_string(),
+        ]);
+        assert_that(expired("{date}", (2024, 3, 1), 19_784, 1)).is_empty();
+    }
+
+    #[test]
+    fn coarsens_hierarchical_dates() {
+        // 2024-03-02, keeping 30 days: cutoff 2024-02-01.
+        assert_that(expired("{year}/{month}", (2023, 6, 1), 19_784, 30))
+            .is_equal_to(vec!["2023".to_string(), "2024/01".to_string()]);
+        assert_that(expired("{year}", (2023, 6, 1), 19_784, 30))
+            .is_equal_to(vec!["2023".to_string()]);
+        assert_that(expired("{year}/{month}/{day}", (2025, 1, 1), 19_784, 30)).is_empty();
+    }
+}
@@ -0,0 +1,157 @@
+//! Deterministic shuffling and sampling of paths, e.g. for building representative subsets of
+//! huge buckets for staging tests. Equal seeds and inputs always produce equal results.
+//!
+//! ```
+//! use s3_path::sampling::{sample, sample_per_prefix};
+//! use s3_path::S3PathBuf;
+//!
+//! let keys = (0..1000).map(|i| S3PathBuf::try_from_str(format!("t{}/{i}.csv", i % 4)).unwrap());
+//!
+//! let subset = sample(keys.clone(), 10, 7);
+//! assert_eq!(subset.len(), 10);
+//! assert_eq!(subset, sample(keys.clone(), 10, 7));
+//!
+//! // Two keys of each of the four tenants.
+//! let per_tenant = sample_per_prefix(keys, 1, 2, 7);
+//! assert_eq!(per_tenant.len(), 8);
+//! ```
+
+use crate::rng::SplitMix64;
+use crate::S3Path;
+use std::collections::HashMap;
+
+/// Shuffles `items` in place, using a Fisher-Yates shuffle seeded with `seed`.
+pub fn shuffle<T>(items: &mut [T], seed: u64) {
+    let mut rng = SplitMix64::new(seed);
+    for i in (1..items.len()).rev() {
+        items.swap(i, rng.below(i + 1));
+    }
+}
+
+/// A uniform sample of up to `k` items of a stream of unknown length (Algorithm R).
+struct Reservoir<T> {
+    seen: usize,
+    items: Vec<(usize, T)>,
+}
+
+impl<T> Reservoir<T> {
+    fn new() -> Self {
+        Self {
+            seen: 0,
+            items: Vec::new(),
+        }
+    }
+
+    /// Offers the item at `index` of the whole stream.
+    fn offer(&mut self, index: usize, item: T, k: usize, rng: &mut SplitMix64) {
+        self.seen += 1;
+        if self.items.len() < k {
+            self.items.push((index, item));
+        } else {
+            let slot = rng.below(self.seen);
+            if slot < k {
+                self.items[slot] = (index, item);
+            }
+        }
+    }
+}
+
+/// Returns the sampled items in the order of the stream.
+fn into_stream_order<T>(reservoirs: impl IntoIterator<Item = Reservoir<T>>) -> Vec<T> {
+    let mut items = reservoirs
+        .into_iter()
+        .flat_map(|reservoir| reservoir.items)
+        .collect::<Vec<_>>();
+    items.sort_unstable_by_key(|(index, _)| *index);
+    items.into_iter().map(|(_, item)| item).collect()
+}
+
+/// Picks a uniform random sample of `k` items (or all, if there are fewer) in a single pass
+/// over `items`, using reservoir sampling. Only the sample is kept in memory, so `items` may be
+/// a listing of a huge bucket. The sample is returned in the order of `items`.
+#[must_use]
+pub fn sample<T>(items: impl IntoIterator<Item = T>, k: usize, seed: u64) -> Vec<T> {
+    let mut rng = SplitMix64::new(seed);
+    let mut reservoir = Reservoir::new();
+    for (index, item) in items.into_iter().enumerate() {
+        reservoir.offer(index, item, k, &mut rng);
+    }
+    into_stream_order([reservoir])
+}
+
+/// Like [`sample`], but picking up to `k` paths for each distinct prefix of `depth` components,
+/// so that sparse prefixes are represented as well as dense ones. Paths with fewer than `depth`
+/// components are grouped by all of their components.
+#[must_use]
+pub fn sample_per_prefix<'p, P: AsRef<S3Path<'p>>>(
+    paths: impl IntoIterator<Item = P>,
+    depth: usize,
+    k: usize,
+    seed: u64,
+) -> Vec<P> {
+    let mut rng = SplitMix64::new(seed);
+    let mut reservoirs: HashMap<Vec<String>, Reservoir<P>> = HashMap::new();
+    for (index, path) in paths.into_iter().enumerate() {
+        let prefix = path
+            .as_ref()
+            .components()
+            .take(depth)
+            .map(str::to_owned)
+            .collect();
+        reservoirs
+            .entry(prefix)
+            .or_insert_with(Reservoir::new)
+            .offer(index, path, k, &mut rng);
+    }
+    into_stream_order(reservoirs.into_values())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::sampling::{sample, sample_per_prefix, shuffle};
+    use crate::S3PathBuf;
+    use assertr::prelude::*;
+
+    #[test]
+    fn shuffles_deterministically() {
+        let mut first = (0..100).collect::<Vec<_>>();
+        let mut second = first.clone();
+        shuffle(&mut first, 3);
+        shuffle(&mut second, 3);
+        assert_that(first.clone()).is_equal_to(second);
+        assert_that(first.clone()).is_not_equal_to((0..100).collect::<Vec<_>>());
+        first.sort_unstable();
+        assert_that(first).is_equal_to((0..100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn samples_uniformly_in_stream_order() {
+        let picked = sample(0..10, 20, 1);
+        assert_that(picked).is_equal_to((0..10).collect::<Vec<_>>());
+
+        let mut hits = [0_u32; 10];
+        for seed in 0..2000 {
+            let picked = sample(0..10, 3, seed);
+            assert_that(picked.windows(2).all(|it| it[0] < it[1])).is_true();
+            for item in picked {
+                hits[item] += 1;
+            }
+        }
+        // Each item is expected in 600 of the 2000 samples.
+        assert_that(hits.iter().all(|&hit| (500..700).contains(&hit))).is_true();
+    }
+
+    #[test]
+    fn samples_each_prefix() {
+        let paths = ["a/1", "a/2", "a/3", "b/1", "c", "a/4"]
+            .map(|key| S3PathBuf::try_from_str(key).unwrap());
+        let picked = sample_per_prefix(&paths, 1, 1, 5);
+        let mut prefixes = picked
+            .iter()
+            .map(|path| path.components().next().unwrap())
+            .collect::<Vec<_>>();
+        prefixes.sort_unstable();
+        assert_that(prefixes).is_equal_to(vec!["a", "b", "c"]);
+        assert_that(sample_per_prefix(&paths, 1, 1, 5)).is_equal_to(picked);
+    }
+}
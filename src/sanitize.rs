@@ -0,0 +1,172 @@
+//! Appending untrusted, user-controlled input to paths.
+
+use crate::error::InvalidS3PathComponent;
+use crate::validation;
+use crate::S3PathBuf;
+use std::borrow::Cow;
+
+/// How [`S3PathBuf::join_user_suffix`] treats segments which are not valid path components.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SanitizePolicy {
+    /// Fail on the first invalid segment, including empty segments caused by leading, trailing or
+    /// repeated slashes.
+    #[default]
+    Reject,
+
+    /// Drop empty, `.` and `..` segments and replace every disallowed character with '_'.
+    Sanitize,
+}
+
+/// The result of [`S3PathBuf::join_user_suffix`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserSuffixJoin {
+    /// The joined path. Always starts with all components of the path joined onto.
+    pub path: S3PathBuf,
+
+    /// Number of segments appended to the path.
+    pub appended: usize,
+
+    /// Number of empty, `.` and `..` segments dropped.
+    pub dropped_segments: usize,
+
+    /// Number of disallowed characters replaced with '_'.
+    pub replaced_characters: usize,
+}
+
+impl UserSuffixJoin {
+    /// Returns true if the suffix was appended without any modification.
+    #[must_use]
+    pub fn is_unmodified(&self) -> bool {
+        self.dropped_segments == 0 && self.replaced_characters == 0
+    }
+}
+
+impl S3PathBuf {
+    /// Appends `suffix`, e.g. a path typed by a user, split on '/' into multiple components.
+    ///
+    /// The returned path never escapes this path: `.` and `..` are never appended, whatever the
+    /// policy. Use [`SanitizePolicy::Reject`] wherever modifying the input silently would be
+    /// surprising to the user.
+    ///
+    /// ```
+    /// use s3_path::sanitize::SanitizePolicy;
+    /// use s3_path::S3PathBuf;
+    ///
+    /// let home = S3PathBuf::try_from(["users", "alice"]).unwrap();
+    ///
+    /// assert!(home.join_user_suffix("../bob/secrets", SanitizePolicy::Reject).is_err());
+    ///
+    /// let joined = home
+    ///     .join_user_suffix("/../bob/my secrets", SanitizePolicy::Sanitize)
+    ///     .unwrap();
+    /// assert_eq!(joined.path.to_string(), "users/alice/bob/my_secrets");
+    /// assert_eq!(joined.dropped_segments, 2);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when the policy is [`SanitizePolicy::Reject`] and a segment of `suffix`
+    /// - is empty
+    /// - contains characters other than: ascii alphanumeric characters, '-', '_' and '.'
+    /// - is equal to `.` or `..`
+    pub fn join_user_suffix(
+        &self,
+        suffix: &str,
+        policy: SanitizePolicy,
+    ) -> Result<UserSuffixJoin, InvalidS3PathComponent> {
+        let mut join = UserSuffixJoin {
+            path: self.clone(),
+            appended: 0,
+            dropped_segments: 0,
+            replaced_characters: 0,
+        };
+        for segment in suffix.split('/') {
+            let component = match policy {
+                SanitizePolicy::Reject => {
+                    validation::validate_component(segment)?;
+                    segment.to_string()
+                }
+                SanitizePolicy::Sanitize => {
+                    if segment.is_empty() || segment == "." || segment == ".." {
+                        join.dropped_segments += 1;
+                        continue;
+                    }
+                    segment
+                        .chars()
+                        .map(|c| {
+                            if validation::is_allowed_char(c) {
+                                c
+                            } else {
+                                join.replaced_characters += 1;
+                                '_'
+                            }
+                        })
+                        .collect()
+                }
+            };
+            join.path.components.push(Cow::Owned(component));
+            join.appended += 1;
+        }
+        // The jail invariant: the result stays below `self` and can not traverse out of it.
+        debug_assert!(join.path.starts_with(self));
+        debug_assert!(join.path.components[self.components.len()..]
+            .iter()
+            .all(|component| validation::validate_component(component).is_ok()));
+        Ok(join)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::sanitize::SanitizePolicy;
+    use crate::S3PathBuf;
+    use assertr::prelude::*;
+
+    fn home() -> S3PathBuf {
+        S3PathBuf::try_from(["users", "alice"]).unwrap()
+    }
+
+    #[test]
+    fn appends_valid_suffix_unmodified_under_both_policies() {
+        for policy in [SanitizePolicy::Reject, SanitizePolicy::Sanitize] {
+            let joined = home().join_user_suffix("docs/a.txt", policy).unwrap();
+            assert_that(joined.path.to_string()).is_equal_to("users/alice/docs/a.txt".to_owned());
+            assert_that(joined.appended).is_equal_to(2);
+            assert_that(joined.is_unmodified()).is_true();
+        }
+    }
+
+    #[test]
+    fn reject_fails_on_any_invalid_segment() {
+        for suffix in ["", "a//b", "/a", "a/", "a/../b", "./a", "a b", "ä"] {
+            assert_that(home().join_user_suffix(suffix, SanitizePolicy::Reject)).is_err();
+        }
+    }
+
+    #[test]
+    fn sanitize_never_escapes_the_base_path() {
+        for suffix in ["..", "../..", "/../../", "./../.", ""] {
+            let joined = home()
+                .join_user_suffix(suffix, SanitizePolicy::Sanitize)
+                .unwrap();
+            assert_that(joined.path).is_equal_to(home());
+            assert_that(joined.appended).is_equal_to(0);
+        }
+
+        let joined = home()
+            .join_user_suffix("/../../etc", SanitizePolicy::Sanitize)
+            .unwrap();
+        assert_that(joined.path.to_string()).is_equal_to("users/alice/etc".to_owned());
+    }
+
+    #[test]
+    fn sanitize_replaces_disallowed_characters() {
+        let joined = home()
+            .join_user_suffix("my files/ä$.txt/...", SanitizePolicy::Sanitize)
+            .unwrap();
+        assert_that(joined.path.to_string())
+            .is_equal_to("users/alice/my_files/__.txt/...".to_owned());
+        assert_that(joined.replaced_characters).is_equal_to(3);
+        assert_that(joined.dropped_segments).is_equal_to(0);
+    }
+}
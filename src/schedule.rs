@@ -0,0 +1,275 @@
+//! Planning of parallel listings for large prefixes.
+//!
+//! Listing a prefix containing millions of keys page by page is slow. A [`PrefixScheduler`] splits
+//! such "hot" prefixes into sub-prefixes which can be listed in parallel. The resulting
+//! [`ListPlan`] is plain data, so that it can be executed using any SDK.
+
+use crate::validation::MAX_KEY_LEN;
+
+/// All characters a key created through this crate can contain after some prefix: '/' and all
+/// characters allowed in path components. Sorted in ascending byte order, which is the order in
+/// which S3 lists keys.
+const NEXT_CHARS: &[u8] = b"-./0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ_abcdefghijklmnopqrstuvwxyz";
+
+/// A single unit of work in a [`ListPlan`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListTask {
+    /// List all keys starting with the given prefix.
+    Prefix(String),
+
+    /// Check whether a key equal to the given string exists.
+    ///
+    /// Created whenever a prefix is split, as the key equal to the split prefix itself is not
+    /// covered by any of the sub-prefixes.
+    Exact(String),
+}
+
+/// An ordered list of [`ListTask`]s.
+///
+/// The key ranges covered by the tasks do not overlap and are in ascending order, meaning that
+/// the concatenation of all task results (in plan order) yields the same sequence of keys a
+/// sequential listing of the original prefix would have returned.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ListPlan {
+    tasks: Vec<ListTask>,
+    truncated: bool,
+}
+
+impl ListPlan {
+    /// Returns the tasks of this plan, in ascending key order.
+    #[must_use]
+    pub fn tasks(&self) -> &[ListTask] {
+        &self.tasks
+    }
+
+    /// Returns true if a hot prefix was left unsplit because the maximum depth or number of tasks
+    /// of the [`PrefixScheduler`] was reached. The plan still covers all keys, but some of its
+    /// tasks are estimated to contain more than the requested number of keys.
+    #[must_use]
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Merges the results of all tasks, given in plan order, into one ordered sequence.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of results differs from the number of tasks in this plan.
+    pub fn merge<T>(&self, results: impl IntoIterator<Item = Vec<T>>) -> Vec<T> {
+        let results = results.into_iter().collect::<Vec<_>>();
+        assert_eq!(
+            results.len(),
+            self.tasks.len(),
+            "expected exactly one result per task"
+        );
+        results.into_iter().flatten().collect()
+    }
+}
+
+/// Recursively splits hot prefixes into sub-prefixes until every resulting prefix is estimated
+/// to contain at most `max_keys_per_task` keys.
+///
+/// As every split adds up to 67 tasks, splitting is bounded by a maximum depth (16 levels by
+/// default) and a maximum number of tasks (10 000 by default). Hot prefixes exceeding these
+/// limits are listed as a whole, and the plan is marked as [truncated](ListPlan::is_truncated).
+///
+/// Only keys consisting of characters valid in this crate ('/' and the allowed component
+/// characters) are covered by the created plans. Keys containing other characters, which might
+/// exist in buckets not exclusively written through this crate, may be missed.
+///
+/// ```
+/// use s3_path::schedule::{ListTask, PrefixScheduler};
+///
+/// // Observed key counts, e.g. taken from a previous run or a sampled first page.
+/// let estimate = |prefix: &str| match prefix {
+///     "logs/" => 5000,
+///     _ => 10,
+/// };
+/// let plan = PrefixScheduler::new(1000).plan("logs/", estimate);
+///
+/// assert_eq!(plan.tasks()[0], ListTask::Exact("logs/".to_owned()));
+/// assert_eq!(plan.tasks()[1], ListTask::Prefix("logs/-".to_owned()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrefixScheduler {
+    max_keys_per_task: usize,
+    max_depth: usize,
+    max_tasks: usize,
+}
+
+impl PrefixScheduler {
+    /// Creates a scheduler splitting every prefix estimated to contain more than
+    /// `max_keys_per_task` keys.
+    #[must_use]
+    pub fn new(max_keys_per_task: usize) -> Self {
+        Self {
+            max_keys_per_task,
+            max_depth: 16,
+            max_tasks: 10_000,
+        }
+    }
+
+    /// Limits how many times prefixes are split recursively. A depth of zero never splits.
+    #[must_use]
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Limits the number of tasks of created plans. Prefixes are only split while the plan stays
+    /// within this limit, so plans always have at least one and at most `max(1, max_tasks)`
+    /// tasks.
+    #[must_use]
+    pub fn with_max_tasks(mut self, max_tasks: usize) -> Self {
+        self.max_tasks = max_tasks;
+        self
+    }
+
+    /// Returns the direct sub-prefixes of `prefix`, in ascending key order.
+    ///
+    /// Together with the key equal to `prefix` itself, these cover all keys starting with
+    /// `prefix`.
+    #[must_use]
+    pub fn split(prefix: &str) -> Vec<String> {
+        NEXT_CHARS
+            .iter()
+            .map(|c| {
+                let mut sub_prefix = String::with_capacity(prefix.len() + 1);
+                sub_prefix.push_str(prefix);
+                sub_prefix.push(char::from(*c));
+                sub_prefix
+            })
+            .collect()
+    }
+
+    /// Creates a plan listing all keys starting with `prefix`.
+    ///
+    /// `estimate` is called with every considered prefix and must return the (estimated) number
+    /// of keys starting with it.
+    pub fn plan(&self, prefix: &str, mut estimate: impl FnMut(&str) -> usize) -> ListPlan {
+        let mut plan = ListPlan::default();
+        // Number of tasks of the final plan, counting sub-prefixes not yet visited.
+        let mut planned = 1;
+        self.plan_into(prefix.to_owned(), 0, &mut estimate, &mut planned, &mut plan);
+        plan
+    }
+
+    fn plan_into(
+        &self,
+        prefix: String,
+        depth: usize,
+        estimate: &mut impl FnMut(&str) -> usize,
+        planned: &mut usize,
+        plan: &mut ListPlan,
+    ) {
+        // Prefixes are never split beyond the maximum key length.
+        if prefix.len() >= MAX_KEY_LEN || estimate(&prefix) <= self.max_keys_per_task {
+            plan.tasks.push(ListTask::Prefix(prefix));
+            return;
+        }
+        let sub_prefixes = Self::split(&prefix);
+        // Splitting replaces the task of this prefix.
+        let added = sub_prefixes.len() + usize::from(!prefix.is_empty()) - 1;
+        if depth >= self.max_depth || *planned + added > self.max_tasks {
+            plan.truncated = true;
+            plan.tasks.push(ListTask::Prefix(prefix));
+            return;
+        }
+        *planned += added;
+        if !prefix.is_empty() {
+            plan.tasks.push(ListTask::Exact(prefix));
+        }
+        for sub_prefix in sub_prefixes {
+            self.plan_into(sub_prefix, depth + 1, estimate, planned, plan);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::schedule::{ListTask, PrefixScheduler};
+    use assertr::prelude::*;
+
+    #[test]
+    fn split_yields_sub_prefixes_in_ascending_order() {
+        let sub_prefixes = PrefixScheduler::split("foo");
+        assert_that(sub_prefixes.len()).is_equal_to(66);
+        assert_that(sub_prefixes.windows(2).all(|w| w[0] < w[1])).is_true();
+        assert_that(sub_prefixes[2].as_str()).is_equal_to("foo/");
+    }
+
+    #[test]
+    fn plan_does_not_split_cold_prefix() {
+        let plan = PrefixScheduler::new(1000).plan("foo/", |_| 1000);
+        assert_that(plan.tasks()).contains_exactly([&ListTask::Prefix("foo/".to_owned())]);
+    }
+
+    #[test]
+    fn plan_splits_hot_prefixes_recursively() {
+        let plan = PrefixScheduler::new(10).plan("foo/", |prefix| match prefix {
+            "foo/" | "foo/a" => 100,
+            _ => 1,
+        });
+
+        // One exact check and 65 sub-prefixes for "foo/", of which "foo/a" got split again.
+        assert_that(plan.tasks().len()).is_equal_to(1 + 65 + 1 + 66);
+        assert_that(&plan.tasks()[0]).is_equal_to(&ListTask::Exact("foo/".to_owned()));
+
+        let ordered_keys = plan
+            .tasks()
+            .iter()
+            .map(|task| match task {
+                ListTask::Prefix(key) | ListTask::Exact(key) => key.as_str(),
+            })
+            .collect::<Vec<_>>();
+        assert_that(ordered_keys.windows(2).all(|w| w[0] < w[1])).is_true();
+    }
+
+    #[test]
+    fn plan_stops_splitting_at_limits() {
+        let always_hot = |_: &str| usize::MAX;
+        let plan = PrefixScheduler::new(10).plan("foo/", always_hot);
+        assert_that(plan.is_truncated()).is_true();
+        assert_that(plan.tasks().len() <= 10_000).is_true();
+
+        let plan = PrefixScheduler::new(10)
+            .with_max_depth(1)
+            .plan("foo/", always_hot);
+        assert_that(plan.is_truncated()).is_true();
+        assert_that(plan.tasks().len()).is_equal_to(1 + 66);
+
+        // "foo/" is split, but none of its sub-prefixes fits into the remaining budget.
+        let plan = PrefixScheduler::new(10)
+            .with_max_tasks(100)
+            .plan("foo/", always_hot);
+        assert_that(plan.tasks().len()).is_equal_to(1 + 66);
+
+        let plan = PrefixScheduler::new(10)
+            .with_max_tasks(0)
+            .plan("foo/", always_hot);
+        assert_that(plan.tasks()).contains_exactly([&ListTask::Prefix("foo/".to_owned())]);
+        assert_that(plan.is_truncated()).is_true();
+
+        let ordered_keys = PrefixScheduler::new(10)
+            .plan("", always_hot)
+            .tasks()
+            .iter()
+            .map(|task| match task {
+                ListTask::Prefix(key) | ListTask::Exact(key) => key.clone(),
+            })
+            .collect::<Vec<_>>();
+        assert_that(ordered_keys.windows(2).all(|w| w[0] < w[1])).is_true();
+    }
+
+    #[test]
+    fn merge_concatenates_results_in_plan_order() {
+        let plan = PrefixScheduler::new(0).plan("", |prefix| usize::from(prefix.is_empty()));
+        let results = plan.tasks().iter().map(|task| match task {
+            ListTask::Prefix(prefix) => vec![format!("{prefix}x")],
+            ListTask::Exact(_) => Vec::new(),
+        });
+        let merged = plan.merge(results);
+        assert_that(merged.len()).is_equal_to(66);
+        assert_that(merged[0].as_str()).is_equal_to("-x");
+    }
+}
@@ -0,0 +1,160 @@
+//! Rendering and parsing paths using separators other than '/'.
+//!
+//! Allows using the same component model for flat-namespace systems, e.g. Redis-style keys like
+//! `user:42:profile` mirrored to S3. As no separator may contain characters allowed in
+//! components, rendering and parsing are unambiguous.
+
+use crate::error::InvalidS3PathComponent;
+use crate::{validation, S3Path, S3PathBuf};
+use std::borrow::Cow;
+use std::fmt::Formatter;
+use std::marker::PhantomData;
+
+/// A separator placed between components, which may consist of multiple characters.
+pub trait Separator {
+    /// The separator string. Must not be empty and must not contain any character allowed in
+    /// components (ascii alphanumeric characters, '-', '_' and '.').
+    const SEPARATOR: &'static str;
+}
+
+/// Separates components using '/', as S3 does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Slash;
+
+impl Separator for Slash {
+    const SEPARATOR: &'static str = "/";
+}
+
+/// Separates components using ':', as commonly done for Redis keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Colon;
+
+impl Separator for Colon {
+    const SEPARATOR: &'static str = ":";
+}
+
+/// Separates components using '|'.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Pipe;
+
+impl Separator for Pipe {
+    const SEPARATOR: &'static str = "|";
+}
+
+/// Renders a path using separator `S`. Created by [`S3Path::display_with`].
+pub struct SeparatedDisplay<'p, 'i, S: Separator = Slash> {
+    path: &'p S3Path<'i>,
+    separator: PhantomData<S>,
+}
+
+impl<S: Separator> std::fmt::Display for SeparatedDisplay<'_, '_, S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, c) in self.path.components().enumerate() {
+            if i > 0 {
+                f.write_str(S::SEPARATOR)?;
+            }
+            f.write_str(c)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'i> S3Path<'i> {
+    /// Returns a value rendering this path using separator `S` instead of '/'.
+    ///
+    /// ```
+    /// use s3_path::s3_path;
+    /// use s3_path::separator::Colon;
+    ///
+    /// let path = s3_path!("user", "42", "profile").unwrap();
+    /// assert_eq!(path.display_with::<Colon>().to_string(), "user:42:profile");
+    /// ```
+    #[must_use]
+    pub fn display_with<S: Separator>(&self) -> SeparatedDisplay<'_, 'i, S> {
+        SeparatedDisplay {
+            path: self,
+            separator: PhantomData,
+        }
+    }
+}
+
+impl S3PathBuf {
+    /// Like [`S3PathBuf::strict_parse`], but splits `string` at each occurrence of separator `S`
+    /// instead of '/'.
+    ///
+    /// For every path `p`, `S3PathBuf::strict_parse_with::<S>(p.display_with::<S>().to_string())`
+    /// returns a path equal to `p`.
+    ///
+    /// ```
+    /// use s3_path::separator::Colon;
+    /// use s3_path::S3PathBuf;
+    ///
+    /// let path = S3PathBuf::strict_parse_with::<Colon>("user:42:profile").unwrap();
+    /// assert_eq!(path.to_string(), "user/42/profile");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when any component read
+    /// - is empty (caused by a leading, trailing or repeated separator)
+    /// - contains characters other than: ascii alphanumeric characters, '-', '_' and '.'
+    /// - is equal to `.` or `..`
+    pub fn strict_parse_with<S: Separator>(
+        string: impl AsRef<str>,
+    ) -> Result<Self, InvalidS3PathComponent> {
+        let string = string.as_ref();
+        let mut path = S3PathBuf::new();
+        if !string.is_empty() {
+            for c in string.split(S::SEPARATOR) {
+                validation::validate_component(c)?;
+                path.components.push(Cow::Owned(c.to_string()));
+            }
+        }
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::separator::{Colon, Pipe, Separator, Slash};
+    use crate::S3PathBuf;
+    use assertr::prelude::*;
+
+    struct DoubleColon;
+
+    impl Separator for DoubleColon {
+        const SEPARATOR: &'static str = "::";
+    }
+
+    #[test]
+    fn slash_separator_matches_default_rendering_and_parsing() {
+        let path = S3PathBuf::try_from_str("a/b/c").unwrap();
+        assert_that(path.display_with::<Slash>().to_string()).is_equal_to(path.to_string());
+        assert_that(S3PathBuf::strict_parse_with::<Slash>("a/b/c"))
+            .is_ok()
+            .is_equal_to(path);
+    }
+
+    #[test]
+    fn round_trips_with_single_and_multi_char_separators() {
+        let path = S3PathBuf::try_from_str("a/b.txt/c").unwrap();
+        let piped = path.display_with::<Pipe>().to_string();
+        assert_that(piped.as_str()).is_equal_to("a|b.txt|c");
+        assert_that(S3PathBuf::strict_parse_with::<Pipe>(&piped))
+            .is_ok()
+            .is_equal_to(path.clone());
+
+        let double_colon = path.display_with::<DoubleColon>().to_string();
+        assert_that(double_colon.as_str()).is_equal_to("a::b.txt::c");
+        assert_that(S3PathBuf::strict_parse_with::<DoubleColon>(&double_colon))
+            .is_ok()
+            .is_equal_to(path);
+    }
+
+    #[test]
+    fn rejects_empty_components() {
+        assert_that(S3PathBuf::strict_parse_with::<Colon>(":a")).is_err();
+        assert_that(S3PathBuf::strict_parse_with::<Colon>("a::b")).is_err();
+        assert_that(S3PathBuf::strict_parse_with::<DoubleColon>("a::")).is_err();
+    }
+}
@@ -0,0 +1,271 @@
+//! Serialization of paths as their rendered key, e.g. `"foo/bar"`, or as the sequence of their
+//! components using [`components`]. Use [`strict`] to only accept canonical keys.
+
+use crate::{S3Path, S3PathBuf};
+use std::fmt::Formatter;
+
+/// Serializes as the rendered key, e.g. `"foo/bar"`.
+impl ::serde::Serialize for S3PathBuf {
+    fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Serializes as the rendered key, e.g. `"foo/bar"`, just like [`S3PathBuf`].
+impl ::serde::Serialize for S3Path<'_> {
+    fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Deserializes from a key, using [`S3PathBuf::try_from_str`]: leading, trailing and repeated
+/// slashes are ignored. Use [`strict`] to reject keys which are not rendered back exactly.
+impl<'de> ::serde::Deserialize<'de> for S3PathBuf {
+    fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct KeyVisitor;
+
+        impl ::serde::de::Visitor<'_> for KeyVisitor {
+            type Value = S3PathBuf;
+
+            fn expecting(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                f.write_str("an S3 key")
+            }
+
+            fn visit_str<E: ::serde::de::Error>(self, key: &str) -> Result<S3PathBuf, E> {
+                S3PathBuf::try_from_str(key).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(KeyVisitor)
+    }
+}
+
+/// Serializes as the glob pattern, e.g. `"logs/**/*.gz"`.
+impl ::serde::Serialize for crate::glob::Glob {
+    fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Deserializes from a glob pattern, using [`Glob::parse`](crate::glob::Glob::parse).
+impl<'de> ::serde::Deserialize<'de> for crate::glob::Glob {
+    fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let glob = <String as ::serde::Deserialize>::deserialize(deserializer)?;
+        Self::parse(&glob).map_err(::serde::de::Error::custom)
+    }
+}
+
+/// Represents paths as their rendered key, like the `Serialize` and `Deserialize` implementations
+/// of [`S3PathBuf`], but only accepting keys which round-trip, using
+/// [`S3PathBuf::strict_parse`]. For use with `#[serde(with = "s3_path::serde::strict")]`.
+///
+/// ```
+/// use s3_path::S3PathBuf;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Object {
+///     #[serde(with = "s3_path::serde::strict")]
+///     key: S3PathBuf,
+/// }
+///
+/// assert!(serde_json::from_str::<Object>(r#"{"key":"a/b"}"#).is_ok());
+/// assert!(serde_json::from_str::<Object>(r#"{"key":"/a//b"}"#).is_err());
+/// ```
+pub mod strict {
+    use crate::{S3Path, S3PathBuf};
+
+    /// Serializes `path` as its rendered key.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the serializer.
+    pub fn serialize<S: ::serde::Serializer>(
+        path: &S3Path<'_>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(path)
+    }
+
+    /// Deserializes a path from a key using [`S3PathBuf::strict_parse`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when the input is no string, or no key accepted by `strict_parse`.
+    pub fn deserialize<'de, D: ::serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<S3PathBuf, D::Error> {
+        let key = <String as ::serde::Deserialize>::deserialize(deserializer)?;
+        S3PathBuf::strict_parse(key).map_err(::serde::de::Error::custom)
+    }
+}
+
+/// Represents paths as the sequence of their components, e.g. `["foo", "bar"]`, for use with
+/// `#[serde(with = "s3_path::serde::components")]`.
+///
+/// Preserves component boundaries exactly, and spares binary formats like postcard or bincode
+/// from splitting keys on '/' again. Deserialization fully validates every component.
+///
+/// ```
+/// use s3_path::S3PathBuf;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Export {
+///     #[serde(with = "s3_path::serde::components")]
+///     target: S3PathBuf,
+/// }
+///
+/// let export = Export {
+///     target: S3PathBuf::try_from_str("exports/2024.csv").unwrap(),
+/// };
+/// let json = serde_json::to_string(&export).unwrap();
+/// assert_eq!(json, r#"{"target":["exports","2024.csv"]}"#);
+/// assert!(serde_json::from_str::<Export>(r#"{"target":["a/b"]}"#).is_err());
+/// ```
+pub mod components {
+    use crate::{validation, S3Path, S3PathBuf};
+    use std::borrow::Cow;
+
+    /// Serializes `path` as a sequence of its components.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the serializer.
+    pub fn serialize<S: ::serde::Serializer>(
+        path: &S3Path<'_>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(path.components_cow())
+    }
+
+    /// Deserializes a path from a sequence of components, validating each fully.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when the input is no sequence of strings, or when any component
+    /// - is empty
+    /// - contains characters other than: ascii alphanumeric characters, '-', '_' and '.'
+    /// - is equal to `.` or `..`
+    pub fn deserialize<'de, D: ::serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<S3PathBuf, D::Error> {
+        let components = <Vec<String> as ::serde::Deserialize>::deserialize(deserializer)?;
+        for component in &components {
+            validation::validate_component(component).map_err(::serde::de::Error::custom)?;
+        }
+        Ok(S3PathBuf {
+            components: components.into_iter().map(Cow::Owned).collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{S3Path, S3PathBuf};
+    use assertr::prelude::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn round_trips_through_rendered_key() {
+        let path = S3PathBuf::try_from_str("foo/bar.txt").unwrap();
+        let json = serde_json::to_string(&path).unwrap();
+        assert_that(json.clone()).is_equal_to("\"foo/bar.txt\"".to_owned());
+        assert_that(serde_json::from_str::<S3PathBuf>(&json))
+            .is_ok()
+            .is_equal_to(path);
+    }
+
+    #[test]
+    fn serializes_borrowed_paths_like_owned_paths() {
+        #[derive(serde::Serialize)]
+        struct Payload<'a> {
+            path: &'a S3Path<'a>,
+        }
+
+        let owned = S3PathBuf::try_from_str("foo/bar").unwrap();
+        let payload = Payload {
+            path: owned.as_path(),
+        };
+        assert_that(serde_json::to_string(&payload))
+            .is_ok()
+            .is_equal_to(r#"{"path":"foo/bar"}"#.to_owned());
+    }
+
+    #[test]
+    fn works_as_map_key() {
+        let map = BTreeMap::from([
+            (S3PathBuf::try_from_str("a/b").unwrap(), 1),
+            (S3PathBuf::try_from_str("a-b").unwrap(), 2),
+        ]);
+        let json = serde_json::to_string(&map).unwrap();
+        assert_that(json.as_str()).is_equal_to(r#"{"a-b":2,"a/b":1}"#);
+        assert_that(serde_json::from_str::<BTreeMap<S3PathBuf, i32>>(&json))
+            .is_ok()
+            .is_equal_to(map);
+        assert_that(serde_json::from_str::<BTreeMap<S3PathBuf, i32>>(
+            r#"{"a b":1}"#,
+        ))
+        .is_err();
+    }
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Components {
+        #[serde(with = "crate::serde::components")]
+        path: S3PathBuf,
+    }
+
+    #[test]
+    fn round_trips_component_sequences() {
+        let value = Components {
+            path: S3PathBuf::try_from_str("foo/bar.txt").unwrap(),
+        };
+        let json = serde_json::to_string(&value).unwrap();
+        assert_that(json.as_str()).is_equal_to(r#"{"path":["foo","bar.txt"]}"#);
+        assert_that(serde_json::from_str::<Components>(&json))
+            .is_ok()
+            .is_equal_to(value);
+        assert_that(serde_json::from_str::<Components>(r#"{"path":[]}"#))
+            .is_ok()
+            .is_equal_to(Components {
+                path: S3PathBuf::new(),
+            });
+        for json in [r#"{"path":["a/b"]}"#, r#"{"path":[""]}"#, r#"{"path":"a"}"#] {
+            assert_that(serde_json::from_str::<Components>(json)).is_err();
+        }
+    }
+
+    #[test]
+    fn parses_keys_like_try_from_str() {
+        assert_that(serde_json::from_str::<S3PathBuf>("\"/foo//bar/\""))
+            .is_ok()
+            .has_display_value("foo/bar");
+        assert_that(serde_json::from_str::<S3PathBuf>("\"foo/..\"")).is_err();
+        assert_that(serde_json::from_str::<S3PathBuf>("42")).is_err();
+    }
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Strict {
+        #[serde(with = "crate::serde::strict")]
+        path: S3PathBuf,
+    }
+
+    #[test]
+    fn strict_rejects_non_canonical_keys() {
+        let value = Strict {
+            path: S3PathBuf::try_from_str("foo/bar").unwrap(),
+        };
+        let json = serde_json::to_string(&value).unwrap();
+        assert_that(json.as_str()).is_equal_to(r#"{"path":"foo/bar"}"#);
+        assert_that(serde_json::from_str::<Strict>(&json))
+            .is_ok()
+            .is_equal_to(value);
+        for json in [
+            r#"{"path":"foo//bar"}"#,
+            r#"{"path":"/foo"}"#,
+            r#"{"path":"foo/.."}"#,
+        ] {
+            assert_that(serde_json::from_str::<Strict>(json)).is_err();
+        }
+    }
+}
@@ -0,0 +1,138 @@
+//! Adapters for [`serde_with`], choosing the representation of paths nested in other types.
+//!
+//! - [`AsKeyString`] represents a path as its rendered key, e.g. `"foo/bar"`, just like the
+//!   `Serialize` implementation of [`S3PathBuf`]. Combined with `serde_with::Map`, a list of
+//!   `(S3PathBuf, V)` entries becomes a JSON object keyed by the rendered keys.
+//! - [`AsComponentSeq`] represents a path as the sequence of its components, e.g.
+//!   `["foo", "bar"]`, preserving component boundaries without splitting on '/'.
+//!
+//! ```
+//! use s3_path::serde_with::{AsComponentSeq, AsKeyString};
+//! use s3_path::S3PathBuf;
+//! use serde::{Deserialize, Serialize};
+//! use serde_with::{serde_as, Map};
+//!
+//! #[serde_as]
+//! #[derive(Serialize, Deserialize)]
+//! struct Index {
+//!     #[serde_as(as = "AsComponentSeq")]
+//!     root: S3PathBuf,
+//!     #[serde_as(as = "Map<AsKeyString, _>")]
+//!     sizes: Vec<(S3PathBuf, u64)>,
+//! }
+//!
+//! let index = Index {
+//!     root: S3PathBuf::try_from_str("data").unwrap(),
+//!     sizes: vec![(S3PathBuf::try_from_str("data/a.csv").unwrap(), 42)],
+//! };
+//! assert_eq!(
+//!     serde_json::to_string(&index).unwrap(),
+//!     r#"{"root":["data"],"sizes":{"data/a.csv":42}}"#,
+//! );
+//! ```
+
+use crate::{S3Path, S3PathBuf};
+use ::serde_with::{DeserializeAs, SerializeAs};
+
+/// Represents a path as its rendered key. Deserialization uses [`S3PathBuf::try_from_str`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AsKeyString;
+
+impl SerializeAs<S3PathBuf> for AsKeyString {
+    fn serialize_as<S: ::serde::Serializer>(
+        source: &S3PathBuf,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(source)
+    }
+}
+
+impl SerializeAs<S3Path<'_>> for AsKeyString {
+    fn serialize_as<S: ::serde::Serializer>(
+        source: &S3Path<'_>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(source)
+    }
+}
+
+impl<'de> DeserializeAs<'de, S3PathBuf> for AsKeyString {
+    fn deserialize_as<D: ::serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<S3PathBuf, D::Error> {
+        ::serde::Deserialize::deserialize(deserializer)
+    }
+}
+
+/// Represents a path as the sequence of its components. Deserialization fully validates every
+/// component.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AsComponentSeq;
+
+impl SerializeAs<S3PathBuf> for AsComponentSeq {
+    fn serialize_as<S: ::serde::Serializer>(
+        source: &S3PathBuf,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        crate::serde::components::serialize(source, serializer)
+    }
+}
+
+impl SerializeAs<S3Path<'_>> for AsComponentSeq {
+    fn serialize_as<S: ::serde::Serializer>(
+        source: &S3Path<'_>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        crate::serde::components::serialize(source, serializer)
+    }
+}
+
+impl<'de> DeserializeAs<'de, S3PathBuf> for AsComponentSeq {
+    fn deserialize_as<D: ::serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<S3PathBuf, D::Error> {
+        crate::serde::components::deserialize(deserializer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::serde_with::{AsComponentSeq, AsKeyString};
+    use crate::S3PathBuf;
+    use assertr::prelude::*;
+    use serde::{Deserialize, Serialize};
+    use serde_with::{serde_as, Map};
+
+    #[serde_as]
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Paths {
+        #[serde_as(as = "Vec<AsComponentSeq>")]
+        seq: Vec<S3PathBuf>,
+        #[serde_as(as = "Map<AsKeyString, _>")]
+        map: Vec<(S3PathBuf, u32)>,
+    }
+
+    #[test]
+    fn round_trips_component_sequences_and_maps() {
+        let paths = Paths {
+            seq: vec![S3PathBuf::new(), S3PathBuf::try_from_str("a/b").unwrap()],
+            map: vec![(S3PathBuf::try_from_str("c/d").unwrap(), 1)],
+        };
+        let json = serde_json::to_string(&paths).unwrap();
+        assert_that(json.as_str()).is_equal_to(r#"{"seq":[[],["a","b"]],"map":{"c/d":1}}"#);
+        assert_that(serde_json::from_str::<Paths>(&json))
+            .is_ok()
+            .is_equal_to(paths);
+    }
+
+    #[test]
+    fn rejects_invalid_components() {
+        for json in [
+            r#"{"seq":[["a/b"]],"map":{}}"#,
+            r#"{"seq":[[".."]],"map":{}}"#,
+            r#"{"seq":[],"map":{"a/../b":1}}"#,
+        ] {
+            assert_that(serde_json::from_str::<Paths>(json)).is_err();
+        }
+    }
+}
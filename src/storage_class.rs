@@ -0,0 +1,196 @@
+//! Declarative routing of objects to storage classes.
+//!
+//! ```
+//! use s3_path::glob::Glob;
+//! use s3_path::storage_class::{StorageClass, StorageClassRouter};
+//! use s3_path::S3PathBuf;
+//!
+//! let mut router = StorageClassRouter::new(StorageClass::Standard);
+//! router
+//!     .prefix(S3PathBuf::try_from_str("backups").unwrap(), StorageClass::DeepArchive)
+//!     .glob(Glob::parse("tenants/*/logs/**").unwrap(), StorageClass::StandardIa)
+//!     .extension("parquet", StorageClass::IntelligentTiering);
+//!
+//! let route = |key: &str| router.route(&S3PathBuf::try_from_str(key).unwrap());
+//! assert_eq!(route("backups/2024/db.tar"), StorageClass::DeepArchive);
+//! assert_eq!(route("tenants/acme/logs/app.log"), StorageClass::StandardIa);
+//! assert_eq!(route("tenants/acme/data.parquet"), StorageClass::IntelligentTiering);
+//! assert_eq!(route("tenants/acme/data.csv"), StorageClass::Standard);
+//! ```
+
+use crate::glob::Glob;
+use crate::{S3Path, S3PathBuf};
+
+/// The storage classes of Amazon S3.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum StorageClass {
+    /// `STANDARD`, for frequently accessed data.
+    #[default]
+    Standard,
+
+    /// `REDUCED_REDUNDANCY`, a legacy class for noncritical data.
+    ReducedRedundancy,
+
+    /// `STANDARD_IA`, for infrequently accessed data.
+    StandardIa,
+
+    /// `ONEZONE_IA`, for infrequently accessed, reproducible data stored in a single zone.
+    OnezoneIa,
+
+    /// `INTELLIGENT_TIERING`, moving data between access tiers automatically.
+    IntelligentTiering,
+
+    /// `GLACIER`, Glacier Flexible Retrieval.
+    Glacier,
+
+    /// `GLACIER_IR`, Glacier Instant Retrieval.
+    GlacierIr,
+
+    /// `DEEP_ARCHIVE`, for long-term archives rarely read.
+    DeepArchive,
+
+    /// `EXPRESS_ONEZONE`, for latency-sensitive data in directory buckets.
+    ExpressOnezone,
+}
+
+impl StorageClass {
+    /// Returns the name of this storage class as used by the S3 API, e.g. `STANDARD_IA`.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            StorageClass::Standard => "STANDARD",
+            StorageClass::ReducedRedundancy => "REDUCED_REDUNDANCY",
+            StorageClass::StandardIa => "STANDARD_IA",
+            StorageClass::OnezoneIa => "ONEZONE_IA",
+            StorageClass::IntelligentTiering => "INTELLIGENT_TIERING",
+            StorageClass::Glacier => "GLACIER",
+            StorageClass::GlacierIr => "GLACIER_IR",
+            StorageClass::DeepArchive => "DEEP_ARCHIVE",
+            StorageClass::ExpressOnezone => "EXPRESS_ONEZONE",
+        }
+    }
+}
+
+impl std::fmt::Display for StorageClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The condition of a routing rule.
+#[derive(Debug, Clone)]
+enum Route {
+    Prefix(S3PathBuf),
+    Glob(Glob),
+    Extension(String),
+}
+
+impl Route {
+    fn matches(&self, path: &S3Path<'_>) -> bool {
+        match self {
+            Route::Prefix(prefix) => path.starts_with(prefix),
+            Route::Glob(glob) => glob.matches(path),
+            Route::Extension(extension) => path
+                .0
+                .last()
+                .and_then(|last| {
+                    last.rfind('.')
+                        .filter(|dot| *dot > 0)
+                        .map(|dot| &last[dot + 1..])
+                })
+                .is_some_and(|it| it == extension),
+        }
+    }
+}
+
+/// Rules assigning storage classes to paths. Rules are evaluated in the order they were added,
+/// the first matching rule wins.
+#[derive(Debug, Clone)]
+pub struct StorageClassRouter {
+    rules: Vec<(Route, StorageClass)>,
+    default: StorageClass,
+}
+
+impl StorageClassRouter {
+    /// Creates a router assigning `default` to all paths matching no rule.
+    #[must_use]
+    pub fn new(default: StorageClass) -> Self {
+        Self {
+            rules: Vec::new(),
+            default,
+        }
+    }
+
+    /// Routes all paths starting with `prefix` to `class`.
+    pub fn prefix(&mut self, prefix: S3PathBuf, class: StorageClass) -> &mut Self {
+        self.rules.push((Route::Prefix(prefix), class));
+        self
+    }
+
+    /// Routes all paths matching `glob` to `class`.
+    pub fn glob(&mut self, glob: Glob, class: StorageClass) -> &mut Self {
+        self.rules.push((Route::Glob(glob), class));
+        self
+    }
+
+    /// Routes all paths whose last component has the given `extension` (without the '.') to
+    /// `class`. A leading '.', as in `.gitignore`, does not start an extension.
+    pub fn extension(&mut self, extension: impl Into<String>, class: StorageClass) -> &mut Self {
+        self.rules.push((Route::Extension(extension.into()), class));
+        self
+    }
+
+    /// Returns the storage class of the first rule matching `path`, or the default.
+    #[must_use]
+    pub fn route(&self, path: &S3Path<'_>) -> StorageClass {
+        self.rules
+            .iter()
+            .find(|(route, _)| route.matches(path))
+            .map_or(self.default, |(_, class)| *class)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::glob::Glob;
+    use crate::storage_class::{StorageClass, StorageClassRouter};
+    use crate::S3PathBuf;
+    use assertr::prelude::*;
+
+    fn route(router: &StorageClassRouter, key: &str) -> StorageClass {
+        router.route(&S3PathBuf::try_from_str(key).unwrap())
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let mut router = StorageClassRouter::new(StorageClass::Standard);
+        router.extension("log", StorageClass::Glacier).prefix(
+            S3PathBuf::try_from_str("logs").unwrap(),
+            StorageClass::StandardIa,
+        );
+        assert_that(route(&router, "logs/a.log")).is_equal_to(StorageClass::Glacier);
+        assert_that(route(&router, "logs/a.txt")).is_equal_to(StorageClass::StandardIa);
+        assert_that(route(&router, "a.txt")).is_equal_to(StorageClass::Standard);
+    }
+
+    #[test]
+    fn extensions_ignore_leading_dots_and_directories() {
+        let mut router = StorageClassRouter::new(StorageClass::Standard);
+        router.extension("tmp", StorageClass::OnezoneIa);
+        assert_that(route(&router, "a/b.tmp")).is_equal_to(StorageClass::OnezoneIa);
+        assert_that(route(&router, "a/.tmp")).is_equal_to(StorageClass::Standard);
+        assert_that(route(&router, "a.tmp/b")).is_equal_to(StorageClass::Standard);
+    }
+
+    #[test]
+    fn globs_route_matching_paths() {
+        let mut router = StorageClassRouter::new(StorageClass::Standard);
+        router.glob(
+            Glob::parse("**/archive/*").unwrap(),
+            StorageClass::GlacierIr,
+        );
+        assert_that(route(&router, "x/y/archive/z")).is_equal_to(StorageClass::GlacierIr);
+        assert_that(route(&router, "x/archive/y/z")).is_equal_to(StorageClass::Standard);
+        assert_that(StorageClass::GlacierIr).has_display_value("GLACIER_IR");
+    }
+}
@@ -0,0 +1,464 @@
+//! A minimal abstraction over object storage, letting utilities of this crate work against any
+//! backend, like an S3 client, an S3-compatible test server or the in-memory [`MemoryBucket`].
+//!
+//! This crate does not depend on an async runtime or an S3 client. Implement
+//! [`ObjectStoreLike`] for the client of your choice:
+//!
+//! ```
+//! use s3_path::store::ObjectStoreLike;
+//! use s3_path::{S3Path, S3PathBuf};
+//! use std::collections::BTreeMap;
+//! use std::convert::Infallible;
+//! use std::sync::Mutex;
+//!
+//! #[derive(Default)]
+//! struct Store(Mutex<BTreeMap<S3PathBuf, Vec<u8>>>);
+//!
+//! impl ObjectStoreLike for Store {
+//!     type Error = Infallible;
+//!
+//!     async fn get(&self, path: &S3Path<'_>) -> Result<Option<Vec<u8>>, Infallible> {
+//!         Ok(self.0.lock().unwrap().get(path).cloned())
+//!     }
+//!
+//!     async fn put(&self, path: &S3Path<'_>, body: Vec<u8>) -> Result<(), Infallible> {
+//!         self.0.lock().unwrap().insert(path.to_owned(), body);
+//!         Ok(())
+//!     }
+//!
+//!     async fn list(&self, prefix: &S3Path<'_>) -> Result<Vec<S3PathBuf>, Infallible> {
+//!         let objects = self.0.lock().unwrap();
+//!         Ok(objects.keys().filter(|key| key.starts_with(prefix)).cloned().collect())
+//!     }
+//!
+//!     async fn delete(&self, path: &S3Path<'_>) -> Result<(), Infallible> {
+//!         self.0.lock().unwrap().remove(path);
+//!         Ok(())
+//!     }
+//! }
+//! ```
+//!
+//! Sync plans (see [`SyncPlan::execute`]) and layout migrations (see
+//! [`LayoutRegistry::migrate_objects`]) can then be executed against that store. For testing
+//! pagination logic, [`MemoryBucket::list_page`] lists pages just like S3 does.
+
+use crate::error::StoreError;
+use crate::listing::{list_sorted, ListPage, ListRequest};
+use crate::migrate::LayoutRegistry;
+use crate::renames::{SyncAction, SyncPlan};
+use crate::{S3Path, S3PathBuf};
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::future::Future;
+use std::ops::Bound;
+use std::sync::{Mutex, MutexGuard};
+
+/// An object store addressed by paths, e.g. an S3 bucket.
+///
+/// The returned futures must be `Send`, so that they can be spawned onto multithreaded runtimes.
+pub trait ObjectStoreLike {
+    /// The error returned by all operations of this store.
+    type Error;
+
+    /// Returns the body of the object stored under `path`, or `None` if there is no such object.
+    fn get(
+        &self,
+        path: &S3Path<'_>,
+    ) -> impl Future<Output = Result<Option<Vec<u8>>, Self::Error>> + Send;
+
+    /// Stores `body` under `path`, replacing any existing object.
+    fn put(
+        &self,
+        path: &S3Path<'_>,
+        body: Vec<u8>,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Lists the paths of all objects starting with the components of `prefix`, including an
+    /// object stored under `prefix` itself. The empty prefix lists all objects.
+    ///
+    /// Paths are returned in the order in which S3 lists keys.
+    fn list(
+        &self,
+        prefix: &S3Path<'_>,
+    ) -> impl Future<Output = Result<Vec<S3PathBuf>, Self::Error>> + Send;
+
+    /// Deletes the object stored under `path`. Deleting a missing object is no error, just like
+    /// in S3.
+    fn delete(&self, path: &S3Path<'_>) -> impl Future<Output = Result<(), Self::Error>> + Send;
+}
+
+/// An in-memory store for tests, never failing.
+///
+/// ```
+/// use s3_path::store::MemoryBucket;
+/// use s3_path::S3PathBuf;
+///
+/// let path = |key: &str| S3PathBuf::try_from_str(key).unwrap();
+/// let bucket = MemoryBucket::from_iter([(path("b"), vec![1]), (path("a/c"), vec![2])]);
+/// assert_eq!(bucket.paths(), [path("a/c"), path("b")]);
+/// assert_eq!(bucket.object(&path("b")), Some(vec![1]));
+/// ```
+#[derive(Debug, Default)]
+pub struct MemoryBucket {
+    /// Objects keyed by their rendered key, so that listings can seek to any key.
+    objects: Mutex<BTreeMap<String, (S3PathBuf, Vec<u8>)>>,
+}
+
+impl MemoryBucket {
+    /// Creates an empty bucket.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of objects in this bucket.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns true if this bucket contains no objects.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Returns the paths of all objects, in the order in which S3 lists keys.
+    #[must_use]
+    pub fn paths(&self) -> Vec<S3PathBuf> {
+        self.lock().values().map(|(path, _)| path.clone()).collect()
+    }
+
+    /// Returns a copy of the body of the object stored under `path`, without going through the
+    /// async [`ObjectStoreLike`] API.
+    #[must_use]
+    pub fn object(&self, path: &S3Path<'_>) -> Option<Vec<u8>> {
+        self.lock()
+            .get(&path.to_string())
+            .map(|(_, body)| body.clone())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BTreeMap<String, (S3PathBuf, Vec<u8>)>> {
+        // No operation panics while holding the lock, so the map is never left inconsistent.
+        self.objects
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+impl FromIterator<(S3PathBuf, Vec<u8>)> for MemoryBucket {
+    fn from_iter<T: IntoIterator<Item = (S3PathBuf, Vec<u8>)>>(iter: T) -> Self {
+        Self {
+            objects: Mutex::new(
+                iter.into_iter()
+                    .map(|(path, body)| (path.to_string(), (path, body)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl ObjectStoreLike for MemoryBucket {
+    type Error = Infallible;
+
+    async fn get(&self, path: &S3Path<'_>) -> Result<Option<Vec<u8>>, Infallible> {
+        Ok(self.object(path))
+    }
+
+    async fn put(&self, path: &S3Path<'_>, body: Vec<u8>) -> Result<(), Infallible> {
+        self.lock()
+            .insert(path.to_string(), (path.to_owned(), body));
+        Ok(())
+    }
+
+    async fn list(&self, prefix: &S3Path<'_>) -> Result<Vec<S3PathBuf>, Infallible> {
+        Ok(self
+            .lock()
+            .values()
+            .map(|(path, _)| path)
+            .filter(|path| path.starts_with(prefix))
+            .cloned()
+            .collect())
+    }
+
+    async fn delete(&self, path: &S3Path<'_>) -> Result<(), Infallible> {
+        self.lock().remove(&path.to_string());
+        Ok(())
+    }
+}
+
+impl MemoryBucket {
+    /// Lists a single page of keys like `ListObjectsV2`, see
+    /// [`list_objects_v2`](crate::listing::list_objects_v2). Seeks to the start of the page and
+    /// past common prefixes, instead of scanning all keys.
+    ///
+    /// ```
+    /// use s3_path::listing::ListRequest;
+    /// use s3_path::store::MemoryBucket;
+    /// use s3_path::S3PathBuf;
+    ///
+    /// let bucket = MemoryBucket::from_iter(
+    ///     ["a/1", "a/2", "b"].map(|key| (S3PathBuf::try_from_str(key).unwrap(), vec![])),
+    /// );
+    /// let page = bucket.list_page(&ListRequest {
+    ///     delimiter: Some("/".to_owned()),
+    ///     ..ListRequest::default()
+    /// });
+    /// assert_eq!(page.common_prefixes, ["a/"]);
+    /// assert_eq!(page.contents, [S3PathBuf::try_from_str("b").unwrap()]);
+    /// ```
+    #[must_use]
+    pub fn list_page(&self, request: &ListRequest) -> ListPage {
+        let objects = self.lock();
+        list_sorted(
+            |from| {
+                objects
+                    .range::<str, _>((Bound::Included(from), Bound::Unbounded))
+                    .next()
+                    .map(|(key, (path, _))| (key.as_str(), path))
+            },
+            request,
+        )
+    }
+}
+
+/// Copies the object at `from` in `source` to `to` in `target`.
+async fn copy<S: ObjectStoreLike>(
+    source: &S,
+    from: &S3Path<'_>,
+    target: &S,
+    to: &S3Path<'_>,
+) -> Result<(), StoreError<S::Error>> {
+    let body = source
+        .get(from)
+        .await
+        .map_err(StoreError::Store)?
+        .ok_or_else(|| StoreError::MissingObject {
+            path: from.to_string(),
+        })?;
+    target.put(to, body).await.map_err(StoreError::Store)
+}
+
+impl SyncPlan {
+    /// Executes the [`actions`](SyncPlan::actions) of this plan against `target`, holding the
+    /// objects of the old manifest. Renamed objects are copied within `target`, uploaded objects
+    /// are read from `source`. Returns the number of executed actions.
+    ///
+    /// # Errors
+    ///
+    /// Stops at the first failing action, returning `Err` when
+    /// - an operation of either store fails
+    /// - an object to copy or upload does not exist
+    pub async fn execute<S: ObjectStoreLike>(
+        &self,
+        source: &S,
+        target: &S,
+    ) -> Result<usize, StoreError<S::Error>> {
+        let actions = self.actions();
+        for action in &actions {
+            match action {
+                SyncAction::Copy { from, to } => copy(target, from, target, to).await?,
+                SyncAction::Upload(path) => copy(source, path, target, path).await?,
+                SyncAction::Delete(path) => {
+                    target.delete(path).await.map_err(StoreError::Store)?;
+                }
+            }
+        }
+        Ok(actions.len())
+    }
+}
+
+impl LayoutRegistry {
+    /// Moves all objects below `prefix` not yet in the layout of `to_version` to their upgraded
+    /// paths (see [`upgrade`](LayoutRegistry::upgrade)). Returns the `(from, to)` paths of all
+    /// moved objects, in listing order.
+    ///
+    /// All paths are upgraded before the first object is moved. Each object is copied before it
+    /// is deleted, so no data is lost if the migration is interrupted.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when
+    /// - an operation of the store fails
+    /// - a listed path cannot be upgraded, before any object was moved
+    /// - a listed object was deleted concurrently
+    pub async fn migrate_objects<S: ObjectStoreLike>(
+        &self,
+        store: &S,
+        prefix: &S3Path<'_>,
+        to_version: u32,
+    ) -> Result<Vec<(S3PathBuf, S3PathBuf)>, StoreError<S::Error>> {
+        let mut moves = Vec::new();
+        for path in store.list(prefix).await.map_err(StoreError::Store)? {
+            if self.detect_version(&path) != Some(to_version) {
+                let upgraded = self
+                    .upgrade(&path, to_version)
+                    .map_err(StoreError::Migration)?;
+                moves.push((path, upgraded));
+            }
+        }
+        for (from, to) in &moves {
+            copy(store, from, store, to).await?;
+            store.delete(from).await.map_err(StoreError::Store)?;
+        }
+        Ok(moves)
+    }
+}
+
+/// Runs `future` to completion on the current thread. Only meant for tests, as it busy-polls.
+#[cfg(test)]
+pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+
+    struct Noop;
+    impl Wake for Noop {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    let waker = Waker::from(Arc::new(Noop));
+    let mut context = Context::from_waker(&waker);
+    let mut future = std::pin::pin!(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+        std::thread::yield_now();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::StoreError;
+    use crate::listing::{ListPage, ListRequest};
+    use crate::manifest::{Manifest, ObjectMeta};
+    use crate::migrate::LayoutRegistry;
+    use crate::renames::{detect_renames, RenameMatcher};
+    use crate::store::{block_on, MemoryBucket, ObjectStoreLike};
+    use crate::S3PathBuf;
+    use assertr::prelude::*;
+    use std::time::SystemTime;
+
+    fn path(key: &str) -> S3PathBuf {
+        S3PathBuf::try_from_str(key).unwrap()
+    }
+
+    fn bucket(objects: &[(&str, u8)]) -> MemoryBucket {
+        objects
+            .iter()
+            .map(|(key, byte)| (path(key), vec![*byte]))
+            .collect()
+    }
+
+    #[test]
+    fn memory_bucket_gets_puts_lists_and_deletes() {
+        let bucket = bucket(&[("a/b", 1), ("a-b", 2), ("a", 3), ("c", 4)]);
+        block_on(async {
+            assert_that(bucket.get(&path("a/b")).await)
+                .is_ok()
+                .is_equal_to(Some(vec![1]));
+            assert_that(bucket.get(&path("a/x")).await)
+                .is_ok()
+                .is_none();
+            assert_that(bucket.list(&path("a")).await)
+                .is_ok()
+                .is_equal_to(vec![path("a"), path("a/b")]);
+
+            bucket.put(&path("a/b"), vec![5]).await.unwrap();
+            bucket.delete(&path("c")).await.unwrap();
+            bucket.delete(&path("c")).await.unwrap();
+        });
+        assert_that(bucket.object(&path("a/b"))).is_equal_to(Some(vec![5]));
+        assert_that(bucket.paths()).is_equal_to(vec![path("a"), path("a-b"), path("a/b")]);
+        assert_that(bucket.len()).is_equal_to(3);
+    }
+
+    #[test]
+    fn executes_sync_plans() {
+        let meta = |etag: &str| ObjectMeta {
+            size: 1,
+            etag: etag.to_string(),
+            last_modified: SystemTime::UNIX_EPOCH,
+        };
+        let old = Manifest::from_iter([(path("inbox/a"), meta("1")), (path("x"), meta("9"))]);
+        let new = Manifest::from_iter([(path("done/a"), meta("1")), (path("b"), meta("2"))]);
+        let plan = detect_renames(&old, &new, &RenameMatcher::default());
+
+        let source = bucket(&[("done/a", 1), ("b", 2)]);
+        let target = bucket(&[("inbox/a", 1), ("x", 9)]);
+        assert_that(block_on(plan.execute(&source, &target)))
+            .is_ok()
+            .is_equal_to(4);
+        assert_that(target.paths()).is_equal_to(source.paths());
+        assert_that(target.object(&path("b"))).is_equal_to(Some(vec![2]));
+
+        let result = block_on(plan.execute(&MemoryBucket::new(), &MemoryBucket::new()));
+        assert_that(matches!(result, Err(StoreError::MissingObject { path }) if path == "inbox/a"))
+            .is_true();
+    }
+
+    #[test]
+    fn migrates_objects_to_layout_version() {
+        let mut registry = LayoutRegistry::new();
+        registry
+            .register(1, "{tenant}/{file}")
+            .unwrap()
+            .register(2, "v2/{tenant}/{file}")
+            .unwrap();
+        let store = bucket(&[("acme/a.csv", 1), ("v2/acme/b.csv", 2)]);
+
+        let moves = block_on(registry.migrate_objects(&store, &S3PathBuf::new(), 2));
+        assert_that(moves)
+            .is_ok()
+            .is_equal_to(vec![(path("acme/a.csv"), path("v2/acme/a.csv"))]);
+        assert_that(store.paths()).is_equal_to(vec![path("v2/acme/a.csv"), path("v2/acme/b.csv")]);
+        assert_that(store.object(&path("v2/acme/a.csv"))).is_equal_to(Some(vec![1]));
+
+        let store = bucket(&[("acme/a.csv", 1), ("x/y/z", 2)]);
+        let result = block_on(registry.migrate_objects(&store, &S3PathBuf::new(), 2));
+        assert_that(matches!(result, Err(StoreError::Migration(_)))).is_true();
+        assert_that(store.len()).is_equal_to(2);
+    }
+
+    #[test]
+    fn list_page_groups_keys_by_delimiter_after_prefix() {
+        let bucket = bucket(&[
+            ("logs/2024-01/a", 0),
+            ("logs/2024-01/b", 0),
+            ("logs/2024-02", 0),
+            ("logs/2025/x", 0),
+            ("other", 0),
+        ]);
+        let page = bucket.list_page(&ListRequest {
+            prefix: "logs/2024-".to_owned(),
+            delimiter: Some("/".to_owned()),
+            ..ListRequest::default()
+        });
+        assert_that(page).is_equal_to(ListPage {
+            contents: vec![path("logs/2024-02")],
+            common_prefixes: vec!["logs/2024-01/".to_owned()],
+            next_continuation_token: None,
+        });
+    }
+
+    #[test]
+    fn list_page_continues_after_common_prefixes() {
+        let bucket = bucket(&[("a/1", 0), ("a/2", 0), ("a0", 0), ("b/1", 0), ("c", 0)]);
+        let mut request = ListRequest {
+            delimiter: Some("/".to_owned()),
+            max_keys: 2,
+            ..ListRequest::default()
+        };
+        let first = bucket.list_page(&request);
+        assert_that(first.contents.clone()).is_equal_to(vec![path("a0")]);
+        assert_that(first.common_prefixes.clone()).is_equal_to(vec!["a/".to_owned()]);
+
+        request.continuation_token = first.next_continuation_token;
+        let second = bucket.list_page(&request);
+        assert_that(second).is_equal_to(ListPage {
+            contents: vec![path("c")],
+            common_prefixes: vec!["b/".to_owned()],
+            next_continuation_token: None,
+        });
+    }
+}
@@ -0,0 +1,220 @@
+//! Deriving S3 object tags from the components of annotated paths.
+//!
+//! An [`ObjectTagging`] maps [`ComponentTag`]s to object tag keys. Deriving tags from an
+//! [`AnnotatedS3Path`] yields one object tag per mapped component tag present in the path.
+//!
+//! ```
+//! use s3_path::annotate::{ComponentTag, Layout};
+//! use s3_path::grammar::KeyGrammar;
+//! use s3_path::tagging::ObjectTagging;
+//! use s3_path::S3PathBuf;
+//!
+//! let layout = Layout::infer(KeyGrammar::parse("{any}/{year}/{month}/{any}").unwrap());
+//! let mut tagging = ObjectTagging::new();
+//! tagging
+//!     .map(ComponentTag::Date, "period")
+//!     .unwrap()
+//!     .constant("source", "ingest")
+//!     .unwrap();
+//!
+//! let path = S3PathBuf::try_from_str("acme/2024/01/data.csv").unwrap();
+//! let tags = tagging.derive(&layout.annotate(&path).unwrap()).unwrap();
+//! assert_eq!(
+//!     tags,
+//!     [
+//!         ("period".to_string(), "2024/01".to_string()),
+//!         ("source".to_string(), "ingest".to_string()),
+//!     ]
+//! );
+//! ```
+
+use crate::annotate::{AnnotatedS3Path, ComponentTag};
+use crate::error::InvalidObjectTag;
+
+/// Maximum number of tags of a single object.
+const MAX_TAGS: usize = 10;
+
+/// Maximum length of a tag key, in characters.
+const MAX_KEY_LEN: usize = 128;
+
+/// Maximum length of a tag value, in characters.
+const MAX_VALUE_LEN: usize = 256;
+
+/// Rules deriving object tags from annotated paths. See the [module documentation](crate::tagging).
+#[derive(Debug, Clone, Default)]
+pub struct ObjectTagging {
+    mapped: Vec<(ComponentTag, String)>,
+    constants: Vec<(String, String)>,
+}
+
+impl ObjectTagging {
+    /// Creates a tagging deriving no tags.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Derives the object tag `key` from the components tagged with `tag`. Multiple such
+    /// components, like the parts of a `YYYY/MM/DD` date, are joined by '/'.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when `key` is not a valid tag key.
+    pub fn map(
+        &mut self,
+        tag: ComponentTag,
+        key: impl Into<String>,
+    ) -> Result<&mut Self, InvalidObjectTag> {
+        let key = key.into();
+        check_tag(&key, "")?;
+        self.mapped.push((tag, key));
+        Ok(self)
+    }
+
+    /// Adds the object tag `key` with a fixed `value` to all derived tag sets.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when `key` or `value` is not a valid tag key or value.
+    pub fn constant(
+        &mut self,
+        key: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Result<&mut Self, InvalidObjectTag> {
+        let (key, value) = (key.into(), value.into());
+        check_tag(&key, &value)?;
+        self.constants.push((key, value));
+        Ok(self)
+    }
+
+    /// Derives the object tags of `path`, mapped tags first, in the order they were added.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when
+    /// - a derived value exceeds 256 characters
+    /// - a key would be used twice
+    /// - more than 10 tags would be derived
+    pub fn derive(
+        &self,
+        path: &AnnotatedS3Path,
+    ) -> Result<Vec<(String, String)>, InvalidObjectTag> {
+        let mut tags: Vec<(String, String)> = Vec::new();
+        let mapped = self.mapped.iter().filter_map(|(tag, key)| {
+            let value = path.tagged(*tag).collect::<Vec<_>>().join("/");
+            (!value.is_empty()).then(|| (key.clone(), value))
+        });
+        for (key, value) in mapped.chain(self.constants.iter().cloned()) {
+            check_tag(&key, &value)?;
+            if tags.iter().any(|(k, _)| *k == key) {
+                return Err(InvalidObjectTag {
+                    key,
+                    value,
+                    reason: "Duplicate tag key".to_string(),
+                });
+            }
+            if tags.len() == MAX_TAGS {
+                return Err(InvalidObjectTag {
+                    key,
+                    value,
+                    reason: format!("Objects can have at most {MAX_TAGS} tags"),
+                });
+            }
+            tags.push((key, value));
+        }
+        Ok(tags)
+    }
+}
+
+/// Checks the constraints S3 imposes on tag keys and values.
+fn check_tag(key: &str, value: &str) -> Result<(), InvalidObjectTag> {
+    let allowed = |c: char| c.is_alphanumeric() || c.is_whitespace() || "+-=._:/@".contains(c);
+    let reason = if key.is_empty() {
+        Some("Empty tag key".to_string())
+    } else if key.chars().count() > MAX_KEY_LEN {
+        Some(format!("Tag keys must not exceed {MAX_KEY_LEN} characters"))
+    } else if key.starts_with("aws:") {
+        Some("Tag keys must not start with 'aws:'".to_string())
+    } else if value.chars().count() > MAX_VALUE_LEN {
+        Some(format!(
+            "Tag values must not exceed {MAX_VALUE_LEN} characters"
+        ))
+    } else {
+        key.chars()
+            .chain(value.chars())
+            .find(|c| !allowed(*c))
+            .map(|c| format!("Character '{c}' is not allowed"))
+    };
+    match reason {
+        None => Ok(()),
+        Some(reason) => Err(InvalidObjectTag {
+            key: key.to_string(),
+            value: value.to_string(),
+            reason,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::annotate::{ComponentTag, Layout};
+    use crate::grammar::KeyGrammar;
+    use crate::tagging::ObjectTagging;
+    use crate::S3PathBuf;
+    use assertr::prelude::*;
+
+    fn layout() -> Layout {
+        Layout::new(
+            KeyGrammar::parse("tenants/{any}/{any}").unwrap(),
+            [
+                ComponentTag::Opaque,
+                ComponentTag::Tenant,
+                ComponentTag::FileName,
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn rejects_invalid_keys_and_values() {
+        let mut tagging = ObjectTagging::new();
+        assert_that(tagging.map(ComponentTag::Tenant, "")).is_err();
+        assert_that(tagging.map(ComponentTag::Tenant, "aws:tenant")).is_err();
+        assert_that(tagging.constant("a", "b$")).is_err();
+        assert_that(tagging.constant("a", "x".repeat(257))).is_err();
+        assert_that(tagging.constant("team name", "data-eng@acme")).is_ok();
+    }
+
+    #[test]
+    fn skips_tags_absent_from_path() {
+        let mut tagging = ObjectTagging::new();
+        tagging
+            .map(ComponentTag::Tenant, "tenant")
+            .unwrap()
+            .map(ComponentTag::Shard, "shard")
+            .unwrap();
+        let path = S3PathBuf::try_from_str("tenants/acme/a.csv").unwrap();
+        let tags = tagging.derive(&layout().annotate(&path).unwrap()).unwrap();
+        assert_that(tags).is_equal_to(vec![("tenant".to_string(), "acme".to_string())]);
+    }
+
+    #[test]
+    fn rejects_duplicate_keys_and_too_many_tags() {
+        let path = S3PathBuf::try_from_str("tenants/acme/a.csv").unwrap();
+        let annotated = layout().annotate(&path).unwrap();
+
+        let mut tagging = ObjectTagging::new();
+        tagging
+            .map(ComponentTag::Tenant, "t")
+            .unwrap()
+            .constant("t", "x")
+            .unwrap();
+        assert_that(tagging.derive(&annotated)).is_err();
+
+        let mut tagging = ObjectTagging::new();
+        for i in 0..11 {
+            tagging.constant(format!("k{i}"), "v").unwrap();
+        }
+        assert_that(tagging.derive(&annotated)).is_err();
+    }
+}
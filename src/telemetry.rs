@@ -0,0 +1,186 @@
+//! Central observation of validation failures.
+//!
+//! Services wanting to count or alert on rejected keys can register a single
+//! [`ValidationObserver`] using [`set_observer`], instead of wrapping every call site constructing
+//! a path. Call sites can attach context to failures using [`with_context`].
+//!
+//! ```
+//! use s3_path::telemetry::{self, Rejection};
+//! use s3_path::S3PathBuf;
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//!
+//! static REJECTED: AtomicUsize = AtomicUsize::new(0);
+//!
+//! telemetry::set_observer(|rejection: &Rejection<'_>| {
+//!     if rejection.context == Some("upload-handler") {
+//!         REJECTED.fetch_add(1, Ordering::Relaxed);
+//!     }
+//! })
+//! .ok();
+//!
+//! let result = telemetry::with_context("upload-handler", || S3PathBuf::try_from_str("a/../b"));
+//! assert!(result.is_err());
+//! assert_eq!(REJECTED.load(Ordering::Relaxed), 1);
+//! ```
+
+use crate::error::InvalidS3PathComponent;
+use std::cell::{Cell, RefCell};
+use std::sync::OnceLock;
+
+/// A single validation failure, as passed to a [`ValidationObserver`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rejection<'a> {
+    /// The rejected component.
+    pub component: &'a str,
+
+    /// Why the component was rejected.
+    pub reason: &'a str,
+
+    /// The innermost context set using [`with_context`] on the current thread, if any.
+    pub context: Option<&'a str>,
+}
+
+/// Gets notified about every validation failure.
+///
+/// Implemented for all closures taking a [`Rejection`]. Observers are called synchronously on the
+/// failing thread and should therefore return quickly.
+pub trait ValidationObserver: Send + Sync {
+    /// Called once for every validation failure, on the thread the failure occurred on.
+    ///
+    /// Observers may construct paths and use [`with_context`] themselves. Failures occurring while
+    /// an observer runs are not reported again, so that a rejecting observer can not recurse.
+    fn on_rejection(&self, rejection: &Rejection<'_>);
+}
+
+impl<F: Fn(&Rejection<'_>) + Send + Sync> ValidationObserver for F {
+    fn on_rejection(&self, rejection: &Rejection<'_>) {
+        self(rejection);
+    }
+}
+
+static OBSERVER: OnceLock<Box<dyn ValidationObserver>> = OnceLock::new();
+
+thread_local! {
+    static CONTEXT: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    static REPORTING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Registers the process-wide observer.
+///
+/// # Errors
+///
+/// Only one observer can ever be registered. Returns the given observer back if one already was.
+pub fn set_observer(
+    observer: impl ValidationObserver + 'static,
+) -> Result<(), Box<dyn ValidationObserver>> {
+    OBSERVER.set(Box::new(observer))
+}
+
+/// Runs `f`, attaching `context` to all validation failures reported on the current thread while
+/// doing so. Contexts can be nested, in which case the innermost one is reported.
+pub fn with_context<R>(context: impl Into<String>, f: impl FnOnce() -> R) -> R {
+    struct PopOnDrop;
+
+    impl Drop for PopOnDrop {
+        fn drop(&mut self) {
+            CONTEXT.with(|stack| stack.borrow_mut().pop());
+        }
+    }
+
+    CONTEXT.with(|stack| stack.borrow_mut().push(context.into()));
+    let _guard = PopOnDrop;
+    f()
+}
+
+/// Reports `err` to the registered observer, if any, and returns it.
+pub(crate) fn report(err: InvalidS3PathComponent) -> InvalidS3PathComponent {
+    struct ResetOnDrop;
+
+    impl Drop for ResetOnDrop {
+        fn drop(&mut self) {
+            REPORTING.with(|reporting| reporting.set(false));
+        }
+    }
+
+    let Some(observer) = OBSERVER.get() else {
+        return err;
+    };
+    if REPORTING.with(|reporting| reporting.replace(true)) {
+        return err;
+    }
+    let _guard = ResetOnDrop;
+    // The context is cloned, so that the observer can push contexts of its own.
+    let context = CONTEXT.with(|stack| stack.borrow().last().cloned());
+    observer.on_rejection(&Rejection {
+        component: &err.component,
+        reason: &err.reason,
+        context: context.as_deref(),
+    });
+    err
+}
+
+#[cfg(test)]
+mod test {
+    use crate::telemetry::{self, Rejection};
+    use crate::S3PathBuf;
+    use assertr::prelude::*;
+    use std::sync::Mutex;
+
+    static SEEN: Mutex<Vec<(String, Option<String>)>> = Mutex::new(Vec::new());
+    static REENTRANT: Mutex<Vec<Option<String>>> = Mutex::new(Vec::new());
+
+    fn record(rejection: &Rejection<'_>) {
+        if rejection
+            .context
+            .is_some_and(|it| it.starts_with("reentrancy-test"))
+        {
+            REENTRANT
+                .lock()
+                .unwrap()
+                .push(rejection.context.map(str::to_owned));
+            telemetry::with_context("reentrancy-test-nested", || {
+                assert_that(S3PathBuf::try_from_str("..")).is_err();
+            });
+        }
+        // Tests run concurrently, so only failures of this module are recorded.
+        if rejection
+            .context
+            .is_some_and(|it| it.starts_with("telemetry-test"))
+        {
+            SEEN.lock().unwrap().push((
+                rejection.component.to_owned(),
+                rejection.context.map(str::to_owned),
+            ));
+        }
+    }
+
+    #[test]
+    fn observer_receives_failures_with_innermost_context() {
+        telemetry::set_observer(record).ok();
+
+        telemetry::with_context("telemetry-test-outer", || {
+            assert_that(S3PathBuf::try_from_str("foo/.")).is_err();
+            telemetry::with_context("telemetry-test-inner", || {
+                assert_that(S3PathBuf::try_from_str("foo/..")).is_err();
+            });
+            assert_that(S3PathBuf::try_from_str("foo/bar")).is_ok();
+        });
+
+        assert_that(SEEN.lock().unwrap().clone()).contains_exactly([
+            (".".to_owned(), Some("telemetry-test-outer".to_owned())),
+            ("..".to_owned(), Some("telemetry-test-inner".to_owned())),
+        ]);
+    }
+
+    #[test]
+    fn observer_can_validate_within_contexts_without_recursing() {
+        telemetry::set_observer(record).ok();
+
+        telemetry::with_context("reentrancy-test", || {
+            assert_that(S3PathBuf::try_from_str("a/../b")).is_err();
+        });
+
+        assert_that(REENTRANT.lock().unwrap().clone())
+            .contains_exactly([Some("reentrancy-test".to_owned())]);
+    }
+}
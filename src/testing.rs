@@ -0,0 +1,328 @@
+//! Utilities for testing code producing paths.
+
+use crate::fixtures::KeyTree;
+use crate::manifest::Manifest;
+use crate::store::ObjectStoreLike;
+use crate::{concat, S3Path};
+use std::path::Path;
+
+/// Asserts that two paths are equal, accepting anything implementing `AsRef<S3Path>`.
+///
+/// On failure, the panic message points at the first differing component and lists the
+/// components only present in either path, instead of just printing two long keys.
+///
+/// ```
+/// use s3_path::{assert_s3_path_eq, s3_path, s3_path_buf};
+///
+/// let expected = s3_path!("foo", "bar").unwrap();
+/// assert_s3_path_eq!(s3_path_buf!("foo", "bar").unwrap(), expected);
+/// assert_s3_path_eq!(expected, expected, "paths of {} differ", "foo");
+/// ```
+#[macro_export]
+macro_rules! assert_s3_path_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        if let Some(diff) = $crate::testing::component_diff(&$left, &$right) {
+            panic!("assertion `left == right` failed\n{diff}");
+        }
+    };
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        if let Some(diff) = $crate::testing::component_diff(&$left, &$right) {
+            panic!("assertion `left == right` failed: {}\n{diff}", format_args!($($arg)+));
+        }
+    };
+}
+
+/// Describes the differences between `left` and `right` component by component, or returns
+/// `None` if both are equal.
+///
+/// ```
+/// use s3_path::s3_path;
+/// use s3_path::testing::component_diff;
+///
+/// let diff = component_diff(s3_path!("a", "b", "c").unwrap(), s3_path!("a", "x").unwrap());
+/// assert_eq!(
+///     diff.unwrap(),
+///     "paths differ at component 1\n\
+///      left:       a/b/c\n\
+///      right:      a/x\n\
+///      common:     a\n\
+///      left only:  b/c\n\
+///      right only: x\n"
+/// );
+/// ```
+pub fn component_diff<'l, 'r>(
+    left: impl AsRef<S3Path<'l>>,
+    right: impl AsRef<S3Path<'r>>,
+) -> Option<String> {
+    let (left, right) = (left.as_ref(), right.as_ref());
+    let common = left
+        .components()
+        .zip(right.components())
+        .take_while(|(l, r)| l == r)
+        .count();
+    if common == left.len() && common == right.len() {
+        return None;
+    }
+    let tail = |path: &S3Path<'_>| path.components().skip(common).collect::<Vec<_>>().join("/");
+    let common_prefix = left.components().take(common).collect::<Vec<_>>().join("/");
+    Some(format!(
+        "paths differ at component {common}\n\
+         left:       {left}\n\
+         right:      {right}\n\
+         common:     {common_prefix}\n\
+         left only:  {}\n\
+         right only: {}\n",
+        tail(left),
+        tail(right),
+    ))
+}
+
+/// Environment variable which, when set to `1`, makes [`assert_golden`] (re-)write golden files
+/// instead of comparing against them.
+pub const UPDATE_GOLDEN_ENV: &str = "S3_PATH_UPDATE_GOLDEN";
+
+/// Renders `paths` in a canonical text form: one key per line, sorted by bytes and without
+/// duplicates.
+///
+/// ```
+/// use s3_path::testing::to_canonical_text;
+/// use s3_path::s3_path_buf;
+///
+/// let paths = [s3_path_buf!("b").unwrap(), s3_path_buf!("a", "c").unwrap()];
+/// assert_eq!(to_canonical_text(&paths), "a/c\nb\n");
+/// ```
+pub fn to_canonical_text<'p, P: AsRef<S3Path<'p>>>(paths: impl IntoIterator<Item = P>) -> String {
+    let mut keys = paths
+        .into_iter()
+        .map(|path| path.as_ref().to_string())
+        .collect::<Vec<_>>();
+    keys.sort_unstable();
+    keys.dedup();
+    let mut text = String::new();
+    for key in keys {
+        text.push_str(&key);
+        text.push('\n');
+    }
+    text
+}
+
+/// Asserts that the canonical text form of `paths` equals the content of `golden_file`.
+///
+/// Set the environment variable [`UPDATE_GOLDEN_ENV`] to `1` to create or update the golden file
+/// instead.
+///
+/// # Panics
+///
+/// Panics when the golden file can not be read (or written, when updating) or when its content
+/// differs. The panic message lists all missing and unexpected keys.
+pub fn assert_golden<'p, P: AsRef<S3Path<'p>>>(
+    golden_file: impl AsRef<Path>,
+    paths: impl IntoIterator<Item = P>,
+) {
+    let golden_file = golden_file.as_ref();
+    let actual = to_canonical_text(paths);
+
+    if std::env::var(UPDATE_GOLDEN_ENV).is_ok_and(|it| it == "1") {
+        std::fs::write(golden_file, &actual).unwrap_or_else(|err| {
+            panic!(
+                "Could not write golden file {}: {err}",
+                golden_file.display()
+            )
+        });
+        return;
+    }
+
+    let expected = std::fs::read_to_string(golden_file).unwrap_or_else(|err| {
+        panic!(
+            "Could not read golden file {}: {err}. Run with {UPDATE_GOLDEN_ENV}=1 to create it.",
+            golden_file.display()
+        )
+    });
+    if let Some(diff) = golden_diff(&expected, &actual) {
+        panic!(
+            "Paths differ from golden file {}. Run with {UPDATE_GOLDEN_ENV}=1 to update it.\n{diff}",
+            golden_file.display()
+        );
+    }
+}
+
+/// Lists keys only present in one of the two canonical texts, prefixed with '-' (missing) or
+/// '+' (unexpected). Returns `None` if both are equal.
+fn golden_diff(expected: &str, actual: &str) -> Option<String> {
+    if expected == actual {
+        return None;
+    }
+    let mut expected_lines = expected.lines().peekable();
+    let mut actual_lines = actual.lines().peekable();
+    let mut diff = String::new();
+    loop {
+        let (line, prefix) = match (expected_lines.peek(), actual_lines.peek()) {
+            (None, None) => break,
+            (Some(e), Some(a)) if e == a => {
+                expected_lines.next();
+                actual_lines.next();
+                continue;
+            }
+            (Some(e), Some(a)) if e < a => (expected_lines.next(), '-'),
+            (Some(_), None) => (expected_lines.next(), '-'),
+            (_, Some(_)) => (actual_lines.next(), '+'),
+        };
+        diff.push(prefix);
+        diff.push_str(line.unwrap_or_default());
+        diff.push('\n');
+    }
+    Some(diff)
+}
+
+/// Uploads all entries of `tree` below `prefix` to `store`, e.g. a MinIO or LocalStack container
+/// driven by an integration test. Each object is filled with `meta.size` zero bytes.
+///
+/// Returns the manifest of the uploaded objects, keyed by their full paths. Use a unique `prefix`
+/// per test to share a bucket between tests.
+///
+/// # Errors
+///
+/// Stops at and returns the first error returned by the store.
+pub async fn upload_fixtures<S: ObjectStoreLike>(
+    store: &S,
+    prefix: &S3Path<'_>,
+    tree: &KeyTree,
+) -> Result<Manifest, S::Error> {
+    let mut uploaded = Manifest::new();
+    for (path, meta) in tree.entries() {
+        let path = concat([prefix, path.as_path()]);
+        let size = usize::try_from(meta.size).unwrap_or(usize::MAX);
+        store.put(&path, vec![0; size]).await?;
+        uploaded.insert(path, meta);
+    }
+    Ok(uploaded)
+}
+
+/// Asserts that listing `prefix` in `store` yields exactly the paths of `expected`, e.g. the
+/// manifest returned by [`upload_fixtures`].
+///
+/// # Errors
+///
+/// Returns the error of the store if listing fails.
+///
+/// # Panics
+///
+/// Panics when the listing differs. The panic message lists all missing and unexpected keys.
+pub async fn assert_listing<S: ObjectStoreLike>(
+    store: &S,
+    prefix: &S3Path<'_>,
+    expected: &Manifest,
+) -> Result<(), S::Error> {
+    let listed = store.list(prefix).await?;
+    let expected = to_canonical_text(expected.iter().map(|(path, _)| path));
+    if let Some(diff) = golden_diff(&expected, &to_canonical_text(&listed)) {
+        panic!("Listing of '{prefix}' differs from the expected keys.\n{diff}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::fixtures::KeyTree;
+    use crate::store::{block_on, MemoryBucket};
+    use crate::testing::{
+        assert_golden, assert_listing, component_diff, golden_diff, to_canonical_text,
+        upload_fixtures,
+    };
+    use crate::S3PathBuf;
+    use assertr::prelude::*;
+
+    fn paths(keys: &[&str]) -> Vec<S3PathBuf> {
+        keys.iter()
+            .map(|key| S3PathBuf::try_from_str(key).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn component_diff_is_none_for_equal_paths() {
+        let path = S3PathBuf::try_from(["foo", "bar"]).unwrap();
+        assert_that(component_diff(&path, path.as_path())).is_none();
+    }
+
+    #[test]
+    fn component_diff_reports_extra_components() {
+        let left = S3PathBuf::try_from(["foo"]).unwrap();
+        let right = S3PathBuf::try_from(["foo", "bar"]).unwrap();
+        assert_that(component_diff(&left, &right))
+            .is_some()
+            .is_equal_to(
+                "paths differ at component 1\n\
+                 left:       foo\n\
+                 right:      foo/bar\n\
+                 common:     foo\n\
+                 left only:  \n\
+                 right only: bar\n"
+                    .to_string(),
+            );
+    }
+
+    #[test]
+    #[should_panic = "left only:  baz"]
+    fn assert_s3_path_eq_panics_with_component_diff() {
+        assert_s3_path_eq!(
+            S3PathBuf::try_from(["foo", "baz"]).unwrap(),
+            S3PathBuf::try_from(["foo", "bar"]).unwrap()
+        );
+    }
+
+    #[test]
+    fn canonical_text_is_sorted_and_deduplicated() {
+        let text = to_canonical_text(paths(&["foo/bar", "foo", "foo/bar", "bar"]));
+        assert_that(text).is_equal_to("bar\nfoo\nfoo/bar\n".to_string());
+    }
+
+    #[test]
+    fn golden_diff_lists_missing_and_unexpected_keys() {
+        let diff = golden_diff("a\nb\nc\n", "a\nc\nd\n");
+        assert_that(diff)
+            .is_some()
+            .is_equal_to("-b\n+d\n".to_string());
+        assert_that(golden_diff("a\n", "a\n")).is_none();
+    }
+
+    #[test]
+    fn assert_golden_compares_against_file() {
+        let golden_file = std::env::temp_dir().join(format!(
+            "s3-path-golden-{}-{}.txt",
+            std::process::id(),
+            line!()
+        ));
+        std::fs::write(&golden_file, "bar\nfoo/bar\n").unwrap();
+
+        assert_golden(&golden_file, paths(&["foo/bar", "bar"]));
+        let result = std::panic::catch_unwind(|| assert_golden(&golden_file, paths(&["bar"])));
+
+        std::fs::remove_file(&golden_file).unwrap();
+        assert_that(result.is_err()).is_true();
+    }
+
+    #[test]
+    fn uploads_and_lists_fixtures_below_prefix() {
+        let store = MemoryBucket::new();
+        let prefix = S3PathBuf::try_from_str("tests/upload").unwrap();
+        let tree = KeyTree {
+            fan_out: 2,
+            files_per_directory: 3,
+            ..KeyTree::default()
+        };
+
+        let uploaded = block_on(upload_fixtures(&store, &prefix, &tree)).unwrap();
+        assert_that(uploaded.len()).is_equal_to(12);
+        assert_that(uploaded.iter().all(|(path, _)| path.starts_with(&prefix))).is_true();
+        for (path, meta) in uploaded.iter() {
+            let size = store.object(path).map_or(0, |body| body.len());
+            assert_that(size as u64).is_equal_to(meta.size);
+        }
+
+        assert_that(block_on(assert_listing(&store, &prefix, &uploaded))).is_ok();
+        let other = S3PathBuf::try_from_str("tests/other").unwrap();
+        let result =
+            std::panic::catch_unwind(|| block_on(assert_listing(&store, &other, &uploaded)));
+        assert_that(result.is_err()).is_true();
+    }
+}
@@ -1,30 +1,201 @@
-use crate::error::InvalidS3PathComponent;
+use crate::error::{InvalidComponentKind, InvalidS3PathComponent};
+use crate::telemetry;
+
+/// Maximum length of a key in bytes (Amazon S3).
+pub(crate) const MAX_KEY_LEN: usize = 1024;
+
+/// Lookup table marking the bytes allowed in path components: ascii alphanumeric characters,
+/// '-', '_' and '.'. Non-ascii bytes are never allowed.
+static ALLOWED_BYTES: [bool; 256] = {
+    let mut table = [false; 256];
+    let mut b = 0;
+    while b < 256 {
+        #[allow(clippy::cast_possible_truncation)]
+        let c = b as u8;
+        table[b] = c.is_ascii_alphanumeric() || c == b'-' || c == b'_' || c == b'.';
+        b += 1;
+    }
+    table
+};
+
+/// Returns true if `c` is allowed in path components.
+pub(crate) fn is_allowed_char(c: char) -> bool {
+    u8::try_from(c).is_ok_and(|b| ALLOWED_BYTES[usize::from(b)])
+}
+
+/// Returns the index of the first byte not allowed in path components, if any.
+///
+/// Checks eight bytes at a time using SWAR ("SIMD within a register") range checks on `u64`
+/// words, which works on every target without `unsafe` or target features. Only a failing word,
+/// and the remainder shorter than a word, are scanned using the lookup table.
+fn find_disallowed_byte(bytes: &[u8]) -> Option<usize> {
+    let mut words = bytes.chunks_exact(8);
+    for (i, word) in words.by_ref().enumerate() {
+        let word = u64::from_le_bytes(word.try_into().expect("chunks have 8 bytes"));
+        if !word_is_allowed(word) {
+            return find_disallowed_byte_in_table(&bytes[i * 8..]).map(|index| i * 8 + index);
+        }
+    }
+    let rest = words.remainder();
+    find_disallowed_byte_in_table(rest).map(|index| bytes.len() - rest.len() + index)
+}
+
+/// Returns the index of the first byte not allowed in path components, if any, looking up each
+/// byte in the table.
+fn find_disallowed_byte_in_table(bytes: &[u8]) -> Option<usize> {
+    bytes.iter().position(|b| !ALLOWED_BYTES[usize::from(*b)])
+}
+
+/// Returns true if all eight bytes of `word` are allowed in path components.
+fn word_is_allowed(word: u64) -> bool {
+    const HIGH: u64 = 0x8080_8080_8080_8080;
+
+    /// Sets the high bit of each byte of `word` in `lo..=hi`, for bytes below 0x80. Per-byte sums
+    /// stay below 0x100, so no carry crosses into the next byte.
+    const fn in_range(word: u64, lo: u8, hi: u8) -> u64 {
+        let at_least_lo = word + 0x0101_0101_0101_0101 * (0x80 - lo as u64);
+        let above_hi = word + 0x0101_0101_0101_0101 * (0x7f - hi as u64);
+        at_least_lo & !above_hi & HIGH
+    }
+
+    if word & HIGH != 0 {
+        return false;
+    }
+    let allowed = in_range(word, b'0', b'9')
+        | in_range(word, b'A', b'Z')
+        | in_range(word, b'a', b'z')
+        | in_range(word, b'-', b'.')
+        | in_range(word, b'_', b'_');
+    allowed == HIGH
+}
 
 /// Validates that a path component contains only allowed characters:
 /// alphanumeric characters, hyphens, underscores, and periods.
+///
+/// Failures are reported to the registered [`telemetry`] observer.
 pub(crate) fn validate_component(component: &str) -> Result<(), InvalidS3PathComponent> {
+    check_component(component).map_err(telemetry::report)
+}
+
+/// Like [`validate_component`], but release builds only reject empty and path traversing
+/// components and components containing '/', skipping the scan for other disallowed characters.
+///
+/// Keeping '/' out of every component keeps rendering one-to-one, so that comparisons of rendered
+/// keys stay consistent with comparisons of components. Used by `S3PathBuf::push_trusted`.
+pub(crate) fn validate_trusted_component(component: &str) -> Result<(), InvalidS3PathComponent> {
+    if cfg!(debug_assertions)
+        || component.is_empty()
+        || component == "."
+        || component == ".."
+        || component.contains('/')
+    {
+        validate_component(component)
+    } else {
+        Ok(())
+    }
+}
+
+/// Returns why `component` is invalid, if it is.
+pub(crate) fn find_violation(component: &str) -> Option<InvalidComponentKind> {
     if component.is_empty() {
-        return Err(InvalidS3PathComponent {
-            component: component.to_string(),
-            reason: "Empty component is not allowed".to_string(),
-        });
-    }
-
-    for c in component.chars() {
-        if !c.is_ascii_alphanumeric() && c != '-' && c != '_' && c != '.' {
-            return Err(InvalidS3PathComponent {
-                component: component.to_string(),
-                reason: format!("Character '{c}' is not allowed"),
-            });
-        }
+        return Some(InvalidComponentKind::Empty);
+    }
+
+    if let Some(index) = find_disallowed_byte(component.as_bytes()) {
+        // Every disallowed byte is either ascii or the first byte of a multibyte char, making
+        // `index` a char boundary.
+        let c = component[index..].chars().next().unwrap_or_default();
+        return Some(InvalidComponentKind::DisallowedCharacter(c));
     }
 
     if component == "." || component == ".." {
-        return Err(InvalidS3PathComponent {
-            component: component.to_string(),
-            reason: "Potentially path traversing components are forbidden.".to_string(),
-        });
+        return Some(InvalidComponentKind::PathTraversal);
     }
 
-    Ok(())
+    None
+}
+
+/// Checks `component` like [`validate_component`], but panicking instead of returning an error,
+/// so that literal components can be checked at compile time when called in a const context.
+#[doc(hidden)]
+pub const fn check_literal_component(component: &str) {
+    let bytes = component.as_bytes();
+    if bytes.is_empty() {
+        panic!("empty components are not allowed");
+    }
+    let mut only_dots = true;
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        if !(b.is_ascii_alphanumeric() || b == b'-' || b == b'_' || b == b'.') {
+            panic!("the component contains a character not allowed in keys");
+        }
+        only_dots &= b == b'.';
+        i += 1;
+    }
+    if only_dots && bytes.len() <= 2 {
+        panic!("'.' and '..' are not allowed as components");
+    }
+}
+
+fn check_component(component: &str) -> Result<(), InvalidS3PathComponent> {
+    match find_violation(component) {
+        None => Ok(()),
+        Some(kind) => Err(kind.into_error(component)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::validate_component_fast;
+    use crate::validation::{check_literal_component, find_disallowed_byte, validate_component};
+    use assertr::prelude::*;
+
+    #[test]
+    fn byte_table_matches_allowed_characters() {
+        for b in 0..=u8::MAX {
+            let c = char::from(b);
+            let expected = b.is_ascii() && (c.is_ascii_alphanumeric() || "-_.".contains(c));
+            assert_that(find_disallowed_byte(&[b]).is_none()).is_equal_to(expected);
+            // Checked as part of a whole word.
+            let mut word = [b'a'; 8];
+            word[3] = b;
+            assert_that(find_disallowed_byte(&word)).is_equal_to((!expected).then_some(3));
+        }
+    }
+
+    #[test]
+    fn finds_first_disallowed_byte_in_any_chunk() {
+        let mut bytes = vec![b'a'; 40];
+        assert_that(find_disallowed_byte(&bytes)).is_none();
+        bytes[37] = b'$';
+        bytes[33] = b' ';
+        assert_that(find_disallowed_byte(&bytes))
+            .is_some()
+            .is_equal_to(33);
+    }
+
+    #[test]
+    fn literal_check_agrees_with_validation() {
+        for component in ["foo", "", "fo o", "a/b", "ä", ".", "..", "..."] {
+            let checked = std::panic::catch_unwind(|| check_literal_component(component));
+            assert_that(checked.is_ok()).is_equal_to(validate_component(component).is_ok());
+        }
+    }
+
+    #[test]
+    fn reports_non_ascii_characters() {
+        let err = validate_component("abcdefghijklmnopqrstuvwxyzä").unwrap_err();
+        assert_that(err.reason).is_equal_to("Character 'ä' is not allowed".to_owned());
+    }
+
+    #[test]
+    fn fast_validation_agrees_with_full_errors() {
+        for component in ["foo", "", "fo o", "ä", ".", "..", "..."] {
+            let full = validate_component(component).map_err(|err| err.to_string());
+            let fast = validate_component_fast(component)
+                .map_err(|kind| kind.into_error(component).to_string());
+            assert_that(fast).is_equal_to(full);
+        }
+    }
 }